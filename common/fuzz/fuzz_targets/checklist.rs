@@ -0,0 +1,15 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(text) = std::str::from_utf8(data) {
+        hints_common::fuzzing::checklist(text);
+    }
+});
@@ -0,0 +1,46 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+use imgui::Ui;
+
+use crate::config::{PackConfig, CONFIG_FILENAME};
+use crate::usage::UsageCounts;
+use crate::view::draw_title;
+
+/// Draws the details of the hint pack from the `[pack]` table of `hints.toml`, followed by the
+/// user's usage counts if they are being kept.
+pub(crate) fn draw_about(
+    ui: &Ui,
+    pack: &PackConfig,
+    hint_count: usize,
+    usage_counts: Option<&UsageCounts>,
+) {
+    draw_title(ui, pack.name.as_deref().unwrap_or("About this hint pack"));
+    if pack.is_empty() {
+        ui.text_wrapped(format!(
+            "This hint pack has no details. Its author can add them in a [pack] table in \
+             {CONFIG_FILENAME}."
+        ));
+    }
+    let details = [
+        ("Version", &pack.version),
+        ("Author", &pack.author),
+        ("Aircraft", &pack.aircraft),
+        ("Website", &pack.url),
+    ];
+    for (label, value) in details {
+        if let Some(value) = value {
+            ui.text(format!("{label}: {value}"));
+        }
+    }
+    ui.text(format!("Hints loaded: {hint_count}"));
+    ui.spacing();
+    ui.text_disabled(format!("FLC Hints {}", env!("CARGO_PKG_VERSION")));
+    if let Some(usage_counts) = usage_counts {
+        ui.separator();
+        usage_counts.draw(ui);
+    }
+}
@@ -0,0 +1,89 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+use imgui::{StyleColor, Ui, WindowHoveredFlags};
+
+/// How far image hints are zoomed in when the accessibility mode is turned on, if they are not
+/// zoomed in further already.
+pub(crate) const ACCESSIBLE_ZOOM: f32 = 2.0;
+const FONT_SCALE: f32 = 1.5;
+
+const BLACK: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+const WHITE: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+const YELLOW: [f32; 4] = [1.0, 1.0, 0.0, 1.0];
+const DARK_GREY: [f32; 4] = [0.2, 0.2, 0.2, 1.0];
+const LIGHT_GREY: [f32; 4] = [0.8, 0.8, 0.8, 1.0];
+
+const CURSOR_RADIUS: f32 = 18.0;
+const CURSOR_OUTLINE: f32 = 6.0;
+const CURSOR_THICKNESS: f32 = 3.0;
+
+/// Draws the contents of the current window with `f` using a high-contrast theme of white and
+/// yellow on black, with larger text, for users with low vision.
+pub(crate) fn draw_high_contrast<F: FnOnce()>(ui: &Ui, f: F) {
+    let pos = ui.window_pos();
+    let size = ui.window_size();
+    // the window's own background was drawn when it began, so cover it
+    ui.get_window_draw_list()
+        .add_rect(pos, [pos[0] + size[0], pos[1] + size[1]], BLACK)
+        .filled(true)
+        .build();
+    ui.set_window_font_scale(FONT_SCALE);
+    let _colours = [
+        (StyleColor::Text, WHITE),
+        (StyleColor::TextDisabled, LIGHT_GREY),
+        (StyleColor::ChildBg, BLACK),
+        (StyleColor::PopupBg, BLACK),
+        (StyleColor::Border, WHITE),
+        (StyleColor::Separator, WHITE),
+        (StyleColor::FrameBg, DARK_GREY),
+        (StyleColor::FrameBgHovered, DARK_GREY),
+        (StyleColor::FrameBgActive, DARK_GREY),
+        (StyleColor::Button, DARK_GREY),
+        (StyleColor::ButtonHovered, DARK_GREY),
+        (StyleColor::ButtonActive, DARK_GREY),
+        (StyleColor::Header, DARK_GREY),
+        (StyleColor::HeaderHovered, DARK_GREY),
+        (StyleColor::HeaderActive, DARK_GREY),
+        (StyleColor::CheckMark, YELLOW),
+        (StyleColor::SliderGrab, YELLOW),
+        (StyleColor::SliderGrabActive, YELLOW),
+    ]
+    .map(|(style_color, colour)| ui.push_style_color(style_color, colour));
+    f();
+}
+
+/// Draws a large ring and crosshair at the mouse cursor while it is over the current window, in
+/// yellow outlined with black so that it can be seen on any hint.
+pub(crate) fn draw_large_cursor(ui: &Ui) {
+    if !ui.is_window_hovered_with_flags(WindowHoveredFlags::CHILD_WINDOWS) {
+        return;
+    }
+    let [x, y] = ui.io().mouse_pos;
+    let draw_list = ui.get_foreground_draw_list();
+    for (colour, thickness) in [(BLACK, CURSOR_OUTLINE), (YELLOW, CURSOR_THICKNESS)] {
+        draw_list
+            .add_circle([x, y], CURSOR_RADIUS, colour)
+            .thickness(thickness)
+            .build();
+        draw_list
+            .add_line(
+                [x - CURSOR_RADIUS * 1.5, y],
+                [x + CURSOR_RADIUS * 1.5, y],
+                colour,
+            )
+            .thickness(thickness)
+            .build();
+        draw_list
+            .add_line(
+                [x, y - CURSOR_RADIUS * 1.5],
+                [x, y + CURSOR_RADIUS * 1.5],
+                colour,
+            )
+            .thickness(thickness)
+            .build();
+    }
+}
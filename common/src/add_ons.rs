@@ -0,0 +1,108 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+//! Hints that are only shown with, or without, an add-on such as a replacement GPS, so that a
+//! pack can adapt to the user's avionics. Add-ons are detected by the signature of their plugin
+//! or by a dataref they create.
+
+use serde::Deserialize;
+
+/// Detects the add-ons installed in the sim.
+pub trait AddOns {
+    /// Whether a plugin with `signature`, such as `"RealityXP.GTN.750"`, is loaded.
+    fn has_plugin(&self, signature: &str) -> bool;
+    /// Whether the dataref `name` exists.
+    fn has_dataref(&self, name: &str) -> bool;
+}
+
+/// [`AddOns`] for where there is no sim, which detects none.
+pub struct NoAddOns;
+
+impl AddOns for NoAddOns {
+    fn has_plugin(&self, _signature: &str) -> bool {
+        false
+    }
+
+    fn has_dataref(&self, _name: &str) -> bool {
+        false
+    }
+}
+
+/// The add-ons a hint needs, or needs to be absent, to be shown, set in `hints.toml`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct AddOnCondition {
+    /// The signature of a plugin that must be loaded.
+    pub if_plugin: Option<String>,
+    /// The signature of a plugin that must not be loaded, such as for the stock avionics that it
+    /// replaces.
+    pub unless_plugin: Option<String>,
+    /// A dataref that must exist.
+    pub if_dataref: Option<String>,
+    /// A dataref that must not exist.
+    pub unless_dataref: Option<String>,
+}
+
+impl AddOnCondition {
+    pub(crate) fn is_met(&self, add_ons: &dyn AddOns) -> bool {
+        self.if_plugin
+            .as_deref()
+            .map_or(true, |signature| add_ons.has_plugin(signature))
+            && self
+                .unless_plugin
+                .as_deref()
+                .map_or(true, |signature| !add_ons.has_plugin(signature))
+            && self
+                .if_dataref
+                .as_deref()
+                .map_or(true, |name| add_ons.has_dataref(name))
+            && self
+                .unless_dataref
+                .as_deref()
+                .map_or(true, |name| !add_ons.has_dataref(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Installed;
+
+    impl AddOns for Installed {
+        fn has_plugin(&self, signature: &str) -> bool {
+            signature == "RealityXP.GTN.750"
+        }
+
+        fn has_dataref(&self, name: &str) -> bool {
+            name == "RXP/GTN/page"
+        }
+    }
+
+    fn condition(toml: &str) -> AddOnCondition {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn checks_for_add_ons() {
+        assert!(condition("").is_met(&Installed));
+        assert!(condition("if_plugin = 'RealityXP.GTN.750'").is_met(&Installed));
+        assert!(!condition("unless_plugin = 'RealityXP.GTN.750'").is_met(&Installed));
+        assert!(!condition("if_plugin = 'RealityXP.GTN.650'").is_met(&Installed));
+        assert!(condition("if_dataref = 'RXP/GTN/page'").is_met(&Installed));
+        assert!(condition("unless_dataref = 'TDS/GTNXI/page'").is_met(&Installed));
+        assert!(
+            !condition("if_plugin = 'RealityXP.GTN.750'\nunless_dataref = 'RXP/GTN/page'")
+                .is_met(&Installed)
+        );
+    }
+
+    #[test]
+    fn detects_nothing_without_a_sim() {
+        assert!(!condition("if_plugin = 'RealityXP.GTN.750'").is_met(&NoAddOns));
+        assert!(condition("unless_plugin = 'RealityXP.GTN.750'").is_met(&NoAddOns));
+    }
+}
@@ -0,0 +1,169 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use image::RgbaImage;
+use imgui::Ui;
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+
+use crate::documented::TomlDocs;
+use crate::error::{read_toml_file, write_toml_file};
+
+const ADJUSTMENTS_FILENAME: &str = "adjustments.toml";
+const ADJUSTMENTS_DOCS: TomlDocs = TomlDocs {
+    header:
+        "Brightness, contrast and gamma adjustments for each hint, keyed by the hint's filename.
+Hints without an entry are shown unadjusted. Each entry may have:
+  brightness: added to each colour, from -1.0 to 1.0 (default 0.0)
+  contrast: multiplies the difference from mid-grey, from 0.0 to 3.0 (default 1.0)
+  gamma: from 0.2 to 5.0; above 1.0 brightens the mid-tones (default 1.0)",
+    fields: &[],
+};
+
+/// Brightness, contrast and gamma corrections applied to a hint's pixels before it is uploaded as
+/// a texture.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Adjustments {
+    pub brightness: f32,
+    pub contrast: f32,
+    pub gamma: f32,
+}
+
+impl Default for Adjustments {
+    fn default() -> Self {
+        Adjustments {
+            brightness: 0.0,
+            contrast: 1.0,
+            gamma: 1.0,
+        }
+    }
+}
+
+impl Adjustments {
+    pub fn is_identity(&self) -> bool {
+        *self == Adjustments::default()
+    }
+
+    /// Returns a copy of `image` with the adjustments applied to the colour channels, or the image
+    /// itself if there is nothing to adjust.
+    pub fn apply<'a>(&self, image: &'a RgbaImage) -> Cow<'a, RgbaImage> {
+        if self.is_identity() {
+            return Cow::Borrowed(image);
+        }
+        let lut = self.lookup_table();
+        let mut adjusted = image.clone();
+        for pixel in adjusted.pixels_mut() {
+            for channel in &mut pixel.0[..3] {
+                *channel = lut[usize::from(*channel)];
+            }
+        }
+        Cow::Owned(adjusted)
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn lookup_table(&self) -> [u8; 256] {
+        let mut lut = [0; 256];
+        for (value, entry) in (0u8..=255).zip(lut.iter_mut()) {
+            let v = f32::from(value) / 255.0;
+            let v = ((v - 0.5) * self.contrast + 0.5 + self.brightness).clamp(0.0, 1.0);
+            let v = v.powf(1.0 / self.gamma);
+            *entry = (v * 255.0).round() as u8;
+        }
+        lut
+    }
+
+    /// Draws sliders for each adjustment, returning `true` once the user has finished editing.
+    pub fn draw_controls(&mut self, ui: &Ui) -> bool {
+        let mut finished = false;
+        ui.slider("Brightness", -1.0, 1.0, &mut self.brightness);
+        finished |= ui.is_item_deactivated_after_edit();
+        ui.slider("Contrast", 0.0, 3.0, &mut self.contrast);
+        finished |= ui.is_item_deactivated_after_edit();
+        ui.slider("Gamma", 0.2, 5.0, &mut self.gamma);
+        finished |= ui.is_item_deactivated_after_edit();
+        if ui.button("Reset adjustments") {
+            *self = Adjustments::default();
+            finished = true;
+        }
+        finished
+    }
+}
+
+/// A colour transform applied to every hint, for example to preserve night vision.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DisplayMode {
+    #[default]
+    Normal,
+    Grayscale,
+    RedLight,
+}
+
+impl DisplayMode {
+    #[must_use]
+    pub fn next(self) -> Self {
+        match self {
+            DisplayMode::Normal => DisplayMode::Grayscale,
+            DisplayMode::Grayscale => DisplayMode::RedLight,
+            DisplayMode::RedLight => DisplayMode::Normal,
+        }
+    }
+
+    /// Returns `image` converted to luminance, either as grey or in the red channel only.
+    pub fn apply<'a>(self, image: Cow<'a, RgbaImage>) -> Cow<'a, RgbaImage> {
+        if self == DisplayMode::Normal {
+            return image;
+        }
+        let mut transformed = image.into_owned();
+        for pixel in transformed.pixels_mut() {
+            let [r, g, b, _] = pixel.0;
+            let luma = luminance(r, g, b);
+            let (g, b) = if self == DisplayMode::RedLight {
+                (0, 0)
+            } else {
+                (luma, luma)
+            };
+            pixel.0[..3].copy_from_slice(&[luma, g, b]);
+        }
+        Cow::Owned(transformed)
+    }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn luminance(r: u8, g: u8, b: u8) -> u8 {
+    (0.299 * f32::from(r) + 0.587 * f32::from(g) + 0.114 * f32::from(b)).round() as u8
+}
+
+/// Loads the per-hint adjustments saved in `dir`, keyed by hint filename.
+pub fn load_adjustments(dir: &Path) -> BTreeMap<String, Adjustments> {
+    let filename = dir.join(ADJUSTMENTS_FILENAME);
+    if !filename.is_file() {
+        return BTreeMap::new();
+    }
+    read_toml_file(&filename).unwrap_or_else(|e| {
+        error!(
+            code = e.code(),
+            "Unable to load hint adjustments from {filename:?}: {e}"
+        );
+        BTreeMap::new()
+    })
+}
+
+pub fn save_adjustments(dir: &Path, adjustments: &BTreeMap<String, Adjustments>) {
+    let filename = dir.join(ADJUSTMENTS_FILENAME);
+    let adjustments: BTreeMap<_, _> = adjustments
+        .iter()
+        .filter(|(_, adjustments)| !adjustments.is_identity())
+        .collect();
+    match write_toml_file(&filename, &adjustments, &ADJUSTMENTS_DOCS) {
+        Ok(()) => info!("Saved hint adjustments to {filename:?}"),
+        Err(e) => error!(code = e.code(), "Unable to save hint adjustments: {e}"),
+    }
+}
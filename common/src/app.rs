@@ -4,41 +4,276 @@
  * All rights reserved.
  */
 
+use std::cell::{Cell, RefCell};
 use std::cmp::Ordering;
-use std::error::Error;
-use std::path::PathBuf;
-use std::sync::{Arc, Mutex};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::sync::{Arc, Mutex, PoisonError, TryLockError};
 
-use imgui::{Image, Key, Ui};
+use imgui::{Key, MouseButton, TextureId, Ui};
 use imgui_support::events::{Action, Event};
 use imgui_support::App;
-use tracing::{info, trace, warn};
+use tracing::{error, info, trace, warn};
 
+use crate::about::draw_about;
+use crate::accessibility::{draw_high_contrast, draw_large_cursor, ACCESSIBLE_ZOOM};
+use crate::add_ons::{AddOns, NoAddOns};
+use crate::adjustments::{load_adjustments, save_adjustments, Adjustments, DisplayMode};
+use crate::charts::{fetch_charts, ChartProvider, FetchedCharts};
+use crate::checklist::ChecklistProgress;
+use crate::clipboard::{copy_image, copy_text};
 use crate::concurrent::thread_loader;
-use crate::hints::Hint;
-use crate::ConfigError;
+use crate::config::{apply_legacy_order, load_config, Config, CONFIG_FILENAME};
+use crate::contents::{
+    category_starts, contents_sections, draw_contents, hint_title, CONTENTS_NAME,
+};
+use crate::conversions::draw_conversions;
+use crate::diagnose::Diagnosis;
+use crate::error::HintsError;
+use crate::flow::{draw_step_highlight, draw_step_text, next_step};
+use crate::hints::{check_in_hints_dir, is_hint_file, Hint, HintContent, Image, ImageSource};
+use crate::history::record_event;
+use crate::import::ImportWizard;
+use crate::index::{load_index, Index};
+use crate::language::{select_language, untranslated_name};
+use crate::library::overlay;
+use crate::links::draw_links;
+use crate::migrate::migrate_settings;
+use crate::navdata::{NavData, NavPanel};
+use crate::overview::{draw_overview, Thumbnail};
+use crate::panel_state::{draw_panel_state, draw_summary};
+use crate::panic::catch_panic;
+use crate::pdf::PDF_EXTENSION;
+use crate::prefetch::{Prefetcher, Request};
+use crate::schedule::{SimDate, Validity};
+use crate::snapshot::render;
+use crate::speech::Speaker;
+use crate::template::{DatarefReader, NoDatarefs};
+use crate::usage::{UsageCounts, UsageLog};
+use crate::variants::{draw_variant_chooser, is_shown, load_variants, save_variants};
+use crate::view::{draw_image, draw_with_background, Viewport, HEADING_COLOUR, WARNING_COLOUR};
+use crate::watch::Watcher;
+use crate::watermark::{draw_watermark, DEFAULT_WATERMARK_COLOUR, EXPIRED_WATERMARK};
+
+const PANE_SPACING: f32 = 4.0;
+/// How many hints the coarse paging events turn.
+const COARSE_PAGE_STEP: usize = 10;
+/// How many hints either side of the shown ones are kept decoded.
+const IMAGE_LOOK_AHEAD: usize = 2;
+const ERROR_COLOUR: [f32; 4] = [1.0, 0.3, 0.3, 1.0];
+/// The texture of an image hint rendered by [`Hints::save_snapshot`].
+const SNAPSHOT_TEXTURE: TextureId = TextureId::new(1);
+
+/// What the app is created with, which is set before the hints are first loaded so that they
+/// are loaded once.
+#[derive(Default)]
+pub struct HintsOptions {
+    /// Where the user's settings, such as hint adjustments and calculator inputs, are saved, if
+    /// not alongside the hints.
+    pub settings_dir: Option<PathBuf>,
+    /// The library of hints kept outside the aircraft, such as for an aircraft installed by
+    /// Steam, whose directory can't be written to. Its hints are shown along with those in the
+    /// hints directory, in place of any with the same name.
+    pub library: Option<PathBuf>,
+    /// Whether the hints directory can't be written to, so that hints created from the
+    /// aircraft's documentation are put in the library.
+    pub read_only: bool,
+    /// The sim's language, such as `"de"`, whose translations of the hints are shown unless the
+    /// hints config sets another.
+    pub language: Option<String>,
+    /// What detects the add-ons installed in the sim, or `None` to detect none.
+    pub add_ons: Option<Box<dyn AddOns>>,
+    /// Whether to start in safe mode, so that users can tell whether a problem is caused by one of
+    /// the parts of the app that it turns off. The hints directory isn't watched, every hint is
+    /// shown whatever variants and add-ons it is for except PDFs, which aren't rendered, and only
+    /// the images of the hints shown are decoded, with no thumbnails in the overview.
+    pub safe_mode: bool,
+}
 
 pub struct Hints {
     path: PathBuf,
-    hints: Arc<Mutex<Vec<Hint>>>,
+    settings_dir: PathBuf,
+    /// The library of hints kept outside the aircraft, if it has one, whose hints are shown along
+    /// with those in `path` and which hints are created in if `path` is read-only.
+    library: Option<PathBuf>,
+    /// Whether `path` can't be written to.
+    read_only: bool,
+    config: Config,
+    /// The hints listed in `index.toml`, if there is one, which are shown in its order.
+    index: Option<Index>,
+    loaded: Arc<Mutex<Vec<Hint>>>,
+    /// The names of the hint files that hints couldn't be created from since the hints were last
+    /// reloaded.
+    failed: Arc<Mutex<Vec<String>>>,
+    hints: RefCell<Vec<Rc<Hint>>>,
+    /// Hints made from charts, shown after the aircraft's hints and kept when they are reloaded.
+    charts: Vec<Rc<Hint>>,
+    chart_provider: Option<Arc<dyn ChartProvider>>,
+    chart_request: Option<Receiver<FetchedCharts>>,
+    /// Airports the user has asked for charts for, which are fetched on the next update.
+    chart_airports: RefCell<Option<Vec<String>>>,
     current_hint_idx: usize,
+    viewport: Cell<Viewport>,
+    compare_hint_idx: Option<usize>,
+    compare_viewport: Cell<Viewport>,
+    active_pane: Cell<Pane>,
+    adjustments: RefCell<BTreeMap<String, Adjustments>>,
+    /// The hints with adjustments but no file that have been warned of, so that reloading doesn't
+    /// warn of them again.
+    logged_orphans: BTreeSet<String>,
+    show_adjustments: bool,
+    show_conversions: bool,
+    show_variants: bool,
+    /// The variant of the aircraft chosen from each group of variants in the hints config.
+    variants: RefCell<BTreeMap<String, String>>,
+    conversion_value: Cell<f32>,
+    show_nav_panel: bool,
+    show_about: bool,
+    show_overview: bool,
+    /// The hint clicked in the overview, which is gone to on the next update.
+    overview_pending: Cell<Option<usize>>,
+    /// Whether the window is shrunk to a thumbnail of the current hint, until it is clicked.
+    mini_mode: Cell<bool>,
+    /// The hint whose guided flow has been started and the step of it that is highlighted.
+    flow_step: Option<(String, usize)>,
+    import_wizard: RefCell<Option<ImportWizard>>,
+    nav_panel: NavPanel,
+    nav_data: Option<Box<dyn NavData>>,
+    text_input_active: Cell<bool>,
+    ctrl_held: Cell<bool>,
+    display_mode: DisplayMode,
+    accessible: bool,
+    error: RefCell<Option<String>>,
+    reload_pending: Cell<bool>,
+    /// The names of the current and compared hints to go back to once they have loaded again,
+    /// after the hints changed on disk.
+    restore_pending: (Option<String>, Option<String>),
+    /// The sim's language, whose translations of the hints are shown unless the hints config sets
+    /// another.
+    language: Option<String>,
+    /// The hint and viewport to return to when the peek command is released, while it is held.
+    peek_return: Option<(usize, Viewport)>,
+    /// The hint to go to from a link clicked while drawing, which is gone to on the next update.
+    link_pending: RefCell<Option<String>>,
+    datarefs: Box<dyn DatarefReader>,
+    add_ons: Box<dyn AddOns>,
+    speaker: Option<Box<dyn Speaker>>,
+    usage_log: RefCell<Option<UsageLog>>,
+    usage_counts: RefCell<Option<UsageCounts>>,
+    /// The status of services set up outside the app, such as syncing, for the diagnosis.
+    services: BTreeMap<String, String>,
+    /// The result of the last self-check, shown until it is closed.
+    diagnosis: RefCell<Option<Diagnosis>>,
+    prefetcher: RefCell<Prefetcher>,
+    /// Watches the hints directory, so that changes to the hints are shown without a reload
+    /// command.
+    watcher: Option<Watcher>,
+    /// Whether the app is in safe mode, for troubleshooting.
+    safe_mode: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Pane {
+    #[default]
+    Current,
+    Compare,
 }
 
 impl Hints {
     /// # Errors
     ///
     /// Returns an error if the config file cannot be found or parsed.
-    pub fn new(path: PathBuf) -> Result<Self, Box<dyn Error>> {
+    pub fn new(path: PathBuf) -> Result<Self, HintsError> {
+        Self::with_options(path, HintsOptions::default())
+    }
+
+    /// Creates the app for the hints in `path` with `options`, which are applied before the hints
+    /// are loaded.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the config file cannot be found or parsed.
+    pub fn with_options(path: PathBuf, options: HintsOptions) -> Result<Self, HintsError> {
         if !path.is_dir() {
-            return Err(Box::new(ConfigError::new(format!(
+            return Err(HintsError::Config(format!(
                 "{} is not a directory",
                 path.display()
-            ))));
+            )));
+        }
+        let mut watcher = if options.safe_mode {
+            None
+        } else {
+            match Watcher::new(&path) {
+                Ok(watcher) => Some(watcher),
+                Err(e) => {
+                    info!(code = e.code(), "{e}");
+                    None
+                }
+            }
+        };
+        if let (Some(watcher), Some(library)) = (&mut watcher, &options.library) {
+            if let Err(e) = watcher.watch(library) {
+                warn!(code = e.code(), "{e}");
+            }
         }
+        let settings_dir = options.settings_dir.unwrap_or_else(|| path.clone());
         let mut hints = Hints {
             path,
-            hints: Arc::new(Mutex::new(vec![])),
+            settings_dir,
+            library: options.library,
+            read_only: options.read_only,
+            config: Config::default(),
+            index: None,
+            loaded: Arc::new(Mutex::new(vec![])),
+            failed: Arc::new(Mutex::new(vec![])),
+            hints: RefCell::new(vec![]),
+            charts: vec![],
+            chart_provider: None,
+            chart_request: None,
+            chart_airports: RefCell::new(None),
             current_hint_idx: 0,
+            viewport: Cell::new(Viewport::default()),
+            compare_hint_idx: None,
+            compare_viewport: Cell::new(Viewport::default()),
+            active_pane: Cell::new(Pane::Current),
+            adjustments: RefCell::new(BTreeMap::new()),
+            logged_orphans: BTreeSet::new(),
+            show_adjustments: false,
+            show_conversions: false,
+            show_variants: false,
+            variants: RefCell::new(BTreeMap::new()),
+            conversion_value: Cell::new(0.0),
+            show_nav_panel: false,
+            show_about: false,
+            show_overview: false,
+            overview_pending: Cell::new(None),
+            mini_mode: Cell::new(false),
+            flow_step: None,
+            import_wizard: RefCell::new(None),
+            nav_panel: NavPanel::default(),
+            nav_data: None,
+            text_input_active: Cell::new(false),
+            ctrl_held: Cell::new(false),
+            display_mode: DisplayMode::default(),
+            accessible: false,
+            error: RefCell::new(None),
+            reload_pending: Cell::new(false),
+            restore_pending: (None, None),
+            language: options.language,
+            peek_return: None,
+            link_pending: RefCell::new(None),
+            datarefs: Box::new(NoDatarefs),
+            add_ons: options.add_ons.unwrap_or_else(|| Box::new(NoAddOns)),
+            speaker: None,
+            usage_log: RefCell::new(None),
+            usage_counts: RefCell::new(None),
+            services: BTreeMap::new(),
+            diagnosis: RefCell::new(None),
+            prefetcher: RefCell::new(Prefetcher::new()),
+            watcher,
+            safe_mode: options.safe_mode,
         };
         hints.reload();
         Ok(hints)
@@ -47,24 +282,75 @@ impl Hints {
     pub fn reload(&mut self) {
         info!("Loading hints from {:?}", self.path);
         self.current_hint_idx = 0;
-        self.hints.lock().unwrap().clear();
-        let thread_hints = Arc::clone(&self.hints);
+        self.viewport.take();
+        self.compare_hint_idx = None;
+        self.restore_pending = (None, None);
+        self.flow_step = None;
+        self.peek_return = None;
+        self.error.take();
+        self.config = load_config(&self.path);
+        self.index = load_index(&self.path);
+        if !self.config.usage.log {
+            self.usage_log.take();
+        } else if self.usage_log.borrow().is_none() {
+            // the log covers the whole flight, so it is kept when hints are reloaded
+            self.usage_log
+                .replace(Some(UsageLog::new(&self.settings_dir)));
+        }
+        if !self.config.usage.count {
+            self.usage_counts.take();
+        } else if self.usage_counts.borrow().is_none() {
+            self.usage_counts
+                .replace(Some(UsageCounts::load(&self.settings_dir)));
+        }
+        self.adjustments
+            .replace(load_adjustments(&self.settings_dir));
+        self.migrate_adjustments();
+        self.variants.replace(load_variants(&self.settings_dir));
+        self.loaded
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+        self.hints.borrow_mut().clear();
+        self.failed
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+        if let Err(e) = self.config.check_version() {
+            warn!(code = e.code(), "Not loading hints: {e}");
+            self.show_error(&e);
+            return;
+        }
+        if self.config.contents.page {
+            self.hints.borrow_mut().push(Rc::new(Hint::contents()));
+        }
+        let thread_hints = Arc::clone(&self.loaded);
+        let thread_failed = Arc::clone(&self.failed);
+        let settings_dir = self.settings_dir.clone();
         let (tx, _) = thread_loader(false, move |image_path: PathBuf| {
-            match Hint::new(&image_path) {
-                Ok(hint) => match thread_hints.lock() {
-                    Ok(mut hints) => hints.push(hint),
-                    Err(e) => warn!(error=%e, "Unable to lock hints"),
-                },
-                Err(e) => warn!("Unable to create hint from {image_path:?}: {e}"),
+            match catch_panic("loader", || Hint::load_all(&image_path, &settings_dir)) {
+                Ok(Ok(hints)) => {
+                    thread_hints
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .extend(hints);
+                    return;
+                }
+                Ok(Err(e)) => {
+                    warn!(
+                        code = e.code(),
+                        "Unable to create hint from {image_path:?}: {e}"
+                    );
+                }
+                Err(_) => warn!("Unable to create hint from {image_path:?}"),
             };
+            thread_failed
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(untranslated_name(&image_path));
         });
 
-        let mut files = std::fs::read_dir(&self.path)
-            .unwrap()
-            .map(|res| res.map(|e| e.path()))
-            .collect::<Result<Vec<_>, std::io::Error>>()
-            .unwrap();
-        files.sort();
+        let files = self.shown_hint_files();
         if files.is_empty() {
             warn!("No files found in {:?}", self.path);
         }
@@ -74,40 +360,862 @@ impl Hints {
         drop(tx);
     }
 
-    fn deallocate_current_texture(&self, hints: &[Hint]) {
-        if let Some(current_hint) = hints.get(self.current_hint_idx) {
-            current_hint.deallocate_texture();
+    /// Moves the adjustments for hints that were renamed by an update of the pack to the hints of
+    /// the same title, and warns once of any that no longer match a hint file. Every file on disk
+    /// counts, so that the adjustments for hints left out by the index or shown in another
+    /// language aren't moved.
+    fn migrate_adjustments(&mut self) {
+        let mut names: Vec<String> = std::iter::once(&self.path)
+            .chain(&self.library)
+            .flat_map(|dir| files_on_disk(dir))
+            .filter(|file| is_hint_file(file))
+            .map(|file| untranslated_name(&file))
+            .collect();
+        names.sort();
+        names.dedup();
+        let mut adjustments = self.adjustments.borrow_mut();
+        let migration = migrate_settings(&mut *adjustments, &names);
+        for (old, new) in &migration.moved {
+            info!("Moved the adjustments for {old:?} to {new:?}");
+        }
+        for orphan in migration.orphans {
+            if !self.logged_orphans.contains(&orphan) {
+                warn!("Keeping the adjustments for {orphan:?}, which is no longer in the hints");
+                self.logged_orphans.insert(orphan);
+            }
+        }
+        if !migration.moved.is_empty() {
+            save_adjustments(&self.settings_dir, &adjustments);
+        }
+    }
+
+    /// The hint files in the hints directory and library, in the order they are shown.
+    fn hint_files(&self) -> Vec<PathBuf> {
+        let language = self.language();
+        let files = list_hint_files(&self.path, language.as_deref(), self.index.as_ref());
+        match &self.library {
+            Some(library) => overlay(files, list_hint_files(library, language.as_deref(), None)),
+            None => files,
+        }
+    }
+
+    /// The directory to create hints in: the hints directory, unless it is read-only and there is
+    /// a library.
+    fn writable_dir(&self) -> &Path {
+        match &self.library {
+            Some(library) if self.read_only => library,
+            _ => &self.path,
+        }
+    }
+
+    /// The files of the hints to show, in their order, leaving out those for other variants of the
+    /// aircraft or for add-ons that aren't installed unless in safe mode, when only PDFs are left
+    /// out.
+    fn shown_hint_files(&self) -> Vec<PathBuf> {
+        let mut files = self.hint_files();
+        if self.safe_mode {
+            files.retain(|file| {
+                !file
+                    .extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case(PDF_EXTENSION))
+            });
+            return files;
+        }
+        let chosen = self.variants.borrow();
+        files.retain(
+            |file| match self.config.hints.get(&untranslated_name(file)) {
+                Some(hint_config) => {
+                    is_shown(&hint_config.variants, &self.config.variants, &chosen)
+                        && hint_config.add_ons.is_met(self.add_ons.as_ref())
+                }
+                None => true,
+            },
+        );
+        files
+    }
+
+    /// The language of the hints to show, as set in the hints config or else the sim's.
+    fn language(&self) -> Option<String> {
+        self.config
+            .language
+            .as_ref()
+            .or(self.language.as_ref())
+            .map(|language| language.to_lowercase())
+    }
+
+    /// Sets the status of a service set up outside the app, such as `"Sync server"`, to report
+    /// when the hints are diagnosed.
+    pub fn set_service_status(&mut self, service: &str, status: &str) {
+        self.services
+            .insert(service.to_string(), status.to_string());
+    }
+
+    /// Sets where the values of dataref placeholders in text hints are read from.
+    pub fn set_dataref_reader(&mut self, datarefs: Box<dyn DatarefReader>) {
+        self.datarefs = datarefs;
+    }
+
+    /// Sets what speaks text hints aloud.
+    pub fn set_speaker(&mut self, speaker: Box<dyn Speaker>) {
+        self.speaker = Some(speaker);
+    }
+
+    /// Sets where charts for the airports in the flight plan are fetched from.
+    pub fn set_chart_provider(&mut self, provider: Arc<dyn ChartProvider>) {
+        self.chart_provider = Some(provider);
+    }
+
+    /// Sets where the navigation panel looks up airports.
+    pub fn set_nav_data(&mut self, nav_data: Box<dyn NavData>) {
+        self.nav_data = Some(nav_data);
+    }
+
+    /// Shows the wizard that creates hints from the documentation of the aircraft in
+    /// `aircraft_dir`, in place of the hints until it is closed.
+    pub fn start_import(&mut self, aircraft_dir: &Path) {
+        self.import_wizard
+            .replace(Some(ImportWizard::new(aircraft_dir, self.writable_dir())));
+    }
+
+    /// Performs any work that is pending from earlier errors, reloads the hints if they have
+    /// changed on disk, keeping the same ones shown, and adds any charts that have been fetched.
+    /// Call regularly on the main thread.
+    pub fn update(&mut self) {
+        let mut restore = None;
+        if self.watcher.as_mut().is_some_and(Watcher::changed) {
+            info!("Hints have changed on disk");
+            let name = |idx| self.hint(idx).map(|hint| hint.name().to_string());
+            let compare = self.compare_hint_idx.and_then(name);
+            restore = Some((name(self.current_hint_idx), compare));
+            self.reload_pending.set(true);
+        }
+        if self.reload_pending.take() {
+            self.reload();
+            if let Some(restore) = restore {
+                self.restore_pending = restore;
+            }
+        }
+        self.restore_shown_hints();
+        if let Some(name) = self.link_pending.take() {
+            self.follow_link(&name);
+        }
+        if let Some(idx) = self.overview_pending.take() {
+            self.go_to_hint(idx);
+            self.close_overview();
+        }
+        if let Some(airports) = self.chart_airports.take() {
+            self.show_charts(&airports);
+        }
+        self.collect_charts();
+        self.keep_nearby_images();
+    }
+
+    /// Keeps the images of the hints near the current and compared ones decoded, so that turning
+    /// to them doesn't wait for them to be decoded, and releases the rest. Images are decoded in
+    /// the background, nearest first, so the hints shown are decoded before any others. While the
+    /// overview is shown, thumbnails are made instead. In safe mode only the images of the hints
+    /// shown are kept.
+    fn keep_nearby_images(&self) {
+        let count = self.hint_count();
+        let mut prefetcher = self.prefetcher.borrow_mut();
+        if let Some((request, decoded)) = prefetcher.decoded() {
+            if let Some(hint) = (0..count)
+                .filter_map(|idx| self.hint(idx))
+                .find(|hint| hint.is_requested_by(&request))
+            {
+                hint.prefetched(&request, decoded);
+            }
+        }
+        if self.show_overview {
+            if !self.safe_mode && prefetcher.is_idle() {
+                if let Some(source) = self.nearest_unthumbnailed_source(count) {
+                    prefetcher.request(Request::Thumbnail(source));
+                }
+            }
+            return;
+        }
+        let shown: Vec<usize> = [Some(self.current_hint_idx), self.compare_hint_idx]
+            .into_iter()
+            .flatten()
+            .collect();
+        let mut nearest: Option<(usize, ImageSource)> = None;
+        let look_ahead = if self.safe_mode { 0 } else { IMAGE_LOOK_AHEAD };
+        for idx in 0..count {
+            let hint = match self.hint(idx) {
+                Some(hint) => hint,
+                None => continue,
+            };
+            let distance = shown
+                .iter()
+                .map(|&shown| {
+                    let distance = idx.abs_diff(shown);
+                    distance.min(count - distance)
+                })
+                .min()
+                .unwrap_or(usize::MAX);
+            if distance > look_ahead {
+                hint.unload();
+                continue;
+            }
+            let source = match hint.unloaded_source() {
+                Some(source) => source,
+                None => continue,
+            };
+            if !nearest.as_ref().is_some_and(|(d, _)| *d <= distance) {
+                nearest = Some((distance, source.clone()));
+            }
+        }
+        if let (true, Some((_, source))) = (prefetcher.is_idle(), nearest) {
+            prefetcher.request(Request::Image(source));
+        }
+    }
+
+    /// The source of the image hint nearest the current one that has no thumbnail yet, counting
+    /// forward, so that the thumbnails on screen when the overview opens are made first.
+    fn nearest_unthumbnailed_source(&self, count: usize) -> Option<ImageSource> {
+        (0..count)
+            .map(|offset| (self.current_hint_idx + offset) % count)
+            .filter_map(|idx| self.hint(idx))
+            .find_map(|hint| hint.unthumbnailed_source().cloned())
+    }
+
+    /// Shows the peek hint set in the hints config, remembering the current hint to return to.
+    fn start_peek(&mut self) {
+        let name = match &self.config.peek {
+            Some(name) => name.clone(),
+            None => {
+                warn!("No peek hint is set in {CONFIG_FILENAME}");
+                return;
+            }
+        };
+        match self.hint_idx(&name) {
+            Some(idx) => {
+                if self.peek_return.is_none() {
+                    self.peek_return = Some((self.current_hint_idx, self.viewport.get()));
+                }
+                self.go_to_hint(idx);
+            }
+            None => warn!("Unable to peek at {name}, as there is no such hint"),
+        }
+    }
+
+    /// The index of the hint called `name`, if there is one.
+    fn hint_idx(&self, name: &str) -> Option<usize> {
+        (0..self.hint_count()).find(|&idx| self.hint(idx).is_some_and(|hint| hint.name() == name))
+    }
+
+    /// Stops showing the overview, releasing the textures of the thumbnails. The thumbnails are
+    /// kept, so the overview is quick to open again.
+    fn close_overview(&mut self) {
+        self.show_overview = false;
+        for idx in 0..self.hint_count() {
+            if let Some(hint) = self.hint(idx) {
+                hint.deallocate_thumbnail_texture();
+            }
+        }
+    }
+
+    /// Goes back to the hints that were shown before the hints changed on disk, as each of them
+    /// is loaded again.
+    fn restore_shown_hints(&mut self) {
+        if self.restore_pending == (None, None) {
+            return;
+        }
+        self.collect_loaded();
+        let (current, compare) = &self.restore_pending;
+        let current_idx = current.as_deref().and_then(|name| self.hint_idx(name));
+        let compare_idx = compare.as_deref().and_then(|name| self.hint_idx(name));
+        if let Some(idx) = current_idx {
+            self.current_hint_idx = idx;
+            self.restore_pending.0 = None;
+        }
+        if let Some(idx) = compare_idx {
+            self.compare_hint_idx = Some(idx);
+            self.restore_pending.1 = None;
+        }
+    }
+
+    /// Goes to the hint called `name`, which a link was clicked to.
+    fn follow_link(&mut self, name: &str) {
+        match self.hint_idx(name) {
+            Some(idx) => {
+                info!("Following link to {name}");
+                self.go_to_hint(idx);
+            }
+            None => warn!("Unable to follow link to {name}, as there is no such hint"),
+        }
+    }
+
+    fn go_to_hint(&mut self, idx: usize) {
+        if idx < self.hint_count() && idx != self.current_hint_idx {
+            self.deallocate_current_texture();
+            self.current_hint_idx = idx;
+        }
+    }
+
+    /// Shows the charts for the airports in the flight plan.
+    fn refresh_charts(&mut self) {
+        let airports = self
+            .nav_data
+            .as_ref()
+            .map(|nav_data| nav_data.flight_plan_airports())
+            .unwrap_or_default();
+        if airports.is_empty() {
+            warn!("There are no airports in the flight plan to show charts for");
+            return;
+        }
+        self.show_charts(&airports);
+    }
+
+    /// Replaces the charts with those for `airports`, which are fetched from the chart provider in
+    /// the background.
+    fn show_charts(&mut self, airports: &[String]) {
+        let provider = match &self.chart_provider {
+            Some(provider) => Arc::clone(provider),
+            None => {
+                warn!("No chart provider is available");
+                return;
+            }
+        };
+        self.collect_loaded();
+        let count = self.hints.borrow().len();
+        if self.current_hint_idx >= count {
+            self.current_hint_idx = 0;
+            self.viewport.take();
+        }
+        if self.compare_hint_idx.is_some_and(|idx| idx >= count) {
+            self.compare_hint_idx = None;
+        }
+        for chart in self.charts.drain(..) {
+            chart.deallocate_texture();
+        }
+        self.chart_request = Some(fetch_charts(provider, airports));
+    }
+
+    /// Adds the charts the chart provider has fetched so far, without waiting for the rest.
+    fn collect_charts(&mut self) {
+        let rx = match &self.chart_request {
+            Some(rx) => rx,
+            None => return,
+        };
+        let finished = loop {
+            match rx.try_recv() {
+                Ok((ident, Ok(charts))) => {
+                    info!(count = charts.len(), "Received charts for {ident}");
+                    self.charts.extend(charts.into_iter().map(Rc::new));
+                }
+                Ok((ident, Err(e))) => {
+                    warn!(code = e.code(), "Unable to get charts for {ident}: {e}");
+                }
+                Err(TryRecvError::Empty) => break false,
+                Err(TryRecvError::Disconnected) => break true,
+            }
+        };
+        if finished {
+            self.chart_request = None;
+        }
+    }
+
+    /// Moves any hints the loader thread has finished with into the main thread's list, without
+    /// waiting if the loader holds the lock. If an earlier panic left the loaded hints poisoned
+    /// they are discarded, as they may be inconsistent, and a reload is scheduled for the next
+    /// [`Hints::update`].
+    fn collect_loaded(&self) {
+        let mut loaded = match self.loaded.try_lock() {
+            Ok(loaded) => loaded,
+            Err(TryLockError::WouldBlock) => return,
+            Err(TryLockError::Poisoned(poisoned)) => {
+                warn!("Hints were left in an unknown state by an earlier error, reloading");
+                let mut loaded = poisoned.into_inner();
+                loaded.clear();
+                self.loaded.clear_poison();
+                self.reload_pending.set(true);
+                return;
+            }
+        };
+        if !loaded.is_empty() {
+            self.hints
+                .borrow_mut()
+                .extend(loaded.drain(..).map(Rc::new));
+        }
+    }
+
+    fn hint(&self, idx: usize) -> Option<Rc<Hint>> {
+        let hints = self.hints.borrow();
+        match hints.get(idx) {
+            Some(hint) => Some(Rc::clone(hint)),
+            None => self.charts.get(idx - hints.len()).cloned(),
+        }
+    }
+
+    fn deallocate_current_texture(&self) {
+        if self.compare_hint_idx != Some(self.current_hint_idx) {
+            if let Some(current_hint) = self.hint(self.current_hint_idx) {
+                current_hint.deallocate_texture();
+            }
+        }
+        self.viewport.take();
+    }
+
+    /// The viewport that zoom events apply to: whichever pane was last under the mouse when
+    /// comparing, otherwise the current hint.
+    fn active_viewport(&self) -> &Cell<Viewport> {
+        if self.compare_hint_idx.is_some() && self.active_pane.get() == Pane::Compare {
+            &self.compare_viewport
+        } else {
+            &self.viewport
+        }
+    }
+
+    fn update_viewport<F: FnOnce(&mut Viewport)>(&self, f: F) {
+        let viewport = self.active_viewport();
+        let mut updated = viewport.get();
+        f(&mut updated);
+        viewport.set(updated);
+    }
+
+    fn toggle_compare(&mut self) {
+        if let Some(compare_idx) = self.compare_hint_idx.take() {
+            if compare_idx != self.current_hint_idx {
+                if let Some(compare_hint) = self.hint(compare_idx) {
+                    compare_hint.deallocate_texture();
+                }
+            }
+        } else if self.have_hints() {
+            self.compare_hint_idx = Some(self.current_hint_idx);
+            self.compare_viewport.set(self.viewport.get());
+            self.active_pane.set(Pane::Current);
         }
     }
 
     pub fn handle_hints_event(&mut self, event: HintsEvent) {
+        self.update();
+        // the user has moved on from the hints shown before a reload
+        self.restore_pending = (None, None);
+        if let Some(usage_counts) = self.usage_counts.get_mut().as_mut() {
+            usage_counts.command_used(event.name());
+        }
         match event {
             HintsEvent::NextHint => {
                 if self.have_hints() {
-                    let hints = self.hints.lock().expect("Could not lock hints");
-                    self.deallocate_current_texture(&hints);
-                    self.current_hint_idx = (self.current_hint_idx + 1) % hints.len();
+                    self.deallocate_current_texture();
+                    self.current_hint_idx = self.next_hint_idx(self.current_hint_idx, true);
                     trace!(new_idx = self.current_hint_idx, "HintsEvent::NextHint");
                 }
             }
             HintsEvent::PreviousHint => {
                 if self.have_hints() {
-                    let hints = self.hints.lock().expect("Could not lock hints");
-                    self.deallocate_current_texture(&hints);
-                    self.current_hint_idx = (self.current_hint_idx + hints.len() - 1) % hints.len();
+                    self.deallocate_current_texture();
+                    self.current_hint_idx = self.next_hint_idx(self.current_hint_idx, false);
                     trace!(new_idx = self.current_hint_idx, "HintsEvent::PreviousHint");
                 }
             }
+            HintsEvent::NextTenHints | HintsEvent::PreviousTenHints => {
+                if self.have_hints() {
+                    let forward = matches!(event, HintsEvent::NextTenHints);
+                    let idx = (0..COARSE_PAGE_STEP).fold(self.current_hint_idx, |idx, _| {
+                        self.next_hint_idx(idx, forward)
+                    });
+                    self.go_to_hint(idx);
+                    trace!(
+                        new_idx = self.current_hint_idx,
+                        "HintsEvent::{}",
+                        event.name()
+                    );
+                }
+            }
+            HintsEvent::GoToHint(idx) => {
+                self.go_to_hint(idx);
+                trace!(new_idx = self.current_hint_idx, "HintsEvent::GoToHint");
+            }
             HintsEvent::Reload => {
                 self.reload();
                 trace!("HintsEvent::Reload");
             }
+            HintsEvent::ZoomIn => {
+                self.update_viewport(Viewport::zoom_in);
+                trace!(viewport = ?self.active_viewport().get(), "HintsEvent::ZoomIn");
+            }
+            HintsEvent::ZoomOut => {
+                self.update_viewport(Viewport::zoom_out);
+                trace!(viewport = ?self.active_viewport().get(), "HintsEvent::ZoomOut");
+            }
+            HintsEvent::ResetZoom => {
+                self.update_viewport(Viewport::reset);
+                trace!("HintsEvent::ResetZoom");
+            }
+            HintsEvent::ToggleCompare => {
+                self.toggle_compare();
+                trace!(compare_idx = ?self.compare_hint_idx, "HintsEvent::ToggleCompare");
+            }
+            HintsEvent::ToggleAdjustments => {
+                self.show_adjustments = !self.show_adjustments;
+                trace!(
+                    show_adjustments = self.show_adjustments,
+                    "HintsEvent::ToggleAdjustments"
+                );
+            }
+            HintsEvent::ToggleVariants => {
+                self.show_variants = !self.show_variants;
+                trace!(
+                    show_variants = self.show_variants,
+                    "HintsEvent::ToggleVariants"
+                );
+            }
+            HintsEvent::ToggleConversions => {
+                self.show_conversions = !self.show_conversions;
+                trace!(
+                    show_conversions = self.show_conversions,
+                    "HintsEvent::ToggleConversions"
+                );
+            }
+            HintsEvent::ToggleAbout => {
+                self.show_about = !self.show_about;
+                trace!(show_about = self.show_about, "HintsEvent::ToggleAbout");
+            }
+            HintsEvent::StartPeek => {
+                self.start_peek();
+                trace!("HintsEvent::StartPeek");
+            }
+            HintsEvent::EndPeek => {
+                if let Some((idx, viewport)) = self.peek_return.take() {
+                    self.go_to_hint(idx);
+                    self.viewport.set(viewport);
+                }
+                trace!("HintsEvent::EndPeek");
+            }
+            HintsEvent::ToggleOverview => {
+                if self.show_overview {
+                    self.close_overview();
+                } else {
+                    self.show_overview = true;
+                }
+                trace!(
+                    show_overview = self.show_overview,
+                    "HintsEvent::ToggleOverview"
+                );
+            }
+            HintsEvent::Diagnose => {
+                let diagnosis = self.diagnose();
+                diagnosis.log();
+                self.diagnosis.replace(Some(diagnosis));
+                trace!("HintsEvent::Diagnose");
+            }
+            HintsEvent::ToggleNavPanel => {
+                self.show_nav_panel = !self.show_nav_panel;
+                trace!(
+                    show_nav_panel = self.show_nav_panel,
+                    "HintsEvent::ToggleNavPanel"
+                );
+            }
+            HintsEvent::NextDisplayMode => {
+                self.display_mode = self.display_mode.next();
+                trace!(display_mode = ?self.display_mode, "HintsEvent::NextDisplayMode");
+            }
+            HintsEvent::ToggleAccessibility => {
+                self.accessible = !self.accessible;
+                if self.accessible {
+                    for viewport in [&self.viewport, &self.compare_viewport] {
+                        let mut magnified = viewport.get();
+                        magnified.magnify(ACCESSIBLE_ZOOM);
+                        viewport.set(magnified);
+                    }
+                }
+                trace!(
+                    accessible = self.accessible,
+                    "HintsEvent::ToggleAccessibility"
+                );
+            }
+            HintsEvent::CopyHint => {
+                if let Some(hint) = self.hint(self.current_hint_idx) {
+                    copy_hint(&hint);
+                }
+                trace!("HintsEvent::CopyHint");
+            }
+            HintsEvent::CopyText => {
+                if let Some(text) = self.current_hint_text() {
+                    match copy_text(&text) {
+                        Ok(()) => info!("Copied the current hint's text to the clipboard"),
+                        Err(e) => error!(code = e.code(), "Unable to copy text: {e}"),
+                    }
+                }
+                trace!("HintsEvent::CopyText");
+            }
+            HintsEvent::SpeakText => {
+                match (self.current_hint_text(), &self.speaker) {
+                    (Some(text), Some(speaker)) => speaker.speak(&text),
+                    (Some(_), None) => warn!("Text can't be spoken here"),
+                    (None, _) => {}
+                }
+                trace!("HintsEvent::SpeakText");
+            }
+            HintsEvent::ToggleMiniMode => {
+                self.mini_mode.set(!self.mini_mode.get());
+                trace!(
+                    mini_mode = self.mini_mode.get(),
+                    "HintsEvent::ToggleMiniMode"
+                );
+            }
+            HintsEvent::RefreshCharts => {
+                self.refresh_charts();
+                trace!("HintsEvent::RefreshCharts");
+            }
+            HintsEvent::NextFlowStep => {
+                self.next_flow_step();
+                trace!(flow_step = ?self.flow_step, "HintsEvent::NextFlowStep");
+            }
         }
     }
 
+    /// Starts the guided flow of the current hint, or moves on to its next step if the current one
+    /// has been done, ending it after the last step.
+    fn next_flow_step(&mut self) {
+        let hint = match self.hint(self.current_hint_idx) {
+            Some(hint) => hint,
+            None => return,
+        };
+        let steps = match self.config.hints.get(hint.name()) {
+            Some(hint_config) if !hint_config.flow.is_empty() => &hint_config.flow,
+            _ => return,
+        };
+        let step = match &self.flow_step {
+            Some((name, step)) if name == hint.name() => Some(*step),
+            _ => None,
+        };
+        let next = next_step(steps, step, self.datarefs.as_ref());
+        if let Some(idx) = step.filter(|idx| next == Some(*idx)) {
+            info!("Waiting for step {} of the flow to be done", idx + 1);
+        }
+        self.flow_step = next.map(|next| (hint.name().to_string(), next));
+    }
+
+    /// The index of the hint after the current one, or before it if `forward` is `false`, wrapping
+    /// around. Hints that are out of date are skipped if the hints config asks for them to be
+    /// hidden, unless they all are.
+    fn next_hint_idx(&self, from: usize, forward: bool) -> usize {
+        let count = self.hint_count();
+        let step = |idx: usize| {
+            if forward {
+                (idx + 1) % count
+            } else {
+                (idx + count - 1) % count
+            }
+        };
+        let mut idx = step(from);
+        if self.config.schedule.hide_expired {
+            if let Some(date) = SimDate::read(self.datarefs.as_ref()) {
+                for _ in 1..count {
+                    let hidden = self
+                        .hint(idx)
+                        .is_some_and(|hint| self.out_of_date(&hint, date).is_some());
+                    if !hidden {
+                        break;
+                    }
+                    idx = step(idx);
+                }
+            }
+        }
+        idx
+    }
+
+    /// When `hint` is valid, if it is not valid on `date`.
+    fn out_of_date(&self, hint: &Hint, date: SimDate) -> Option<&Validity> {
+        self.config
+            .hints
+            .get(hint.name())
+            .map(|hint_config| &hint_config.validity)
+            .filter(|validity| !validity.is_valid(date))
+    }
+
+    /// Progress through the current hint, if it is a checklist.
+    #[must_use]
+    pub fn checklist_progress(&self) -> Option<ChecklistProgress> {
+        match self.hint(self.current_hint_idx)?.content() {
+            HintContent::Checklist(checklist) => Some(checklist.progress()),
+            _ => None,
+        }
+    }
+
+    /// The current hint as plain text, if it is a text or checklist hint.
+    fn current_hint_text(&self) -> Option<String> {
+        let hint = self.hint(self.current_hint_idx)?;
+        match hint.content() {
+            HintContent::Text(text) => Some(text.plain_text(self.datarefs.as_ref())),
+            HintContent::Checklist(checklist) => Some(checklist.plain_text()),
+            _ => {
+                warn!("Only text and checklist hints can be copied or spoken as text");
+                None
+            }
+        }
+    }
+
+    /// Whether the window is to be shrunk to a thumbnail of the current hint, which is left when
+    /// the thumbnail is clicked.
+    #[must_use]
+    pub fn is_mini_mode(&self) -> bool {
+        self.mini_mode.get()
+    }
+
+    #[must_use]
+    pub fn current_hint_index(&self) -> usize {
+        self.current_hint_idx
+    }
+
+    /// The name of the current hint, which is its filename for the aircraft's own hints.
+    #[must_use]
+    pub fn current_hint_name(&self) -> Option<String> {
+        self.collect_loaded();
+        self.hint(self.current_hint_idx)
+            .map(|hint| hint.name().to_string())
+    }
+
+    /// The index of the first hint of each run of hints in the same category, such as the charts
+    /// or the pages of a PDF.
+    #[must_use]
+    pub fn category_starts(&self) -> Vec<usize> {
+        let hints: Vec<Rc<Hint>> = (0..self.hint_count())
+            .filter_map(|idx| self.hint(idx))
+            .collect();
+        let names: Vec<&str> = hints.iter().map(|hint| hint.name()).collect();
+        category_starts(&names)
+    }
+
+    /// Checks the state of the hints, for the `Diagnose` event.
+    fn diagnose(&self) -> Diagnosis {
+        self.collect_loaded();
+        let directory_found = self.path.is_dir();
+        let hints = self.hints.borrow();
+        let all_hints = || hints.iter().chain(&self.charts);
+        let texture_bytes = all_hints()
+            .map(|hint| match hint.content() {
+                HintContent::Image(image) => {
+                    let (width, height) = image.dimensions();
+                    u64::from(width) * u64::from(height) * 4
+                }
+                _ => 0,
+            })
+            .sum();
+        let mut services = vec![(
+            String::from("Charts server"),
+            String::from(if self.chart_provider.is_some() {
+                "configured"
+            } else {
+                "not configured"
+            }),
+        )];
+        services.push((
+            String::from("File watcher"),
+            String::from(match (&self.watcher, self.safe_mode) {
+                (Some(_), _) => "watching",
+                (None, true) => "disabled in safe mode",
+                (None, false) => "not watching",
+            }),
+        ));
+        services.extend(
+            self.services
+                .iter()
+                .map(|(service, status)| (service.clone(), status.clone())),
+        );
+        Diagnosis {
+            directory: self.path.clone(),
+            directory_found,
+            library: self.library.clone(),
+            files: if directory_found {
+                self.shown_hint_files().len()
+            } else {
+                0
+            },
+            loaded: hints
+                .iter()
+                .filter(|hint| !matches!(hint.content(), HintContent::Contents))
+                .count(),
+            failures: self
+                .failed
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .iter()
+                .cloned()
+                .chain(all_hints().filter_map(|hint| match hint.content() {
+                    HintContent::Image(image) if image.has_failed() => {
+                        Some(hint.name().to_string())
+                    }
+                    _ => None,
+                }))
+                .collect(),
+            texture_bytes,
+            services,
+        }
+    }
+
+    #[must_use]
+    pub fn hint_count(&self) -> usize {
+        self.collect_loaded();
+        self.hints.borrow().len() + self.charts.len()
+    }
+
+    /// Loads the hint at `path` and inserts it at `position`, or after the aircraft's other hints
+    /// if `position` is past the last of them, returning the index it was inserted at. The current
+    /// and compared hints stay the same. The hint is not copied into the hints directory, so it is
+    /// lost on reload unless the caller has also put it there.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the image cannot be read or decoded.
+    pub fn insert_hint<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        position: usize,
+    ) -> Result<usize, HintsError> {
+        let hint = Hint::new(path, &self.settings_dir)?;
+        let count = self.hint_count();
+        let idx = position.min(self.hints.borrow().len());
+        self.hints.borrow_mut().insert(idx, Rc::new(hint));
+        if count > 0 && idx <= self.current_hint_idx {
+            self.current_hint_idx += 1;
+        }
+        if let Some(compare_idx) = self.compare_hint_idx.as_mut() {
+            if idx <= *compare_idx {
+                *compare_idx += 1;
+            }
+        }
+        trace!(idx, current_idx = self.current_hint_idx, "Inserted hint");
+        Ok(idx)
+    }
+
+    /// Removes the hint at `idx`, releasing its texture. If it was being shown, the hint that
+    /// takes its place is shown instead, and if it was being compared, comparing stops. Returns
+    /// `false` if there is no hint at `idx`.
+    pub fn remove_hint(&mut self, idx: usize) -> bool {
+        let count = self.hint_count();
+        if idx >= count {
+            return false;
+        }
+        let file_count = self.hints.borrow().len();
+        let hint = if idx < file_count {
+            self.hints.borrow_mut().remove(idx)
+        } else {
+            self.charts.remove(idx - file_count)
+        };
+        hint.deallocate_texture();
+        match self.compare_hint_idx {
+            Some(compare_idx) if compare_idx == idx => self.compare_hint_idx = None,
+            Some(compare_idx) if compare_idx > idx => self.compare_hint_idx = Some(compare_idx - 1),
+            _ => {}
+        }
+        match idx.cmp(&self.current_hint_idx) {
+            Ordering::Less => self.current_hint_idx -= 1,
+            Ordering::Equal => {
+                self.current_hint_idx = self.current_hint_idx.min(count.saturating_sub(2));
+                self.viewport.take();
+            }
+            Ordering::Greater => {}
+        }
+        trace!(idx, current_idx = self.current_hint_idx, "Removed hint");
+        true
+    }
+
     fn have_hints(&self) -> bool {
-        let hints = self.hints.lock().expect("Could not lock hints");
-        if hints.is_empty() {
+        if self.hint_count() == 0 {
             warn!("Check log for errors. No hints are loaded");
             false
         } else {
@@ -118,26 +1226,28 @@ impl Hints {
 
 impl App for Hints {
     fn draw_ui(&self, ui: &Ui) {
-        let hints = self.hints.lock().unwrap();
-        if let Some(hint) = hints.get(self.current_hint_idx) {
-            let (width, height) = hint.dimensions();
-            let scale_factor = get_scale_factor((width, height), ui.content_region_max());
-            if let Some(texture_id) = hint.texture_id() {
-                #[allow(clippy::cast_precision_loss)]
-                {
-                    Image::new(
-                        texture_id,
-                        [width as f32 * scale_factor, height as f32 * scale_factor],
-                    )
-                    .build(ui);
-                }
-            }
+        self.text_input_active.set(ui.io().want_text_input);
+        self.ctrl_held.set(ui.io().key_ctrl);
+        match self.config.window.background {
+            Some(background) => draw_with_background(ui, background, || self.draw_window(ui)),
+            None => self.draw_window(ui),
         }
     }
 
     fn handle_event(&mut self, event: Event) -> bool {
+        // leave keys to imgui while a field is being typed into, so typing a value doesn't page
+        if matches!(event, Event::Key(..)) && self.text_input_active.get() {
+            return false;
+        }
+        // leave Ctrl+scroll to imgui too, which zooms the hint about the mouse cursor
+        if matches!(event, Event::Scroll(..)) && self.ctrl_held.get() {
+            return false;
+        }
         if let Some(event) = HintsEvent::from(&event) {
-            self.handle_hints_event(event);
+            record_event("input", format_args!("{event:?}"));
+            if let Err(message) = catch_panic("event", || self.handle_hints_event(event)) {
+                self.show_error(&HintsError::Panic(message));
+            }
             true
         } else {
             false
@@ -145,22 +1255,509 @@ impl App for Hints {
     }
 }
 
-#[allow(clippy::cast_precision_loss)]
-fn get_scale_factor(image_size: (u32, u32), window_size: [f32; 2]) -> f32 {
-    let (width, height) = image_size;
-    let width_scale = window_size[0] / width as f32;
-    let height_scale = window_size[1] / height as f32;
-    width_scale.min(height_scale)
+impl Hints {
+    /// Replaces the hints with an error page until they are next reloaded.
+    pub fn show_error(&self, error: &HintsError) {
+        self.error
+            .replace(Some(format!("Error {}: {error}", error.code())));
+    }
+
+    /// Draws the error page if there is an error, or the hints otherwise.
+    fn draw_window(&self, ui: &Ui) {
+        if let Some(message) = self.error.borrow().as_deref() {
+            draw_error(ui, message);
+            return;
+        }
+        if let Err(message) = catch_panic("draw", || self.draw_contents(ui)) {
+            self.show_error(&HintsError::Panic(message));
+        }
+    }
+
+    /// Draws the hints, in the high-contrast theme with a large cursor if the accessibility mode
+    /// is on.
+    fn draw_contents(&self, ui: &Ui) {
+        if self.accessible {
+            draw_high_contrast(ui, || self.draw_hints(ui));
+            draw_large_cursor(ui);
+        } else {
+            ui.set_window_font_scale(1.0);
+            self.draw_hints(ui);
+        }
+    }
+
+    fn draw_hints(&self, ui: &Ui) {
+        self.collect_loaded();
+        let current = self.hint(self.current_hint_idx);
+        if let Some(usage_log) = self.usage_log.borrow_mut().as_mut() {
+            usage_log.drawn(current.as_ref().map(|hint| hint.name()));
+        }
+        if let Some(usage_counts) = self.usage_counts.borrow_mut().as_mut() {
+            usage_counts.drawn(current.as_ref().map(|hint| hint.name()));
+        }
+        if self.mini_mode.get() {
+            if let Some(hint) = &current {
+                // always fitted to the thumbnail, leaving the viewport as it was for when the
+                // window is expanded again
+                self.draw_hint(ui, hint, &Cell::new(Viewport::default()));
+            }
+            if ui.is_window_hovered() && ui.is_mouse_clicked(MouseButton::Left) {
+                info!("Leaving mini mode");
+                self.mini_mode.set(false);
+            }
+            return;
+        }
+        let mut import_wizard = self.import_wizard.borrow_mut();
+        if let Some(wizard) = import_wizard.as_mut() {
+            if wizard.draw(ui) {
+                if wizard.imported() > 0 {
+                    self.reload_pending.set(true);
+                }
+                *import_wizard = None;
+            }
+            return;
+        }
+        drop(import_wizard);
+        let mut diagnosis = self.diagnosis.borrow_mut();
+        if let Some(shown) = diagnosis.as_ref() {
+            if shown.draw(ui) {
+                *diagnosis = None;
+            }
+            return;
+        }
+        drop(diagnosis);
+        if self.show_about {
+            draw_about(
+                ui,
+                &self.config.pack,
+                self.hint_count(),
+                self.usage_counts.borrow().as_ref(),
+            );
+            return;
+        }
+        if self.show_overview {
+            self.draw_overview_page(ui);
+            return;
+        }
+        if self.show_conversions {
+            draw_conversions(ui, &self.conversion_value);
+        }
+        if self.show_variants {
+            let mut chosen = self.variants.borrow_mut();
+            if draw_variant_chooser(ui, &self.config.variants, &mut chosen) {
+                save_variants(&self.settings_dir, &chosen);
+                self.reload_pending.set(true);
+            }
+        }
+        if self.show_nav_panel {
+            let charts_for =
+                self.nav_panel
+                    .draw(ui, self.nav_data.as_deref(), self.chart_provider.is_some());
+            if let Some(ident) = charts_for {
+                self.chart_airports.replace(Some(vec![ident]));
+            }
+        }
+        if self.show_adjustments {
+            if let Some(hint) = current
+                .as_ref()
+                .filter(|hint| matches!(hint.content(), HintContent::Image(_)))
+            {
+                self.draw_adjustment_controls(ui, hint);
+            }
+        }
+        if let Some(compare_idx) = self.compare_hint_idx {
+            let [width, height] = ui.content_region_avail();
+            let pane_size = [(width - PANE_SPACING) / 2.0, height];
+            let compare = self.hint(compare_idx);
+            self.draw_pane(ui, Pane::Compare, compare.as_deref(), pane_size);
+            ui.same_line_with_spacing(0.0, PANE_SPACING);
+            self.draw_pane(ui, Pane::Current, current.as_deref(), pane_size);
+        } else if let Some(hint) = &current {
+            self.draw_title(ui, hint);
+            self.draw_hint(ui, hint, &self.viewport);
+        }
+    }
+
+    fn draw_pane(&self, ui: &Ui, pane: Pane, hint: Option<&Hint>, size: [f32; 2]) {
+        let (id, viewport) = match pane {
+            Pane::Current => ("current", &self.viewport),
+            Pane::Compare => ("compare", &self.compare_viewport),
+        };
+        ui.child_window(id).size(size).build(|| {
+            if ui.is_window_hovered() {
+                self.active_pane.set(pane);
+            }
+            if let Some(hint) = hint {
+                self.draw_title(ui, hint);
+                self.draw_hint(ui, hint, viewport);
+            }
+        });
+    }
+
+    fn draw_hint(&self, ui: &Ui, hint: &Hint, viewport: &Cell<Viewport>) {
+        self.draw_hint_with_texture(ui, hint, viewport, |image, adjustments| {
+            image.texture_id(adjustments, self.display_mode)
+        });
+    }
+
+    /// Draws `hint`, with `texture` giving the texture to draw an image hint with.
+    fn draw_hint_with_texture<F: Fn(&Image, Adjustments) -> Option<TextureId>>(
+        &self,
+        ui: &Ui,
+        hint: &Hint,
+        viewport: &Cell<Viewport>,
+        texture: F,
+    ) {
+        let out_of_date =
+            SimDate::read(self.datarefs.as_ref()).and_then(|date| self.out_of_date(hint, date));
+        if let Some(validity) = out_of_date {
+            ui.text_colored(
+                WARNING_COLOUR,
+                format!("Out of date: this page is {}", validity.describe()),
+            );
+        }
+        let hint_config = self.config.hints.get(hint.name());
+        let switches = hint_config.map_or(&[][..], |hint_config| &hint_config.switches[..]);
+        let flow = hint_config.map_or(&[][..], |hint_config| &hint_config.flow[..]);
+        let links = hint_config.map_or(&[][..], |hint_config| &hint_config.links[..]);
+        let flow_step = match &self.flow_step {
+            Some((name, step)) if name == hint.name() => Some(*step),
+            _ => None,
+        };
+        if matches!(hint.content(), HintContent::Image(_)) {
+            if !switches.is_empty() {
+                draw_summary(ui, switches, self.datarefs.as_ref());
+            }
+            if !flow.is_empty() {
+                draw_step_text(ui, flow, flow_step, self.datarefs.as_ref());
+            }
+        }
+        let region = (ui.cursor_screen_pos(), ui.content_region_avail());
+        match hint.content() {
+            HintContent::Image(image) => {
+                let adjustments = self.adjustments_for(hint);
+                if let Some(texture_id) = texture(image, adjustments) {
+                    // drawing the image can pan or zoom it, so the markers are placed for the
+                    // viewport it was drawn with
+                    let drawn_viewport = viewport.get();
+                    if draw_image(
+                        ui,
+                        texture_id,
+                        image.dimensions(),
+                        viewport,
+                        self.accessible,
+                    ) {
+                        copy_hint(hint);
+                    }
+                    if !switches.is_empty() {
+                        draw_panel_state(
+                            ui,
+                            switches,
+                            self.datarefs.as_ref(),
+                            image.dimensions(),
+                            drawn_viewport,
+                            region,
+                        );
+                    }
+                    if let Some(step) = flow_step.and_then(|step| flow.get(step)) {
+                        draw_step_highlight(ui, step, image.dimensions(), drawn_viewport, region);
+                    }
+                    // clicking the thumbnail in mini mode expands the window instead
+                    if !links.is_empty() && !self.mini_mode.get() {
+                        let clicked =
+                            draw_links(ui, links, image.dimensions(), drawn_viewport, region);
+                        if let Some(to) = clicked {
+                            self.link_pending.replace(Some(to.to_string()));
+                        }
+                    }
+                } else if image.has_failed() {
+                    ui.text_colored(WARNING_COLOUR, "Unable to show this image");
+                } else {
+                    // the image is being decoded in the background
+                    ui.text_disabled("Loading...");
+                }
+            }
+            HintContent::Text(text) => text.draw(ui, self.datarefs.as_ref()),
+            HintContent::Checklist(checklist) => checklist.draw(ui),
+            HintContent::WeightAndBalance(weight_and_balance) => weight_and_balance.draw(ui),
+            HintContent::Performance(performance) => performance.draw(ui, self.datarefs.as_ref()),
+            HintContent::Weather(weather) => weather.draw(ui, self.datarefs.as_ref()),
+            HintContent::Fuel(fuel) => fuel.draw(ui, self.datarefs.as_ref()),
+            HintContent::Contents => self.draw_contents_page(ui),
+        }
+        let watermark = if out_of_date.is_some() {
+            Some(EXPIRED_WATERMARK)
+        } else {
+            hint_config.and_then(|hint_config| hint_config.watermark.as_deref())
+        };
+        if let Some(watermark) = watermark {
+            let colour = self
+                .config
+                .watermark
+                .colour
+                .unwrap_or(DEFAULT_WATERMARK_COLOUR);
+            draw_watermark(ui, watermark, region, colour);
+        }
+    }
+
+    /// The title of `hint` from `index.toml`, if it has one.
+    fn title(&self, hint: &Hint) -> Option<&str> {
+        self.index.as_ref()?.title(hint.name())
+    }
+
+    /// Draws the title of `hint` above it, so that users know which page they are on.
+    fn draw_title(&self, ui: &Ui, hint: &Hint) {
+        if let Some(title) = self.title(hint) {
+            ui.text_colored(HEADING_COLOUR, title);
+        }
+    }
+
+    /// Draws the overview, going to any hint that is clicked in it.
+    fn draw_overview_page(&self, ui: &Ui) {
+        let hints: Vec<Rc<Hint>> = (0..self.hint_count())
+            .filter_map(|idx| self.hint(idx))
+            .collect();
+        let thumbnails: Vec<Thumbnail> = hints
+            .iter()
+            .map(|hint| {
+                let image = match hint.content() {
+                    HintContent::Image(image) if !self.safe_mode => Some(image),
+                    _ => None,
+                };
+                Thumbnail {
+                    title: self.title(hint).unwrap_or_else(|| hint_title(hint.name())),
+                    dimensions: image.map(Image::dimensions),
+                    texture: image.and_then(|image| {
+                        image.thumbnail_texture_id(self.adjustments_for(hint), self.display_mode)
+                    }),
+                }
+            })
+            .collect();
+        if let Some(idx) = draw_overview(ui, &thumbnails, self.current_hint_idx) {
+            self.overview_pending.set(Some(idx));
+        }
+    }
+
+    /// Draws the contents page, going to any hint that is clicked in it.
+    fn draw_contents_page(&self, ui: &Ui) {
+        let hints: Vec<Rc<Hint>> = (0..self.hint_count())
+            .filter_map(|idx| self.hint(idx))
+            .collect();
+        let names: Vec<&str> = hints.iter().map(|hint| hint.name()).collect();
+        let heading = self
+            .config
+            .contents
+            .title
+            .as_deref()
+            .or(self.config.pack.name.as_deref())
+            .unwrap_or(CONTENTS_NAME);
+        let mut sections = contents_sections(&names);
+        for (idx, title) in sections.iter_mut().flat_map(|section| &mut section.entries) {
+            if let Some(indexed) = self.title(&hints[*idx]) {
+                *title = indexed;
+            }
+        }
+        if let Some(idx) = draw_contents(ui, heading, &sections) {
+            self.link_pending.replace(Some(names[idx].to_string()));
+        }
+    }
+
+    /// Renders page `page` of the hints, counting from 1, to a PNG image of `size` at `output`
+    /// without a window, as it would be drawn in a window of that size. The hint is loaded from
+    /// disk, so the hints don't need to have finished loading.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no such page, or it can't be loaded or written.
+    pub fn save_snapshot(
+        &self,
+        page: usize,
+        size: [u32; 2],
+        output: &Path,
+    ) -> Result<(), HintsError> {
+        let files = self.shown_hint_files();
+        let file = match page.checked_sub(1).and_then(|idx| files.get(idx)) {
+            Some(file) => file,
+            None => {
+                return Err(HintsError::Config(format!(
+                    "there is no page {page}, as there are {} hints in {}",
+                    files.len(),
+                    self.path.display()
+                )))
+            }
+        };
+        let hint = Hint::new(file, &self.settings_dir)?;
+        let image = match hint.content() {
+            HintContent::Image(image) => Some(
+                self.display_mode
+                    .apply(self.adjustments_for(&hint).apply(&image.decode()?)),
+            ),
+            _ => None,
+        };
+        let textures: HashMap<_, _> = image
+            .iter()
+            .map(|image| (SNAPSHOT_TEXTURE, &**image))
+            .collect();
+        let viewport = Cell::new(Viewport::default());
+        let snapshot = render(size, &textures, |ui| {
+            self.draw_hint_with_texture(ui, &hint, &viewport, |_, _| {
+                image.as_ref().map(|_| SNAPSHOT_TEXTURE)
+            });
+        });
+        snapshot.save(output)?;
+        info!("Rendered {} to {output:?}", hint.name());
+        Ok(())
+    }
+
+    fn adjustments_for(&self, hint: &Hint) -> Adjustments {
+        self.adjustments
+            .borrow()
+            .get(hint.name())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn draw_adjustment_controls(&self, ui: &Ui, hint: &Hint) {
+        let mut adjustments = self.adjustments_for(hint);
+        let finished = adjustments.draw_controls(ui);
+        let mut all_adjustments = self.adjustments.borrow_mut();
+        all_adjustments.insert(hint.name().to_string(), adjustments);
+        if finished {
+            save_adjustments(&self.settings_dir, &all_adjustments);
+        }
+    }
+}
+
+/// Lists the hint files in `dir` in the order they are shown, choosing translations into
+/// `language` where there are any. With an `index`, only the hints it lists are shown, in its
+/// order.
+fn list_hint_files(dir: &Path, language: Option<&str>, index: Option<&Index>) -> Vec<PathBuf> {
+    let mut files = read_dir(dir);
+    files.retain(|f| is_hint_file(f));
+    files.retain(|f| match check_in_hints_dir(dir, f) {
+        Ok(()) => true,
+        Err(e) => {
+            warn!(code = e.code(), "Not loading hint: {e}");
+            false
+        }
+    });
+    files.sort();
+    match index {
+        Some(index) => index.apply(select_language(dir, files, language)),
+        None => select_language(dir, apply_legacy_order(dir, files), language),
+    }
+}
+
+/// The files in `dir` and in the language folders in it.
+fn files_on_disk(dir: &Path) -> Vec<PathBuf> {
+    read_dir(dir)
+        .into_iter()
+        .flat_map(|path| {
+            if path.is_dir() {
+                read_dir(&path)
+            } else {
+                vec![path]
+            }
+        })
+        .collect()
+}
+
+/// The paths in `dir`, or none if it can't be read.
+fn read_dir(dir: &Path) -> Vec<PathBuf> {
+    match std::fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .collect(),
+        Err(e) => {
+            let e = HintsError::from(e);
+            warn!(code = e.code(), "Unable to read {dir:?}: {e}");
+            vec![]
+        }
+    }
+}
+
+/// Copies `hint` to the clipboard, if it is an image.
+fn copy_hint(hint: &Hint) {
+    match hint.content() {
+        HintContent::Image(image) => match image.decode().and_then(|image| copy_image(&image)) {
+            Ok(()) => info!("Copied {} to the clipboard", hint.name()),
+            Err(e) => error!(code = e.code(), "Unable to copy {}: {e}", hint.name()),
+        },
+        _ => warn!("Only image hints can be copied to the clipboard"),
+    }
+}
+
+fn draw_error(ui: &Ui, message: &str) {
+    ui.text_colored(ERROR_COLOUR, "Hints encountered an error");
+    ui.text_wrapped(message);
+    ui.text_wrapped("Check the log for details, then reload the hints to try again.");
 }
 
 #[derive(Debug, Clone, Copy)]
 pub enum HintsEvent {
     NextHint,
     PreviousHint,
+    NextTenHints,
+    PreviousTenHints,
+    GoToHint(usize),
     Reload,
+    ZoomIn,
+    ZoomOut,
+    ResetZoom,
+    ToggleCompare,
+    ToggleAdjustments,
+    ToggleConversions,
+    ToggleNavPanel,
+    ToggleAbout,
+    NextDisplayMode,
+    ToggleAccessibility,
+    CopyHint,
+    CopyText,
+    SpeakText,
+    RefreshCharts,
+    ToggleMiniMode,
+    NextFlowStep,
+    ToggleVariants,
+    Diagnose,
+    ToggleOverview,
+    /// Shows the peek hint until [`HintsEvent::EndPeek`], for commands that are held.
+    StartPeek,
+    EndPeek,
 }
 
 impl HintsEvent {
+    /// The name the event is counted under in the usage counts.
+    fn name(&self) -> &'static str {
+        match self {
+            Self::NextHint => "NextHint",
+            Self::PreviousHint => "PreviousHint",
+            Self::NextTenHints => "NextTenHints",
+            Self::PreviousTenHints => "PreviousTenHints",
+            Self::GoToHint(_) => "GoToHint",
+            Self::Reload => "Reload",
+            Self::ZoomIn => "ZoomIn",
+            Self::ZoomOut => "ZoomOut",
+            Self::ResetZoom => "ResetZoom",
+            Self::ToggleCompare => "ToggleCompare",
+            Self::ToggleAdjustments => "ToggleAdjustments",
+            Self::ToggleConversions => "ToggleConversions",
+            Self::ToggleNavPanel => "ToggleNavPanel",
+            Self::ToggleAbout => "ToggleAbout",
+            Self::NextDisplayMode => "NextDisplayMode",
+            Self::ToggleAccessibility => "ToggleAccessibility",
+            Self::CopyHint => "CopyHint",
+            Self::CopyText => "CopyText",
+            Self::SpeakText => "SpeakText",
+            Self::RefreshCharts => "RefreshCharts",
+            Self::ToggleMiniMode => "ToggleMiniMode",
+            Self::NextFlowStep => "NextFlowStep",
+            Self::ToggleVariants => "ToggleVariants",
+            Self::Diagnose => "Diagnose",
+            Self::ToggleOverview => "ToggleOverview",
+            Self::StartPeek => "StartPeek",
+            Self::EndPeek => "EndPeek",
+        }
+    }
+
     fn from(event: &Event) -> Option<Self> {
         match *event {
             Event::Scroll(_, y) => match y.cmp(&0) {
@@ -173,7 +1770,27 @@ impl HintsEvent {
                     match key {
                         Key::UpArrow => Some(Self::PreviousHint),
                         Key::DownArrow => Some(Self::NextHint),
+                        Key::PageUp => Some(Self::PreviousTenHints),
+                        Key::PageDown => Some(Self::NextTenHints),
                         Key::R => Some(Self::Reload),
+                        Key::Equal | Key::KeypadAdd => Some(Self::ZoomIn),
+                        Key::Minus | Key::KeypadSubtract => Some(Self::ZoomOut),
+                        Key::Alpha0 | Key::Keypad0 => Some(Self::ResetZoom),
+                        Key::C => Some(Self::ToggleCompare),
+                        Key::A => Some(Self::ToggleAdjustments),
+                        Key::M => Some(Self::NextDisplayMode),
+                        Key::U => Some(Self::ToggleConversions),
+                        Key::N => Some(Self::ToggleNavPanel),
+                        Key::I => Some(Self::ToggleAbout),
+                        Key::V => Some(Self::ToggleAccessibility),
+                        Key::T => Some(Self::CopyText),
+                        Key::S => Some(Self::SpeakText),
+                        Key::H => Some(Self::RefreshCharts),
+                        Key::P => Some(Self::ToggleMiniMode),
+                        Key::F => Some(Self::NextFlowStep),
+                        Key::K => Some(Self::ToggleVariants),
+                        Key::D => Some(Self::Diagnose),
+                        Key::O => Some(Self::ToggleOverview),
                         _ => None,
                     }
                 } else {
@@ -184,3 +1801,97 @@ impl HintsEvent {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use crate::hints::Hint;
+    use crate::test_dir::TestDir;
+
+    use super::Hints;
+
+    /// An app for an empty hints directory in `dir`, with the text hints `names` inserted after
+    /// each other and the charts `charts` after them.
+    fn app(dir: &TestDir, names: &[&str], charts: &[&str]) -> Hints {
+        std::fs::create_dir(dir.join("hints")).unwrap();
+        let mut app = Hints::new(dir.join("hints")).unwrap();
+        for &name in names {
+            dir.touch(&[name]);
+            app.insert_hint(dir.join(name), usize::MAX).unwrap();
+        }
+        for &name in charts {
+            dir.touch(&[name]);
+            app.charts
+                .push(Rc::new(Hint::new(dir.join(name), dir).unwrap()));
+        }
+        app
+    }
+
+    fn names(app: &Hints) -> Vec<String> {
+        (0..app.hint_count())
+            .map(|idx| app.hint(idx).unwrap().name().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn inserting_keeps_the_current_and_compared_hints() {
+        let dir = TestDir::new();
+        let mut app = app(&dir, &["a.txt", "b.txt", "c.txt"], &[]);
+        app.current_hint_idx = 1;
+        app.compare_hint_idx = Some(2);
+        dir.touch(&["x.txt", "y.txt"]);
+        assert_eq!(app.insert_hint(dir.join("x.txt"), 0).unwrap(), 0);
+        assert_eq!((app.current_hint_idx, app.compare_hint_idx), (2, Some(3)));
+        assert_eq!(app.insert_hint(dir.join("y.txt"), 3).unwrap(), 3);
+        assert_eq!((app.current_hint_idx, app.compare_hint_idx), (2, Some(4)));
+        assert_eq!(names(&app), ["x.txt", "a.txt", "b.txt", "y.txt", "c.txt"]);
+    }
+
+    #[test]
+    fn inserting_past_the_hints_puts_the_hint_before_the_charts() {
+        let dir = TestDir::new();
+        let mut app = app(&dir, &["a.txt", "b.txt"], &["chart.txt"]);
+        app.current_hint_idx = 2;
+        dir.touch(&["x.txt"]);
+        assert_eq!(app.insert_hint(dir.join("x.txt"), 10).unwrap(), 2);
+        assert_eq!(names(&app), ["a.txt", "b.txt", "x.txt", "chart.txt"]);
+        assert_eq!(app.current_hint_idx, 3);
+    }
+
+    #[test]
+    fn removing_keeps_the_current_and_compared_hints() {
+        let dir = TestDir::new();
+        let mut app = app(&dir, &["a.txt", "b.txt", "c.txt"], &[]);
+        app.current_hint_idx = 2;
+        app.compare_hint_idx = Some(1);
+        assert!(app.remove_hint(0));
+        assert_eq!((app.current_hint_idx, app.compare_hint_idx), (1, Some(0)));
+        assert!(app.remove_hint(0));
+        assert_eq!((app.current_hint_idx, app.compare_hint_idx), (0, None));
+        assert_eq!(names(&app), ["c.txt"]);
+    }
+
+    #[test]
+    fn removing_a_chart_stops_comparing_it() {
+        let dir = TestDir::new();
+        let mut app = app(&dir, &["a.txt", "b.txt"], &["chart.txt"]);
+        app.compare_hint_idx = Some(2);
+        assert!(!app.remove_hint(3));
+        assert!(app.remove_hint(2));
+        assert_eq!(names(&app), ["a.txt", "b.txt"]);
+        assert_eq!((app.current_hint_idx, app.compare_hint_idx), (0, None));
+    }
+
+    #[test]
+    fn removing_the_last_hint_shows_the_one_before() {
+        let dir = TestDir::new();
+        let mut app = app(&dir, &["a.txt", "b.txt"], &[]);
+        app.current_hint_idx = 1;
+        assert!(app.remove_hint(1));
+        assert_eq!(app.current_hint_idx, 0);
+        assert!(app.remove_hint(0));
+        assert_eq!(app.current_hint_idx, 0);
+        assert_eq!(app.hint_count(), 0);
+    }
+}
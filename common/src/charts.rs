@@ -0,0 +1,355 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+use std::sync::mpsc::Receiver;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+use tracing::{info, warn};
+
+use crate::concurrent::thread_loader;
+use crate::error::HintsError;
+use crate::hints::Hint;
+
+/// The interval to poll a token endpoint at if the authorisation doesn't give one.
+const DEFAULT_POLL_INTERVAL: u64 = 5;
+/// How much longer to wait between polls each time the token endpoint asks to slow down.
+const SLOW_DOWN_INCREASE: Duration = Duration::from_secs(5);
+
+/// A chart for an airport, such as an approach plate, as an encoded PNG or JPEG image.
+#[derive(Debug, Clone)]
+pub struct Chart {
+    /// The identifier of the airport, such as `EGLL`.
+    pub airport: String,
+    pub name: String,
+    pub image: Vec<u8>,
+}
+
+/// A source of charts, such as a subscription chart service. Providers are built in their own
+/// crates, or behind features, and set with [`crate::Hints::set_chart_provider`]. The charts for
+/// the airports in the flight plan are then shown after the aircraft's hints.
+///
+/// Charts are fetched on a background thread, so providers may block on the network.
+pub trait ChartProvider: Send + Sync {
+    /// The name of the provider, as shown in the log.
+    fn name(&self) -> &str;
+
+    /// Returns the charts for the airport with the identifier `ident`, such as `EGLL`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the charts can't be fetched, for example because the user is not
+    /// signed in.
+    fn charts(&self, ident: &str) -> Result<Vec<Chart>, HintsError>;
+}
+
+/// The charts fetched for one airport, as hints, or why they couldn't be fetched.
+pub(crate) type FetchedCharts = (String, Result<Vec<Hint>, HintsError>);
+
+/// Fetches the charts for each of `airports` from `provider` on a background thread, decoding
+/// them into hints. The results for each airport are received in order, and the receiver
+/// disconnects once all have been fetched.
+pub(crate) fn fetch_charts(
+    provider: Arc<dyn ChartProvider>,
+    airports: &[String],
+) -> Receiver<FetchedCharts> {
+    info!(provider = provider.name(), ?airports, "Fetching charts");
+    let (tx, rx) = thread_loader(true, move |ident: String| {
+        let charts = provider.charts(&ident).map(|charts| {
+            charts
+                .iter()
+                .filter_map(|chart| match Hint::from_chart(chart) {
+                    Ok(hint) => Some(hint),
+                    Err(e) => {
+                        warn!(
+                            code = e.code(),
+                            "Unable to decode chart {}: {e}", chart.name
+                        );
+                        None
+                    }
+                })
+                .collect()
+        });
+        (ident, charts)
+    });
+    for ident in airports {
+        // the thread only stops once the sender is dropped, so sending can't fail
+        let _ = tx.send(ident.clone());
+    }
+    rx
+}
+
+/// What the user needs to authorise this device with a chart service, as returned by the
+/// service's device authorisation endpoint in the OAuth device flow (RFC 8628).
+#[derive(Debug, Clone, Deserialize)]
+pub struct DeviceAuthorization {
+    pub device_code: String,
+    /// The code the user enters at [`DeviceAuthorization::verification_uri`].
+    pub user_code: String,
+    pub verification_uri: String,
+    /// A URI that includes the user code, so that it doesn't need to be entered.
+    #[serde(default)]
+    pub verification_uri_complete: Option<String>,
+    /// How long the codes are valid for, in seconds.
+    pub expires_in: u64,
+    /// How long to wait between polls of the token endpoint, in seconds.
+    #[serde(default = "default_poll_interval")]
+    pub interval: u64,
+}
+
+fn default_poll_interval() -> u64 {
+    DEFAULT_POLL_INTERVAL
+}
+
+/// The token granted once the user has authorised the device.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct AccessToken {
+    pub access_token: String,
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+    /// How long the access token is valid for, in seconds.
+    #[serde(default)]
+    pub expires_in: Option<u64>,
+}
+
+/// The answer from polling a token endpoint during the device flow.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenPoll {
+    Granted(AccessToken),
+    /// The user hasn't authorised the device yet.
+    Pending,
+    /// Polling is too frequent.
+    SlowDown,
+    Denied,
+    Expired,
+}
+
+impl TokenPoll {
+    /// Interprets the `error` of an error response from a token endpoint.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `error` is not one of the device flow's own errors.
+    pub fn from_error(error: &str) -> Result<Self, HintsError> {
+        match error {
+            "authorization_pending" => Ok(TokenPoll::Pending),
+            "slow_down" => Ok(TokenPoll::SlowDown),
+            "access_denied" => Ok(TokenPoll::Denied),
+            "expired_token" => Ok(TokenPoll::Expired),
+            _ => Err(HintsError::Charts(format!(
+                "Unexpected response from token endpoint: {error}"
+            ))),
+        }
+    }
+}
+
+/// The requests a chart service's OAuth client makes during the device flow. Providers implement
+/// this with their own HTTP client, and [`DeviceFlow`] does the rest.
+pub trait DeviceFlowClient {
+    /// Requests a device code and user code from the device authorisation endpoint.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails.
+    fn request_authorization(&self) -> Result<DeviceAuthorization, HintsError>;
+
+    /// Asks the token endpoint whether the user has authorised `device_code` yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails.
+    fn poll_token(&self, device_code: &str) -> Result<TokenPoll, HintsError>;
+}
+
+/// Signs in to a chart service with the OAuth device flow, in which the user authorises the
+/// plugin by entering a code on another device, as there is no browser in the sim. Show the user
+/// [`DeviceFlow::authorization`] and call [`DeviceFlow::poll`] regularly until it returns a
+/// token.
+pub struct DeviceFlow {
+    authorization: DeviceAuthorization,
+    interval: Duration,
+    next_poll: Instant,
+    expires: Instant,
+    /// Whether a token or error has been returned, after which polling stops.
+    finished: bool,
+}
+
+impl DeviceFlow {
+    /// Starts the device flow by requesting the codes to show the user.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the codes can't be requested.
+    pub fn start(client: &dyn DeviceFlowClient, now: Instant) -> Result<Self, HintsError> {
+        let authorization = client.request_authorization()?;
+        info!(
+            verification_uri = %authorization.verification_uri,
+            "Waiting for the user to authorise chart access"
+        );
+        let interval = Duration::from_secs(authorization.interval);
+        let expires = now + Duration::from_secs(authorization.expires_in);
+        Ok(DeviceFlow {
+            authorization,
+            interval,
+            next_poll: now + interval,
+            expires,
+            finished: false,
+        })
+    }
+
+    #[must_use]
+    pub fn authorization(&self) -> &DeviceAuthorization {
+        &self.authorization
+    }
+
+    /// Polls the token endpoint if it is time to, returning the token once the user has
+    /// authorised the device, or `None` while waiting.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the user denied access, the codes have expired or the request fails.
+    pub fn poll(
+        &mut self,
+        client: &dyn DeviceFlowClient,
+        now: Instant,
+    ) -> Result<Option<AccessToken>, HintsError> {
+        if self.finished {
+            return Ok(None);
+        }
+        if now >= self.expires {
+            return self.finish("The sign-in code has expired");
+        }
+        if now < self.next_poll {
+            return Ok(None);
+        }
+        self.next_poll = now + self.interval;
+        match client.poll_token(&self.authorization.device_code)? {
+            TokenPoll::Granted(token) => {
+                info!("Chart access authorised");
+                self.finished = true;
+                Ok(Some(token))
+            }
+            TokenPoll::Pending => Ok(None),
+            TokenPoll::SlowDown => {
+                self.interval += SLOW_DOWN_INCREASE;
+                self.next_poll = now + self.interval;
+                Ok(None)
+            }
+            TokenPoll::Denied => self.finish("Chart access was denied"),
+            TokenPoll::Expired => self.finish("The sign-in code has expired"),
+        }
+    }
+
+    fn finish(&mut self, message: &str) -> Result<Option<AccessToken>, HintsError> {
+        self.finished = true;
+        Err(HintsError::Charts(message.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::time::{Duration, Instant};
+
+    use super::{AccessToken, DeviceAuthorization, DeviceFlow, DeviceFlowClient, TokenPoll};
+    use crate::error::HintsError;
+
+    struct MockClient {
+        polls: RefCell<Vec<TokenPoll>>,
+        polled: RefCell<usize>,
+    }
+
+    impl MockClient {
+        fn new(mut polls: Vec<TokenPoll>) -> Self {
+            polls.reverse();
+            MockClient {
+                polls: RefCell::new(polls),
+                polled: RefCell::new(0),
+            }
+        }
+    }
+
+    impl DeviceFlowClient for MockClient {
+        fn request_authorization(&self) -> Result<DeviceAuthorization, HintsError> {
+            Ok(DeviceAuthorization {
+                device_code: String::from("device"),
+                user_code: String::from("ABCD-EFGH"),
+                verification_uri: String::from("https://example.com/device"),
+                verification_uri_complete: None,
+                expires_in: 60,
+                interval: 5,
+            })
+        }
+
+        fn poll_token(&self, device_code: &str) -> Result<TokenPoll, HintsError> {
+            assert_eq!(device_code, "device");
+            *self.polled.borrow_mut() += 1;
+            Ok(self.polls.borrow_mut().pop().unwrap())
+        }
+    }
+
+    fn token() -> AccessToken {
+        AccessToken {
+            access_token: String::from("token"),
+            refresh_token: None,
+            expires_in: Some(3600),
+        }
+    }
+
+    #[test]
+    fn polls_at_interval_until_granted() {
+        let client = MockClient::new(vec![
+            TokenPoll::Pending,
+            TokenPoll::SlowDown,
+            TokenPoll::Granted(token()),
+        ]);
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let mut flow = DeviceFlow::start(&client, start).unwrap();
+        assert_eq!(flow.authorization().user_code, "ABCD-EFGH");
+        assert_eq!(flow.poll(&client, at(4)).unwrap(), None);
+        assert_eq!(*client.polled.borrow(), 0);
+        assert_eq!(flow.poll(&client, at(5)).unwrap(), None);
+        // slows down to every 10 seconds
+        assert_eq!(flow.poll(&client, at(10)).unwrap(), None);
+        assert_eq!(flow.poll(&client, at(19)).unwrap(), None);
+        assert_eq!(*client.polled.borrow(), 2);
+        assert_eq!(flow.poll(&client, at(20)).unwrap(), Some(token()));
+        assert_eq!(flow.poll(&client, at(30)).unwrap(), None);
+        assert_eq!(*client.polled.borrow(), 3);
+    }
+
+    #[test]
+    fn stops_when_denied_or_expired() {
+        let client = MockClient::new(vec![TokenPoll::Denied]);
+        let start = Instant::now();
+        let mut flow = DeviceFlow::start(&client, start).unwrap();
+        assert!(flow.poll(&client, start + Duration::from_secs(5)).is_err());
+        assert_eq!(
+            flow.poll(&client, start + Duration::from_secs(10)).unwrap(),
+            None
+        );
+
+        let client = MockClient::new(vec![]);
+        let mut flow = DeviceFlow::start(&client, start).unwrap();
+        assert!(flow.poll(&client, start + Duration::from_secs(60)).is_err());
+        assert_eq!(*client.polled.borrow(), 0);
+    }
+
+    #[test]
+    fn interprets_token_errors() {
+        assert_eq!(
+            TokenPoll::from_error("authorization_pending").unwrap(),
+            TokenPoll::Pending
+        );
+        assert_eq!(
+            TokenPoll::from_error("slow_down").unwrap(),
+            TokenPoll::SlowDown
+        );
+        assert!(TokenPoll::from_error("invalid_grant").is_err());
+    }
+}
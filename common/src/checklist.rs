@@ -0,0 +1,133 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+use std::cell::RefCell;
+use std::path::Path;
+
+use imgui::Ui;
+use serde::Deserialize;
+
+use crate::error::{read_toml_file, HintsError};
+use crate::view::draw_title;
+
+/// An interactive checklist, read from a `.checklist` file containing TOML such as:
+///
+/// ```toml
+/// title = "Before start"
+///
+/// [[items]]
+/// challenge = "Parking brake"
+/// response = "SET"
+/// ```
+#[derive(Debug)]
+pub struct Checklist {
+    title: Option<String>,
+    items: Vec<ChecklistItem>,
+    checked: RefCell<Vec<bool>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChecklistFile {
+    title: Option<String>,
+    items: Vec<ChecklistItem>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChecklistItem {
+    pub challenge: String,
+    #[serde(default)]
+    pub response: String,
+}
+
+/// How far through a checklist the user is, so that it can be shown on cockpit hardware.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChecklistProgress {
+    /// The percentage of items checked, from 0 to 100.
+    pub percent_complete: f32,
+    /// The challenge of the first item not yet checked, or `None` if the checklist is complete.
+    pub current_item: Option<String>,
+}
+
+impl Checklist {
+    pub(crate) fn load(path: &Path) -> Result<Self, HintsError> {
+        Ok(Self::from_file(read_toml_file(path)?))
+    }
+
+    /// Parses the contents of a `.checklist` file.
+    #[cfg(feature = "fuzzing")]
+    pub(crate) fn parse(toml: &str) -> Result<Self, HintsError> {
+        Ok(Self::from_file(toml::from_str(toml)?))
+    }
+
+    fn from_file(file: ChecklistFile) -> Self {
+        Checklist {
+            title: file.title,
+            checked: RefCell::new(vec![false; file.items.len()]),
+            items: file.items,
+        }
+    }
+
+    pub fn reset(&self) {
+        self.checked.borrow_mut().fill(false);
+    }
+
+    pub fn progress(&self) -> ChecklistProgress {
+        let checked = self.checked.borrow();
+        let completed = checked.iter().filter(|checked| **checked).count();
+        #[allow(clippy::cast_precision_loss)]
+        let percent_complete = if checked.is_empty() {
+            100.0
+        } else {
+            completed as f32 * 100.0 / checked.len() as f32
+        };
+        let current_item = self
+            .items
+            .iter()
+            .zip(checked.iter())
+            .find(|(_, checked)| !**checked)
+            .map(|(item, _)| item.challenge.clone());
+        ChecklistProgress {
+            percent_complete,
+            current_item,
+        }
+    }
+
+    pub fn draw(&self, ui: &Ui) {
+        if let Some(title) = &self.title {
+            draw_title(ui, title);
+        }
+        {
+            let mut checked = self.checked.borrow_mut();
+            for (idx, (item, checked)) in self.items.iter().zip(checked.iter_mut()).enumerate() {
+                ui.checkbox(format!("{}##item{idx}", item.challenge), checked);
+                if !item.response.is_empty() {
+                    ui.same_line();
+                    ui.text_disabled(&item.response);
+                }
+            }
+        }
+        ui.spacing();
+        if ui.button("Reset checklist") {
+            self.reset();
+        }
+    }
+
+    /// The checklist as plain text, one item per line with whether it has been checked, for
+    /// screen readers and text-to-speech.
+    pub(crate) fn plain_text(&self) -> String {
+        let checked = self.checked.borrow();
+        let mut lines: Vec<String> = self.title.iter().cloned().collect();
+        for (item, checked) in self.items.iter().zip(checked.iter()) {
+            let state = if *checked { "done" } else { "not done" };
+            if item.response.is_empty() {
+                lines.push(format!("{}, {state}.", item.challenge));
+            } else {
+                lines.push(format!("{}: {}, {state}.", item.challenge, item.response));
+            }
+        }
+        lines.join("\n")
+    }
+}
@@ -0,0 +1,62 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+use image::RgbaImage;
+
+use crate::error::HintsError;
+
+#[cfg(feature = "clipboard")]
+thread_local! {
+    /// The clipboard is kept open because on Linux its contents are lost when it is closed.
+    static CLIPBOARD: std::cell::RefCell<Option<arboard::Clipboard>> =
+        std::cell::RefCell::new(None);
+}
+
+/// Places `image` on the system clipboard, so that it can be pasted into other applications.
+#[cfg(feature = "clipboard")]
+pub(crate) fn copy_image(image: &RgbaImage) -> Result<(), HintsError> {
+    CLIPBOARD.with(|clipboard| -> Result<(), HintsError> {
+        let mut clipboard = clipboard.borrow_mut();
+        if clipboard.is_none() {
+            *clipboard = Some(arboard::Clipboard::new()?);
+        }
+        let (width, height) = image.dimensions();
+        let data = arboard::ImageData {
+            width: width as usize,
+            height: height as usize,
+            bytes: std::borrow::Cow::Borrowed(image.as_raw().as_slice()),
+        };
+        clipboard.as_mut().unwrap().set_image(data)?;
+        Ok(())
+    })
+}
+
+/// Places `text` on the system clipboard.
+#[cfg(feature = "clipboard")]
+pub(crate) fn copy_text(text: &str) -> Result<(), HintsError> {
+    CLIPBOARD.with(|clipboard| -> Result<(), HintsError> {
+        let mut clipboard = clipboard.borrow_mut();
+        if clipboard.is_none() {
+            *clipboard = Some(arboard::Clipboard::new()?);
+        }
+        clipboard.as_mut().unwrap().set_text(text)?;
+        Ok(())
+    })
+}
+
+#[cfg(not(feature = "clipboard"))]
+pub(crate) fn copy_image(_image: &RgbaImage) -> Result<(), HintsError> {
+    Err(HintsError::Clipboard(String::from(
+        "this build does not support the clipboard",
+    )))
+}
+
+#[cfg(not(feature = "clipboard"))]
+pub(crate) fn copy_text(_text: &str) -> Result<(), HintsError> {
+    Err(HintsError::Clipboard(String::from(
+        "this build does not support the clipboard",
+    )))
+}
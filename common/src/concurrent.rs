@@ -5,9 +5,14 @@
  */
 
 use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::OnceLock;
 use std::thread;
 
-use tracing::error;
+use tracing::{error, trace, warn};
+
+type Task = Box<dyn FnOnce() + Send>;
+
+static MAIN_THREAD_TASKS: OnceLock<Sender<Task>> = OnceLock::new();
 
 /// Creates a `Sender`, `Receiver` pair that can be used to load data in a background thread.
 ///
@@ -39,6 +44,55 @@ where
     (tx_in, rx_out)
 }
 
+/// Schedules `f` to run on the main thread the next time [`MainThreadTasks::run_pending`] is
+/// called. Background threads use this for work that must happen on the main thread, such as
+/// creating textures or publishing datarefs.
+///
+/// The task is dropped with a warning if no [`MainThreadTasks`] has been installed.
+pub fn run_on_main_thread<F: FnOnce() + Send + 'static>(f: F) {
+    match MAIN_THREAD_TASKS.get() {
+        Some(tx) => {
+            if let Err(e) = tx.send(Box::new(f)) {
+                error!(error = %e, "Failed to schedule main thread task");
+            }
+        }
+        None => warn!("No main thread task queue installed, dropping task"),
+    }
+}
+
+/// The receiving end of the tasks scheduled by [`run_on_main_thread`], to be drained regularly by
+/// the main thread.
+pub struct MainThreadTasks {
+    rx: Receiver<Task>,
+}
+
+impl MainThreadTasks {
+    /// Installs the main thread task queue.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if a queue has already been installed.
+    pub fn install() -> Result<Self, &'static str> {
+        let (tx, rx) = channel::<Task>();
+        MAIN_THREAD_TASKS
+            .set(tx)
+            .map_err(|_| "Main thread task queue already installed")?;
+        Ok(MainThreadTasks { rx })
+    }
+
+    /// Runs all tasks that have been scheduled since the last call.
+    pub fn run_pending(&self) {
+        let mut count = 0;
+        while let Ok(task) = self.rx.try_recv() {
+            task();
+            count += 1;
+        }
+        if count > 0 {
+            trace!(count, "Ran main thread tasks");
+        }
+    }
+}
+
 fn spawn_thread_with_name<F, T, S>(name: S, f: F) -> thread::JoinHandle<T>
 where
     F: FnOnce() -> T,
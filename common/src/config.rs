@@ -0,0 +1,328 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use tracing::{error, info, warn};
+
+use crate::add_ons::AddOnCondition;
+use crate::error::{read_toml_file, HintsError};
+use crate::flow::FlowStep;
+use crate::hints::{check_in_hints_dir, is_hint_file};
+use crate::links::HintLink;
+use crate::panel_state::PanelSwitch;
+use crate::schedule::Validity;
+
+pub const CONFIG_FILENAME: &str = "hints.toml";
+/// The config file of the old hints-plugin build, which listed the images to show in order.
+const LEGACY_CONFIG_FILENAME: &str = "config.toml";
+
+/// The version of the hints app, which hints can require a minimum of with `requires_version`.
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Optional settings read from `hints.toml` in the hints directory.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// The oldest version of the app that can show the hints, such as `"0.6"` for hints that use a
+    /// kind of hint added in that version.
+    pub requires_version: Option<String>,
+    /// The language of the hints to show, such as `"de"`, rather than the sim's.
+    pub language: Option<String>,
+    /// The hint shown while the peek command is held, such as an emergency index.
+    pub peek: Option<String>,
+    pub pack: PackConfig,
+    pub window: WindowConfig,
+    pub usage: UsageConfig,
+    pub schedule: ScheduleConfig,
+    pub watermark: WatermarkConfig,
+    pub contents: ContentsConfig,
+    /// Groups of variants of the aircraft that the hints cover, such as
+    /// `model = ["-700", "-800"]`, from each of which the user chooses one.
+    pub variants: BTreeMap<String, Vec<String>>,
+    /// Settings for individual hints, keyed by filename.
+    pub hints: BTreeMap<String, HintConfig>,
+}
+
+impl Config {
+    /// Returns an error if the hints require a newer version of the app than this one.
+    pub fn check_version(&self) -> Result<(), HintsError> {
+        let required = match &self.requires_version {
+            Some(required) => required,
+            None => return Ok(()),
+        };
+        match (parse_version(required), parse_version(VERSION)) {
+            (Some(required_parts), Some(current_parts)) if required_parts > current_parts => {
+                Err(HintsError::Config(format!(
+                    "these hints need version {required} or later of FLC Hints, but version \
+                     {VERSION} is installed. Please update FLC Hints."
+                )))
+            }
+            (None, _) => {
+                warn!("Ignoring invalid requires_version {required:?} in {CONFIG_FILENAME}");
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Details of the hint pack, shown on the about page so that users can tell which revision they
+/// have and who to report problems to.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct PackConfig {
+    pub name: Option<String>,
+    pub author: Option<String>,
+    pub version: Option<String>,
+    pub aircraft: Option<String>,
+    pub url: Option<String>,
+}
+
+impl PackConfig {
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none()
+            && self.author.is_none()
+            && self.version.is_none()
+            && self.aircraft.is_none()
+            && self.url.is_none()
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct WindowConfig {
+    /// RGBA colour (0.0 - 1.0) of the window background around the hint, drawn over the
+    /// window's usual background, so an alpha below 1.0 tints it.
+    pub background: Option<[f32; 4]>,
+}
+
+/// Parses a version such as `0.5` or `0.5.1` into its major, minor and patch numbers, treating any
+/// that are missing as 0 so that `0.5` and `0.5.0` compare equal.
+#[must_use]
+pub fn parse_version(version: &str) -> Option<[u32; 3]> {
+    let mut parts = [0; 3];
+    let mut numbers = version.trim().split('.');
+    for part in &mut parts {
+        if let Some(number) = numbers.next() {
+            *part = number.parse().ok()?;
+        }
+    }
+    numbers.next().is_none().then_some(parts)
+}
+
+/// Loads `hints.toml` from `dir`, falling back to defaults if it is missing or invalid.
+pub fn load_config(dir: &Path) -> Config {
+    let filename = dir.join(CONFIG_FILENAME);
+    if !filename.is_file() {
+        return Config::default();
+    }
+    match read_toml_file::<Config>(&filename) {
+        Ok(config) => {
+            info!(
+                name = ?config.pack.name,
+                version = ?config.pack.version,
+                author = ?config.pack.author,
+                "Loaded hints config from {filename:?}"
+            );
+            config
+        }
+        Err(e) => {
+            error!(code = e.code(), "Unable to load hints config: {e}");
+            Config::default()
+        }
+    }
+}
+
+/// A `config.toml` from the old hints-plugin build, such as:
+///
+/// ```toml
+/// images = ["preflight.png", "before-start.png", "starting.png"]
+/// ```
+#[derive(Debug, Deserialize)]
+struct LegacyConfig {
+    images: Vec<PathBuf>,
+}
+
+/// Orders the hint `files` found in `dir` by the `images` of a legacy `config.toml`, if there is
+/// one, so that hints curated for the old hints-plugin build keep their order. Listed hints come
+/// first, followed by any others in their existing order.
+pub(crate) fn apply_legacy_order(dir: &Path, files: Vec<PathBuf>) -> Vec<PathBuf> {
+    let filename = dir.join(LEGACY_CONFIG_FILENAME);
+    if !filename.is_file() {
+        return files;
+    }
+    let legacy = match read_toml_file::<LegacyConfig>(&filename) {
+        Ok(legacy) => legacy,
+        Err(e) => {
+            error!(code = e.code(), "Unable to load legacy hints config: {e}");
+            return files;
+        }
+    };
+    info!(
+        count = legacy.images.len(),
+        "Ordering hints by legacy config {filename:?}"
+    );
+    let mut ordered = vec![];
+    for image in legacy.images {
+        let path = dir.join(&image);
+        if !is_hint_file(&path) || !path.is_file() {
+            warn!("Ignoring {image:?} in {LEGACY_CONFIG_FILENAME}, which is not a hint file");
+        } else if let Err(e) = check_in_hints_dir(dir, &image) {
+            warn!(
+                code = e.code(),
+                "Ignoring {image:?} in {LEGACY_CONFIG_FILENAME}: {e}"
+            );
+        } else if !ordered.contains(&path) {
+            ordered.push(path);
+        }
+    }
+    let unlisted: Vec<_> = files
+        .into_iter()
+        .filter(|file| !ordered.contains(file))
+        .collect();
+    ordered.extend(unlisted);
+    ordered
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct UsageConfig {
+    /// Whether to log which hints are viewed during each flight, and for how long.
+    pub log: bool,
+    /// Whether to keep running totals of how often each command is used and each hint is shown.
+    pub count: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ScheduleConfig {
+    /// Whether to skip hints that are not valid on the sim's date when paging through them,
+    /// rather than only marking them as out of date.
+    pub hide_expired: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct WatermarkConfig {
+    /// RGBA colour (0.0 - 1.0) of the watermarks drawn across hints.
+    pub colour: Option<[f32; 4]>,
+}
+
+/// The contents page, which lists the hints so that packs have an index without drawing one.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ContentsConfig {
+    /// Whether to show the contents page before the hints.
+    pub page: bool,
+    /// The heading of the contents page, such as the name of the aircraft. The pack name is used
+    /// if this isn't set.
+    pub title: Option<String>,
+}
+
+/// Settings for one hint in `hints.toml`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct HintConfig {
+    /// When the hint can be used, for hints such as seasonal procedures or charts for one AIRAC
+    /// cycle.
+    #[serde(flatten)]
+    pub validity: Validity,
+    /// Text drawn diagonally across the hint, such as `"Not for navigation"` for charts that are
+    /// only licensed for reference.
+    pub watermark: Option<String>,
+    /// The switches shown in a panel diagram, each marked over the image with whether it is set
+    /// as shown, such as `[[hints."overhead.png".switches]]` with `name = "Battery"`,
+    /// `at = [120, 45]` in image pixels and `expect = '"sim/cockpit/electrical/battery_on" == 0'`.
+    pub switches: Vec<PanelSwitch>,
+    /// The steps of a guided flow through a flow diagram, each highlighting a region of the image
+    /// in turn, such as `[[hints."start.png".flow]]` with `name = "Battery on"`,
+    /// `rect = [100, 40, 60, 30]` as the left, top, width and height in image pixels and an
+    /// optional `verify` expression that must be true before moving on.
+    pub flow: Vec<FlowStep>,
+    /// Regions of the hint that go to another hint when clicked, such as
+    /// `[[hints."index.png".links]]` with `rect = [20, 300, 200, 30]` as the left, top, width and
+    /// height in image pixels and `to = "engines.png"`.
+    pub links: Vec<HintLink>,
+    /// The variants of the aircraft the hint is for, such as `["-800"]`, from the groups in
+    /// `[variants]`. Hints are for every variant of groups they list none from.
+    pub variants: Vec<String>,
+    /// The add-ons the hint is only shown with or without, such as
+    /// `if_plugin = "RealityXP.GTN.750"`.
+    #[serde(flatten)]
+    pub add_ons: AddOnCondition,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::test_dir::TestDir;
+
+    use super::{apply_legacy_order, Config};
+
+    fn hints_dir(legacy_config: Option<&str>) -> (TestDir, Vec<PathBuf>) {
+        let dir = TestDir::new();
+        dir.touch(&["a.png", "b.png", "c.png"]);
+        let files: Vec<_> = ["a.png", "b.png", "c.png"]
+            .iter()
+            .map(|name| dir.join(name))
+            .collect();
+        if let Some(legacy_config) = legacy_config {
+            std::fs::write(dir.join("config.toml"), legacy_config).unwrap();
+        }
+        (dir, files)
+    }
+
+    fn names(files: &[PathBuf]) -> Vec<String> {
+        files
+            .iter()
+            .map(|file| file.file_name().unwrap().to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn listed_hints_come_first() {
+        let (dir, files) = hints_dir(Some(
+            r#"images = ["c.png", "missing.png", "../a.png", "config.toml", "a.png", "c.png"]"#,
+        ));
+        assert_eq!(
+            names(&apply_legacy_order(&dir, files)),
+            ["c.png", "a.png", "b.png"]
+        );
+    }
+
+    #[test]
+    fn order_is_unchanged_without_legacy_config() {
+        let (dir, files) = hints_dir(None);
+        assert_eq!(apply_legacy_order(&dir, files.clone()), files);
+        let (dir, files) = hints_dir(Some("images = 1"));
+        assert_eq!(apply_legacy_order(&dir, files.clone()), files);
+    }
+
+    #[test]
+    fn reads_hint_settings() {
+        let config: Config = toml::from_str(
+            r#"
+            [hints."approach-chart.png"]
+            valid_until = "2024-02-21"
+            watermark = "Not for navigation"
+
+            [hints."winter-ops.png"]
+            valid_from = "11-01"
+            "#,
+        )
+        .unwrap();
+        let chart = &config.hints["approach-chart.png"];
+        assert!(chart.validity.valid_until.is_some());
+        assert_eq!(chart.watermark.as_deref(), Some("Not for navigation"));
+        let winter = &config.hints["winter-ops.png"];
+        assert!(winter.validity.valid_from.is_some());
+        assert_eq!(winter.watermark, None);
+    }
+}
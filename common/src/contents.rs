@@ -0,0 +1,141 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+//! The contents page, an optional first page listing the other hints, so that a pack has an index
+//! without its author drawing one. Hints whose names have a category, such as
+//! `Charts/EGLL ILS 27L`, are listed under a heading for it.
+
+use std::path::Path;
+
+use imgui::Ui;
+
+use crate::view::HEADING_COLOUR;
+
+/// The name of the contents page, which has no file.
+pub(crate) const CONTENTS_NAME: &str = "Contents";
+
+/// The hints in a category, in the order they are shown.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Section<'a> {
+    /// The category, or `None` for hints without one.
+    pub(crate) category: Option<&'a str>,
+    /// The index of each hint and its title.
+    pub(crate) entries: Vec<(usize, &'a str)>,
+}
+
+/// Groups the hints called `names` into sections by category, in the order each category is
+/// first seen, leaving out the contents page itself.
+pub(crate) fn contents_sections<'a>(names: &[&'a str]) -> Vec<Section<'a>> {
+    let mut sections: Vec<Section> = vec![];
+    for (idx, name) in names.iter().enumerate() {
+        if *name == CONTENTS_NAME {
+            continue;
+        }
+        let (category, title) = match name.split_once('/') {
+            Some((category, title)) => (Some(category), title),
+            None => (None, hint_title(name)),
+        };
+        match sections
+            .iter_mut()
+            .find(|section| section.category == category)
+        {
+            Some(section) => section.entries.push((idx, title)),
+            None => sections.push(Section {
+                category,
+                entries: vec![(idx, title)],
+            }),
+        }
+    }
+    sections
+}
+
+/// The index of the first of each run of hints called `names` that share a category, so that a
+/// cockpit pager can skip from one category to the next. Hints without a category are a run of
+/// their own.
+pub(crate) fn category_starts(names: &[&str]) -> Vec<usize> {
+    names
+        .iter()
+        .enumerate()
+        .filter(|&(idx, name)| idx == 0 || category(names[idx - 1]) != category(name))
+        .map(|(idx, _)| idx)
+        .collect()
+}
+
+/// The category of the hint called `name`, the part before the first `/`.
+fn category(name: &str) -> Option<&str> {
+    name.split_once('/').map(|(category, _)| category)
+}
+
+/// The title of a hint file, its name without the extension.
+pub(crate) fn hint_title(name: &str) -> &str {
+    Path::new(name)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or(name)
+}
+
+/// Draws the contents page headed `heading`, returning the index of the hint that was clicked.
+/// Pages are numbered from 1, as they are elsewhere.
+pub(crate) fn draw_contents(ui: &Ui, heading: &str, sections: &[Section]) -> Option<usize> {
+    ui.text_colored(HEADING_COLOUR, heading);
+    ui.separator();
+    let mut clicked = None;
+    for section in sections {
+        if let Some(category) = section.category {
+            ui.spacing();
+            ui.text_colored(HEADING_COLOUR, category);
+        }
+        for &(idx, title) in &section.entries {
+            if ui.selectable(format!("{:>3}  {title}", idx + 1)) {
+                clicked = Some(idx);
+            }
+        }
+    }
+    clicked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_where_each_category_starts() {
+        let names = [
+            CONTENTS_NAME,
+            "before-start.png",
+            "Charts/EGLL ILS 27L",
+            "Charts/EGKK ILS 26L",
+            "Manual/1",
+            "takeoff.told",
+        ];
+        assert_eq!(category_starts(&names), [0, 2, 4, 5]);
+        assert!(category_starts(&[]).is_empty());
+    }
+
+    #[test]
+    fn groups_hints_by_category() {
+        let names = [
+            CONTENTS_NAME,
+            "before-start.png",
+            "Charts/EGLL ILS 27L",
+            "takeoff.told",
+            "Charts/EGKK ILS 26L",
+        ];
+        assert_eq!(
+            contents_sections(&names),
+            [
+                Section {
+                    category: None,
+                    entries: vec![(1, "before-start"), (3, "takeoff")],
+                },
+                Section {
+                    category: Some("Charts"),
+                    entries: vec![(2, "EGLL ILS 27L"), (4, "EGKK ILS 26L")],
+                },
+            ]
+        );
+    }
+}
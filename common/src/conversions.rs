@@ -0,0 +1,66 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+use std::cell::Cell;
+
+use imgui::Ui;
+
+/// A pair of units that a value can be converted between in either direction.
+struct Conversion {
+    units: [&'static str; 2],
+    forward: fn(f32) -> f32,
+    back: fn(f32) -> f32,
+}
+
+const CONVERSIONS: [Conversion; 5] = [
+    Conversion {
+        units: ["kg", "lb"],
+        forward: |kg| kg * 2.204_623,
+        back: |lb| lb / 2.204_623,
+    },
+    Conversion {
+        units: ["ft", "m"],
+        forward: |ft| ft * 0.3048,
+        back: |m| m / 0.3048,
+    },
+    Conversion {
+        units: ["inHg", "hPa"],
+        forward: |in_hg| in_hg * 33.863_89,
+        back: |hpa| hpa / 33.863_89,
+    },
+    Conversion {
+        units: ["°C", "°F"],
+        forward: |c| c * 9.0 / 5.0 + 32.0,
+        back: |f| (f - 32.0) * 5.0 / 9.0,
+    },
+    Conversion {
+        units: ["nm", "km"],
+        forward: |nm| nm * 1.852,
+        back: |km| km / 1.852,
+    },
+];
+
+/// Draws a field to type a value into and that value converted between common aviation units.
+pub(crate) fn draw_conversions(ui: &Ui, value: &Cell<f32>) {
+    let mut input = value.get();
+    ui.input_float("Convert##conversion", &mut input)
+        .display_format("%.2f")
+        .build();
+    value.set(input);
+    for Conversion {
+        units: [a, b],
+        forward,
+        back,
+    } in &CONVERSIONS
+    {
+        ui.text(format!(
+            "{input:.2} {a} = {:.2} {b}    {input:.2} {b} = {:.2} {a}",
+            forward(input),
+            back(input)
+        ));
+    }
+    ui.separator();
+}
@@ -0,0 +1,154 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+//! A self-check of the hints, written to the log as one block and shown in the window, so that a
+//! user asking for help can send everything needed in one go.
+
+use std::path::PathBuf;
+
+use imgui::Ui;
+use tracing::info;
+
+use crate::view::{draw_title, WARNING_COLOUR};
+
+const BYTES_PER_MB: u64 = 1024 * 1024;
+
+/// The state of the hints when the check was run.
+#[derive(Debug)]
+pub(crate) struct Diagnosis {
+    pub(crate) directory: PathBuf,
+    pub(crate) directory_found: bool,
+    /// The library of hints kept outside the aircraft, if it has one.
+    pub(crate) library: Option<PathBuf>,
+    /// The hint files that are shown, after leaving out other languages, variants and add-ons.
+    pub(crate) files: usize,
+    pub(crate) loaded: usize,
+    /// The names of the hint files that couldn't be decoded.
+    pub(crate) failures: Vec<String>,
+    /// The texture memory the loaded images need if they are all shown.
+    pub(crate) texture_bytes: u64,
+    /// The status of each service the hints use, such as the charts server.
+    pub(crate) services: Vec<(String, String)>,
+}
+
+impl Diagnosis {
+    /// The check as lines of text, one for each thing checked.
+    pub(crate) fn lines(&self) -> Vec<String> {
+        let found = if self.directory_found {
+            "found"
+        } else {
+            "not found"
+        };
+        let mut lines = vec![format!(
+            "Hints directory: {} ({found})",
+            self.directory.display()
+        )];
+        if let Some(library) = &self.library {
+            lines.push(format!("Hints library: {}", library.display()));
+        }
+        lines.extend([
+            format!("Hint files: {}", self.files),
+            format!("Hints loaded: {}", self.loaded),
+            format!("Decode failures: {}", self.failures.len()),
+        ]);
+        lines.extend(
+            self.failures
+                .iter()
+                .map(|failure| format!("  Unable to decode {failure}")),
+        );
+        lines.push(format!(
+            "Texture memory: about {} MB",
+            self.texture_bytes.div_ceil(BYTES_PER_MB)
+        ));
+        lines.extend(
+            self.services
+                .iter()
+                .map(|(service, status)| format!("{service}: {status}")),
+        );
+        lines
+    }
+
+    /// Writes the check to the log as a single message.
+    pub(crate) fn log(&self) {
+        info!(
+            files = self.files,
+            loaded = self.loaded,
+            failures = self.failures.len(),
+            texture_bytes = self.texture_bytes,
+            "Hints diagnosis:\n  {}",
+            self.lines().join("\n  ")
+        );
+    }
+
+    /// Draws the check, returning whether it has been closed.
+    pub(crate) fn draw(&self, ui: &Ui) -> bool {
+        draw_title(ui, "Hints diagnosis");
+        for line in self.lines() {
+            if line.starts_with("  Unable") {
+                ui.text_colored(WARNING_COLOUR, line);
+            } else {
+                ui.text_wrapped(line);
+            }
+        }
+        ui.spacing();
+        ui.text_disabled("This has also been written to the log.");
+        ui.button("Close")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_everything_checked() {
+        let diagnosis = Diagnosis {
+            directory: PathBuf::from("/aircraft/hints"),
+            directory_found: true,
+            library: None,
+            files: 3,
+            loaded: 2,
+            failures: vec![String::from("broken.png")],
+            texture_bytes: 3 * BYTES_PER_MB + 1,
+            services: vec![(
+                String::from("Charts server"),
+                String::from("not configured"),
+            )],
+        };
+        assert_eq!(
+            diagnosis.lines(),
+            [
+                "Hints directory: /aircraft/hints (found)",
+                "Hint files: 3",
+                "Hints loaded: 2",
+                "Decode failures: 1",
+                "  Unable to decode broken.png",
+                "Texture memory: about 4 MB",
+                "Charts server: not configured",
+            ]
+        );
+    }
+    #[test]
+    fn lists_library() {
+        let diagnosis = Diagnosis {
+            directory: PathBuf::from("/aircraft/hints"),
+            directory_found: true,
+            library: Some(PathBuf::from("/prefs/hints/library/aircraft")),
+            files: 1,
+            loaded: 1,
+            failures: vec![],
+            texture_bytes: 0,
+            services: vec![],
+        };
+        assert_eq!(
+            diagnosis.lines()[..2],
+            [
+                "Hints directory: /aircraft/hints (found)",
+                "Hints library: /prefs/hints/library/aircraft",
+            ]
+        );
+    }
+}
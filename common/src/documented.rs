@@ -0,0 +1,193 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+use serde::Serialize;
+
+use crate::error::HintsError;
+
+/// Comments written into a saved TOML file, so that it can be edited by hand with confidence.
+pub struct TomlDocs {
+    /// Written at the top of the file, typically what the file is for and, for files keyed by
+    /// hint or aircraft, what each entry holds.
+    pub header: &'static str,
+    /// Written above the keys and tables with these dotted paths, such as `position` or
+    /// `position.left`. Structs defined with [`documented_struct!`] have these in `FIELD_DOCS`.
+    pub fields: &'static [(&'static str, &'static str)],
+}
+
+/// Defines a struct with an associated constant `FIELD_DOCS`, the [`TomlDocs::fields`] for its
+/// fields taken from their doc comments, so that the comments in a saved file can't drift from
+/// the code. Doc comments must come before any `serde` attributes on a field, and fields can have
+/// no other attributes.
+#[macro_export]
+macro_rules! documented_struct {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident {
+            $(
+                $(#[doc = $doc:literal])*
+                $(#[serde $serde:tt])*
+                $field_vis:vis $field:ident: $ty:ty,
+            )*
+        }
+    ) => {
+        $(#[$attr])*
+        // the doc comments are written for the saved file rather than for rustdoc
+        #[allow(clippy::doc_markdown)]
+        $vis struct $name {
+            $(
+                $(#[doc = $doc])*
+                $(#[serde $serde])*
+                $field_vis $field: $ty,
+            )*
+        }
+
+        impl $name {
+            const FIELD_DOCS: &'static [(&'static str, &'static str)] = &[
+                $((stringify!($field), concat!($($doc, "\n"),*)),)*
+            ];
+        }
+    };
+}
+
+/// Serialises `value` as TOML with the comments in `docs`. Comments are ignored when the file is
+/// read back, so hand-edited files load as before.
+///
+/// # Errors
+///
+/// Will return `Err` if `value` cannot be represented as TOML.
+pub fn to_documented_toml<T: Serialize>(value: &T, docs: &TomlDocs) -> Result<String, HintsError> {
+    let toml = toml::to_string_pretty(value)?;
+    let mut documented = String::new();
+    push_comment(&mut documented, docs.header);
+    documented.push('\n');
+    let mut table = String::new();
+    for line in toml.lines() {
+        let path = if let Some(header) = table_header(line) {
+            table = header;
+            Some(table.clone())
+        } else {
+            line.split_once(" = ")
+                .filter(|_| !line.starts_with(char::is_whitespace))
+                .map(|(key, _)| match key.trim_matches('"') {
+                    key if table.is_empty() => key.to_string(),
+                    key => format!("{table}.{key}"),
+                })
+        };
+        if let Some((_, description)) =
+            path.and_then(|path| docs.fields.iter().find(|(field, _)| *field == path))
+        {
+            // doc comments keep the space after the slashes
+            let description: Vec<_> = description
+                .lines()
+                .map(|line| line.strip_prefix(' ').unwrap_or(line))
+                .collect();
+            push_comment(&mut documented, &description.join("\n"));
+        }
+        documented.push_str(line);
+        documented.push('\n');
+    }
+    Ok(documented)
+}
+
+/// The dotted path of the table started by `line`, if it is a table header.
+fn table_header(line: &str) -> Option<String> {
+    let line = line.trim();
+    if line.starts_with("[[") {
+        return None;
+    }
+    let name = line.strip_prefix('[')?.strip_suffix(']')?;
+    Some(
+        name.split('.')
+            .map(|part| part.trim().trim_matches('"'))
+            .collect::<Vec<_>>()
+            .join("."),
+    )
+}
+
+fn push_comment(documented: &mut String, text: &str) {
+    for line in text.lines() {
+        documented.push('#');
+        if !line.is_empty() {
+            documented.push(' ');
+            documented.push_str(line);
+        }
+        documented.push('\n');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde::{Deserialize, Serialize};
+
+    use super::{to_documented_toml, TomlDocs};
+
+    crate::documented_struct! {
+        #[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+        #[serde(default)]
+        struct Settings {
+            /// Whether the keys are registered
+            enabled: bool,
+            /// The key for each command, which is
+            /// documented over two lines
+            keys: BTreeMap<String, String>,
+            window: Window,
+        }
+    }
+
+    #[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+    struct Window {
+        left: i32,
+        top: i32,
+    }
+
+    const DOCS: TomlDocs = TomlDocs {
+        header: "Settings for the tests.",
+        fields: Settings::FIELD_DOCS,
+    };
+
+    fn settings() -> Settings {
+        Settings {
+            enabled: true,
+            keys: BTreeMap::from([(String::from("flc/hints/next"), String::from("ctrl+n"))]),
+            window: Window { left: 10, top: 20 },
+        }
+    }
+
+    #[test]
+    fn takes_comments_from_doc_comments() {
+        let toml = to_documented_toml(&settings(), &DOCS).unwrap();
+        assert!(toml.starts_with("# Settings for the tests.\n\n"));
+        assert!(toml.contains("# Whether the keys are registered\nenabled = true\n"));
+        assert!(toml.contains(
+            "# The key for each command, which is\n# documented over two lines\n[keys]\n"
+        ));
+        assert_eq!(toml.lines().filter(|line| line.starts_with('#')).count(), 4);
+    }
+
+    #[test]
+    fn comments_nested_keys() {
+        let docs = TomlDocs {
+            header: "Settings for the tests.",
+            fields: &[("window.left", "The left edge")],
+        };
+        let toml = to_documented_toml(&settings(), &docs).unwrap();
+        assert!(toml.contains("# The left edge\nleft = 10\n"));
+    }
+
+    #[test]
+    fn documented_files_read_back_unchanged() {
+        let toml = to_documented_toml(&settings(), &DOCS).unwrap();
+        assert_eq!(toml::from_str::<Settings>(&toml).unwrap(), settings());
+        let empty = to_documented_toml(&Settings::default(), &DOCS).unwrap();
+        assert_eq!(
+            toml::from_str::<Settings>(&empty).unwrap(),
+            Settings::default()
+        );
+    }
+}
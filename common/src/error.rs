@@ -0,0 +1,110 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::documented::{to_documented_toml, TomlDocs};
+use crate::expression::ExpressionError;
+
+/// Errors reported by the hints app. Each variant has a stable code, shown in the log and the
+/// error page, so that a problem can be identified from a single code.
+#[derive(Error, Debug)]
+pub enum HintsError {
+    #[error("Unable to load hints: {0}")]
+    Config(String),
+    #[error("Unable to read or write file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("{0:?} is outside the hints directory")]
+    OutsideHintsDirectory(PathBuf),
+    #[error("Unable to watch the hints directory: {0}")]
+    Watch(String),
+    #[error("Unable to decode image: {0}")]
+    Image(#[from] image::ImageError),
+    #[error("Unable to show PDF: {0}")]
+    Pdf(String),
+    #[error("Unable to parse TOML: {0}")]
+    TomlParse(#[from] toml::de::Error),
+    #[error("Unable to write TOML: {0}")]
+    TomlWrite(#[from] toml::ser::Error),
+    #[error("Invalid expression: {0}")]
+    Expression(#[from] ExpressionError),
+    #[error("Unable to copy to the clipboard: {0}")]
+    Clipboard(String),
+    #[error("Unable to get charts: {0}")]
+    Charts(String),
+    #[error("Unexpected error: {0}")]
+    Panic(String),
+}
+
+impl HintsError {
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        match self {
+            HintsError::Config(_) => "E100",
+            HintsError::Io(_) => "E200",
+            HintsError::OutsideHintsDirectory(_) => "E201",
+            HintsError::Watch(_) => "E202",
+            HintsError::Image(_) => "E300",
+            HintsError::Pdf(_) => "E301",
+            HintsError::TomlParse(_) => "E400",
+            HintsError::TomlWrite(_) => "E401",
+            HintsError::Expression(_) => "E500",
+            HintsError::Clipboard(_) => "E600",
+            HintsError::Charts(_) => "E700",
+            HintsError::Panic(_) => "E900",
+        }
+    }
+}
+
+/// Reads `path` as TOML.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or parsed.
+pub fn read_toml_file<T: DeserializeOwned>(path: &Path) -> Result<T, HintsError> {
+    let toml = std::fs::read_to_string(path)?;
+    Ok(toml::from_str(&toml)?)
+}
+
+/// Writes `value` to `path` as TOML, with the comments in `docs`.
+///
+/// # Errors
+///
+/// Returns an error if the value cannot be serialised or the file cannot be written.
+pub fn write_toml_file<T: Serialize>(
+    path: &Path,
+    value: &T,
+    docs: &TomlDocs,
+) -> Result<(), HintsError> {
+    let toml = to_documented_toml(value, docs)?;
+    std::fs::write(path, toml)?;
+    Ok(())
+}
+
+#[cfg(feature = "clipboard")]
+impl From<arboard::Error> for HintsError {
+    fn from(error: arboard::Error) -> Self {
+        HintsError::Clipboard(error.to_string())
+    }
+}
+
+#[cfg(feature = "pdf")]
+impl From<pdfium_render::prelude::PdfiumError> for HintsError {
+    fn from(error: pdfium_render::prelude::PdfiumError) -> Self {
+        HintsError::Pdf(error.to_string())
+    }
+}
+
+#[cfg(feature = "watch")]
+impl From<notify::Error> for HintsError {
+    fn from(error: notify::Error) -> Self {
+        HintsError::Watch(error.to_string())
+    }
+}
@@ -0,0 +1,563 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::template::DatarefReader;
+
+/// How deeply brackets, `-` and `not` can be nested, so that a pathological expression is
+/// reported as an error rather than overflowing the stack while it is parsed or evaluated.
+const MAX_DEPTH: usize = 64;
+/// The most tokens an expression can have, which also limits how deeply a chain such as
+/// `1 + 1 + 1 ...` is nested when it is evaluated.
+const MAX_TOKENS: usize = 1024;
+
+/// A small arithmetic and logical expression over dataref values, for example
+/// `"sim/flightmodel/weight/m_total" / 1000 * 0.6 + 85` or
+/// `"sim/cockpit2/controls/parking_brake_ratio" > 0.5 and "sim/cockpit/electrical/battery_on" == 1`.
+///
+/// Datarefs are written as quoted names, with `[<index>]` after the closing quote for an array
+/// element. Numbers are the only values: comparisons and `and`, `or` and `not` give 1 for true and
+/// 0 for false, and treat any non-zero value as true. Operators, from lowest to highest
+/// precedence, are `or` (`||`), `and` (`&&`), comparisons (`==`, `!=`, `<`, `<=`, `>`, `>=`),
+/// `+` and `-`, `*` and `/`, and unary `-` and `not` (`!`).
+///
+/// In TOML files an expression is written as a string, such as
+/// `expect = '"sim/cockpit/electrical/battery_on" == 0'`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Expression {
+    root: Node,
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("{message} at column {column}")]
+pub struct ExpressionError {
+    pub column: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Number(f64),
+    Dataref { name: String, index: Option<usize> },
+    Negate(Box<Node>),
+    Not(Box<Node>),
+    Binary(Operator, Box<Node>, Box<Node>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Or,
+    And,
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Dataref(String),
+    Operator(Operator),
+    Not,
+    Minus,
+    LeftParen,
+    RightParen,
+    LeftBracket,
+    RightBracket,
+}
+
+impl Expression {
+    /// # Errors
+    ///
+    /// Returns an error giving the column of the first problem if `text` is not a valid
+    /// expression.
+    pub fn parse(text: &str) -> Result<Self, ExpressionError> {
+        let tokens = tokenize(text)?;
+        if let Some((column, _)) = tokens.get(MAX_TOKENS) {
+            return Err(error(*column, "Expression is too long"));
+        }
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+            end_column: text.chars().count() + 1,
+            depth: 0,
+        };
+        let root = parser.parse_or()?;
+        if let Some((column, _)) = parser.tokens.get(parser.position) {
+            return Err(error(*column, "Unexpected text after expression"));
+        }
+        Ok(Expression { root })
+    }
+
+    /// An expression that is just the value of the dataref `name`, or of element `index` of it.
+    pub(crate) fn dataref(name: &str, index: Option<usize>) -> Self {
+        Expression {
+            root: Node::Dataref {
+                name: name.to_string(),
+                index,
+            },
+        }
+    }
+
+    /// Evaluates the expression, returning `None` if a dataref cannot be read or a division by
+    /// zero occurs.
+    pub fn evaluate(&self, datarefs: &dyn DatarefReader) -> Option<f64> {
+        self.root.evaluate(datarefs)
+    }
+}
+
+impl TryFrom<String> for Expression {
+    type Error = ExpressionError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Expression::parse(&value)
+    }
+}
+
+impl Node {
+    fn evaluate(&self, datarefs: &dyn DatarefReader) -> Option<f64> {
+        match self {
+            Node::Number(value) => Some(*value),
+            Node::Dataref { name, index } => datarefs.read(name, *index),
+            Node::Negate(node) => node.evaluate(datarefs).map(|value| -value),
+            Node::Not(node) => node
+                .evaluate(datarefs)
+                .map(|value| from_bool(!is_true(value))),
+            Node::Binary(Operator::And, left, right) => {
+                if is_true(left.evaluate(datarefs)?) {
+                    right
+                        .evaluate(datarefs)
+                        .map(|value| from_bool(is_true(value)))
+                } else {
+                    Some(0.0)
+                }
+            }
+            Node::Binary(Operator::Or, left, right) => {
+                if is_true(left.evaluate(datarefs)?) {
+                    Some(1.0)
+                } else {
+                    right
+                        .evaluate(datarefs)
+                        .map(|value| from_bool(is_true(value)))
+                }
+            }
+            Node::Binary(operator, left, right) => {
+                let left = left.evaluate(datarefs)?;
+                let right = right.evaluate(datarefs)?;
+                #[allow(clippy::float_cmp)]
+                let value = match operator {
+                    Operator::Equal => from_bool(left == right),
+                    Operator::NotEqual => from_bool(left != right),
+                    Operator::Less => from_bool(left < right),
+                    Operator::LessOrEqual => from_bool(left <= right),
+                    Operator::Greater => from_bool(left > right),
+                    Operator::GreaterOrEqual => from_bool(left >= right),
+                    Operator::Add => left + right,
+                    Operator::Subtract => left - right,
+                    Operator::Multiply => left * right,
+                    Operator::Divide if right == 0.0 => return None,
+                    Operator::Divide => left / right,
+                    Operator::And | Operator::Or => unreachable!(),
+                };
+                Some(value)
+            }
+        }
+    }
+}
+
+fn is_true(value: f64) -> bool {
+    value != 0.0
+}
+
+fn from_bool(value: bool) -> f64 {
+    if value {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+fn error(column: usize, message: &str) -> ExpressionError {
+    ExpressionError {
+        column,
+        message: message.to_string(),
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<(usize, Token)>, ExpressionError> {
+    let mut tokens = vec![];
+    let mut chars = text.char_indices().peekable();
+    let mut column = 0;
+    while let Some((offset, c)) = chars.next() {
+        column += 1;
+        let start = column;
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '0'..='9' | '.' => {
+                let mut end = offset + c.len_utf8();
+                while let Some((next, d)) = chars.next_if(|(_, d)| d.is_ascii_digit() || *d == '.')
+                {
+                    column += 1;
+                    end = next + d.len_utf8();
+                }
+                let number = &text[offset..end];
+                Token::Number(
+                    number
+                        .parse()
+                        .map_err(|_| error(start, &format!("Invalid number '{number}'")))?,
+                )
+            }
+            '"' => {
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, d)) => name.push(d),
+                        None => return Err(error(start, "Unterminated dataref name")),
+                    }
+                }
+                column += name.chars().count() + 1;
+                if name.is_empty() {
+                    return Err(error(start, "Empty dataref name"));
+                }
+                Token::Dataref(name)
+            }
+            '+' => Token::Operator(Operator::Add),
+            '-' => Token::Minus,
+            '*' => Token::Operator(Operator::Multiply),
+            '/' => Token::Operator(Operator::Divide),
+            '(' => Token::LeftParen,
+            ')' => Token::RightParen,
+            '[' => Token::LeftBracket,
+            ']' => Token::RightBracket,
+            '<' | '>' | '=' | '!' => {
+                let equals = next_is(&mut chars, &mut column, '=');
+                match (c, equals) {
+                    ('<', false) => Token::Operator(Operator::Less),
+                    ('<', true) => Token::Operator(Operator::LessOrEqual),
+                    ('>', false) => Token::Operator(Operator::Greater),
+                    ('>', true) => Token::Operator(Operator::GreaterOrEqual),
+                    ('=', true) => Token::Operator(Operator::Equal),
+                    ('!', true) => Token::Operator(Operator::NotEqual),
+                    ('!', false) => Token::Not,
+                    _ => return Err(error(start, "Expected '==' for comparison")),
+                }
+            }
+            '&' | '|' => {
+                if !next_is(&mut chars, &mut column, c) {
+                    return Err(error(start, &format!("Expected '{c}{c}'")));
+                }
+                Token::Operator(if c == '&' {
+                    Operator::And
+                } else {
+                    Operator::Or
+                })
+            }
+            c if c.is_ascii_alphabetic() => {
+                let mut word = String::from(c);
+                while let Some((_, d)) = chars.next_if(|(_, d)| d.is_ascii_alphanumeric()) {
+                    column += 1;
+                    word.push(d);
+                }
+                match word.as_str() {
+                    "and" => Token::Operator(Operator::And),
+                    "or" => Token::Operator(Operator::Or),
+                    "not" => Token::Not,
+                    _ => {
+                        return Err(error(
+                            start,
+                            &format!("Unknown word '{word}', dataref names must be quoted"),
+                        ))
+                    }
+                }
+            }
+            c => return Err(error(start, &format!("Unexpected character '{c}'"))),
+        };
+        tokens.push((start, token));
+    }
+    Ok(tokens)
+}
+
+fn next_is(chars: &mut Peekable<CharIndices>, column: &mut usize, expected: char) -> bool {
+    if chars.next_if(|(_, c)| *c == expected).is_some() {
+        *column += 1;
+        true
+    } else {
+        false
+    }
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    position: usize,
+    end_column: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(_, token)| token)
+    }
+
+    fn column(&self) -> usize {
+        self.tokens
+            .get(self.position)
+            .map_or(self.end_column, |(column, _)| *column)
+    }
+
+    fn advance(&mut self) -> Option<Token> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .map(|(_, token)| token.clone());
+        self.position += 1;
+        token
+    }
+
+    fn next_operator(&mut self, operators: &[Operator]) -> Option<Operator> {
+        match self.peek() {
+            Some(Token::Operator(operator)) if operators.contains(operator) => {
+                let operator = *operator;
+                self.position += 1;
+                Some(operator)
+            }
+            Some(Token::Minus) if operators.contains(&Operator::Subtract) => {
+                self.position += 1;
+                Some(Operator::Subtract)
+            }
+            _ => None,
+        }
+    }
+
+    fn parse_binary(
+        &mut self,
+        operators: &[Operator],
+        operand: fn(&mut Self) -> Result<Node, ExpressionError>,
+    ) -> Result<Node, ExpressionError> {
+        let mut node = operand(self)?;
+        while let Some(operator) = self.next_operator(operators) {
+            node = Node::Binary(operator, Box::new(node), Box::new(operand(self)?));
+        }
+        Ok(node)
+    }
+
+    fn parse_or(&mut self) -> Result<Node, ExpressionError> {
+        self.parse_binary(&[Operator::Or], Self::parse_and)
+    }
+
+    fn parse_and(&mut self) -> Result<Node, ExpressionError> {
+        self.parse_binary(&[Operator::And], Self::parse_comparison)
+    }
+
+    fn parse_comparison(&mut self) -> Result<Node, ExpressionError> {
+        let left = self.parse_sum()?;
+        let comparisons = [
+            Operator::Equal,
+            Operator::NotEqual,
+            Operator::Less,
+            Operator::LessOrEqual,
+            Operator::Greater,
+            Operator::GreaterOrEqual,
+        ];
+        if let Some(operator) = self.next_operator(&comparisons) {
+            let right = self.parse_sum()?;
+            if self.next_operator(&comparisons).is_some() {
+                return Err(error(
+                    self.tokens[self.position - 1].0,
+                    "Comparisons cannot be chained, use 'and'",
+                ));
+            }
+            Ok(Node::Binary(operator, Box::new(left), Box::new(right)))
+        } else {
+            Ok(left)
+        }
+    }
+
+    fn parse_sum(&mut self) -> Result<Node, ExpressionError> {
+        self.parse_binary(&[Operator::Add, Operator::Subtract], Self::parse_product)
+    }
+
+    fn parse_product(&mut self) -> Result<Node, ExpressionError> {
+        self.parse_binary(&[Operator::Multiply, Operator::Divide], Self::parse_unary)
+    }
+
+    fn parse_unary(&mut self) -> Result<Node, ExpressionError> {
+        if self.depth == MAX_DEPTH {
+            return Err(error(self.column(), "Expression is nested too deeply"));
+        }
+        self.depth += 1;
+        let node = self.parse_nested_unary();
+        self.depth -= 1;
+        node
+    }
+
+    fn parse_nested_unary(&mut self) -> Result<Node, ExpressionError> {
+        match self.peek() {
+            Some(Token::Minus) => {
+                self.position += 1;
+                Ok(Node::Negate(Box::new(self.parse_unary()?)))
+            }
+            Some(Token::Not) => {
+                self.position += 1;
+                Ok(Node::Not(Box::new(self.parse_unary()?)))
+            }
+            _ => self.parse_primary(),
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<Node, ExpressionError> {
+        let column = self.column();
+        match self.advance() {
+            Some(Token::Number(value)) => Ok(Node::Number(value)),
+            Some(Token::Dataref(name)) => {
+                let index = if self.peek() == Some(&Token::LeftBracket) {
+                    self.position += 1;
+                    Some(self.parse_index()?)
+                } else {
+                    None
+                };
+                Ok(Node::Dataref { name, index })
+            }
+            Some(Token::LeftParen) => {
+                let node = self.parse_or()?;
+                let column = self.column();
+                match self.advance() {
+                    Some(Token::RightParen) => Ok(node),
+                    _ => Err(error(column, "Expected ')'")),
+                }
+            }
+            Some(_) => Err(error(column, "Expected a number, dataref or '('")),
+            None => Err(error(column, "Unexpected end of expression")),
+        }
+    }
+
+    fn parse_index(&mut self) -> Result<usize, ExpressionError> {
+        let column = self.column();
+        let index = match self.advance() {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            Some(Token::Number(value)) if value.fract() == 0.0 => value as usize,
+            _ => return Err(error(column, "Expected a whole number array index")),
+        };
+        let column = self.column();
+        match self.advance() {
+            Some(Token::RightBracket) => Ok(index),
+            _ => Err(error(column, "Expected ']'")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::template::FixedDatarefs;
+
+    fn evaluate(text: &str) -> Option<f64> {
+        let values = FixedDatarefs::new([("sim/weight", 60000.0), ("sim/brake", 1.0)])
+            .with_element("sim/n1", 1, 85.5);
+        Expression::parse(text).unwrap().evaluate(&values)
+    }
+
+    fn parse_error(text: &str) -> ExpressionError {
+        Expression::parse(text).unwrap_err()
+    }
+
+    #[test]
+    fn arithmetic_follows_precedence() {
+        assert_eq!(evaluate("1 + 2 * 3"), Some(7.0));
+        assert_eq!(evaluate("(1 + 2) * 3"), Some(9.0));
+        assert_eq!(evaluate("10 - 4 - 3"), Some(3.0));
+        assert_eq!(evaluate("-2 * -3"), Some(6.0));
+        assert_eq!(evaluate("7 / 2"), Some(3.5));
+    }
+
+    #[test]
+    fn reads_datarefs() {
+        assert_eq!(evaluate(r#""sim/weight" / 1000 + 80"#), Some(140.0));
+        assert_eq!(evaluate(r#""sim/n1"[1]"#), Some(85.5));
+        assert_eq!(evaluate(r#""sim/missing" + 1"#), None);
+        assert_eq!(evaluate(r#""sim/n1"[0]"#), None);
+    }
+
+    #[test]
+    fn comparisons_and_logic() {
+        assert_eq!(evaluate("1 < 2"), Some(1.0));
+        assert_eq!(evaluate("2 <= 1"), Some(0.0));
+        assert_eq!(evaluate("1 == 1 and 2 != 2"), Some(0.0));
+        assert_eq!(evaluate("1 == 2 or 2 >= 2"), Some(1.0));
+        assert_eq!(evaluate("1 > 2 || 3 > 2 && 0"), Some(0.0));
+        assert_eq!(evaluate(r#"not "sim/brake""#), Some(0.0));
+        assert_eq!(evaluate("!0"), Some(1.0));
+    }
+
+    #[test]
+    fn logic_short_circuits_missing_datarefs() {
+        assert_eq!(evaluate(r#"0 and "sim/missing""#), Some(0.0));
+        assert_eq!(evaluate(r#"1 or "sim/missing""#), Some(1.0));
+        assert_eq!(evaluate(r#"1 and "sim/missing""#), None);
+    }
+
+    #[test]
+    fn division_by_zero_has_no_value() {
+        assert_eq!(evaluate("1 / 0"), None);
+    }
+
+    #[test]
+    fn reports_parse_errors_with_column() {
+        assert_eq!(
+            parse_error("1 +"),
+            ExpressionError {
+                column: 4,
+                message: String::from("Unexpected end of expression")
+            }
+        );
+        assert_eq!(parse_error("(1 + 2").column, 7);
+        assert_eq!(parse_error("1 = 2").column, 3);
+        assert_eq!(parse_error("1 & 2").column, 3);
+        assert_eq!(parse_error("1 2").column, 3);
+        assert_eq!(parse_error(r#""sim/weight"#).column, 1);
+        assert_eq!(parse_error(r#""sim/n1"[1.5]"#).column, 10);
+        assert_eq!(parse_error("1 < 2 < 3").column, 7);
+        assert_eq!(parse_error("weight > 1").column, 1);
+        assert_eq!(parse_error("1 # 2").column, 3);
+        assert_eq!(parse_error("1..2").column, 1);
+    }
+
+    #[test]
+    fn rejects_deep_nesting() {
+        let nested = |depth| format!("{}1{}", "(".repeat(depth), ")".repeat(depth));
+        assert_eq!(evaluate(&nested(MAX_DEPTH - 1)), Some(1.0));
+        assert_eq!(parse_error(&nested(100_000)).column, MAX_DEPTH + 1);
+        assert_eq!(parse_error(&"-".repeat(100_000)).column, MAX_DEPTH + 1);
+    }
+
+    #[test]
+    fn rejects_long_expressions() {
+        let sum = |terms| vec!["1"; terms].join("+");
+        assert_eq!(evaluate(&sum(MAX_TOKENS / 2)), Some(512.0));
+        assert_eq!(parse_error(&sum(100_000)).column, MAX_TOKENS + 1);
+    }
+
+    #[test]
+    fn error_message_includes_column() {
+        assert_eq!(
+            parse_error("1 +").to_string(),
+            "Unexpected end of expression at column 4"
+        );
+    }
+}
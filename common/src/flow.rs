@@ -0,0 +1,188 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+//! Guided flows, which step through the regions of a flow diagram in order, highlighting each in
+//! turn, for learning flows such as engine start. A step can have an expression that must be
+//! true, such as a switch being on, before the flow moves on from it.
+
+use imgui::Ui;
+use serde::Deserialize;
+
+use crate::expression::Expression;
+use crate::template::DatarefReader;
+use crate::view::{image_rect_to_screen, pixel_rect_to_uv, Viewport};
+
+const HIGHLIGHT_COLOUR: [f32; 4] = [1.0, 0.8, 0.0, 1.0];
+const HIGHLIGHT_FILL: [f32; 4] = [1.0, 0.8, 0.0, 0.2];
+const HIGHLIGHT_THICKNESS: f32 = 3.0;
+const DONE_COLOUR: [f32; 4] = [0.2, 0.9, 0.2, 1.0];
+const WAITING_COLOUR: [f32; 4] = [1.0, 0.3, 0.3, 1.0];
+
+/// A step of a guided flow, set in `hints.toml`.
+#[derive(Debug, Deserialize)]
+pub struct FlowStep {
+    /// What to do in the step, shown above the image.
+    pub(crate) name: String,
+    /// The region of the image highlighted during the step, as the left, top, width and height
+    /// in pixels.
+    pub(crate) rect: [f32; 4],
+    /// True once the step has been done, if it can be checked.
+    pub(crate) verify: Option<Expression>,
+}
+
+impl FlowStep {
+    /// Whether the step has been done, or `None` if it can't be told because its datarefs can't be
+    /// read. Steps without an expression to verify them are always done.
+    fn is_done(&self, datarefs: &dyn DatarefReader) -> Option<bool> {
+        match &self.verify {
+            Some(verify) => verify.evaluate(datarefs).map(|value| value != 0.0),
+            None => Some(true),
+        }
+    }
+}
+
+/// The step of `steps` after `step`, starting the flow if it hasn't been started and returning
+/// `None` once it is finished. The flow stays at `step` if it is known not to have been done, so
+/// that it can't be skipped, but moves on if its datarefs can't be read, such as when there is no
+/// sim.
+pub(crate) fn next_step(
+    steps: &[FlowStep],
+    step: Option<usize>,
+    datarefs: &dyn DatarefReader,
+) -> Option<usize> {
+    match step {
+        None if steps.is_empty() => None,
+        None => Some(0),
+        Some(idx) => match steps.get(idx) {
+            Some(current) if current.is_done(datarefs) == Some(false) => Some(idx),
+            _ => Some(idx + 1).filter(|next| *next < steps.len()),
+        },
+    }
+}
+
+/// Draws a line describing step `step` of `steps`, or how to start the flow if it isn't started,
+/// for drawing above the image.
+pub(crate) fn draw_step_text(
+    ui: &Ui,
+    steps: &[FlowStep],
+    step: Option<usize>,
+    datarefs: &dyn DatarefReader,
+) {
+    let (idx, current) = match step.and_then(|idx| steps.get(idx).map(|current| (idx, current))) {
+        Some(step) => step,
+        None => {
+            ui.text_disabled(format!(
+                "Guided flow of {} steps: use Next step to start",
+                steps.len()
+            ));
+            return;
+        }
+    };
+    ui.text(format!(
+        "Step {} of {}: {}",
+        idx + 1,
+        steps.len(),
+        current.name
+    ));
+    match current.is_done(datarefs) {
+        Some(true) if current.verify.is_some() => {
+            ui.same_line();
+            ui.text_colored(DONE_COLOUR, "(done)");
+        }
+        Some(false) => {
+            ui.same_line();
+            ui.text_colored(WAITING_COLOUR, "(waiting)");
+        }
+        _ => {}
+    }
+}
+
+/// Highlights the region of `step` in an image of `image_size` that was drawn into `region` with
+/// `viewport`.
+pub(crate) fn draw_step_highlight(
+    ui: &Ui,
+    step: &FlowStep,
+    image_size: (u32, u32),
+    viewport: Viewport,
+    region: ([f32; 2], [f32; 2]),
+) {
+    if image_size.0 == 0 || image_size.1 == 0 {
+        return;
+    }
+    let uv_rect = pixel_rect_to_uv(step.rect, image_size);
+    if let Some((min, max)) = image_rect_to_screen(uv_rect, image_size, viewport, region) {
+        let draw_list = ui.get_window_draw_list();
+        draw_list
+            .add_rect(min, max, HIGHLIGHT_FILL)
+            .filled(true)
+            .build();
+        draw_list
+            .add_rect(min, max, HIGHLIGHT_COLOUR)
+            .thickness(HIGHLIGHT_THICKNESS)
+            .build();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::template::FixedDatarefs;
+
+    fn step(verify: Option<&str>) -> FlowStep {
+        FlowStep {
+            name: String::from("Battery on"),
+            rect: [10.0, 20.0, 30.0, 40.0],
+            verify: verify.map(|verify| Expression::parse(verify).unwrap()),
+        }
+    }
+
+    #[test]
+    fn steps_through_the_flow() {
+        let values = FixedDatarefs::new([]);
+        let steps = [step(None), step(None)];
+        assert_eq!(next_step(&steps, None, &values), Some(0));
+        assert_eq!(next_step(&steps, Some(0), &values), Some(1));
+        assert_eq!(next_step(&steps, Some(1), &values), None);
+        assert_eq!(next_step(&[], None, &values), None);
+    }
+
+    #[test]
+    fn waits_for_steps_to_be_done() {
+        let steps = [step(Some("\"sim/battery\" == 1")), step(None)];
+        let off = FixedDatarefs::new([("sim/battery", 0.0)]);
+        assert_eq!(next_step(&steps, Some(0), &off), Some(0));
+        let on = FixedDatarefs::new([("sim/battery", 1.0)]);
+        assert_eq!(next_step(&steps, Some(0), &on), Some(1));
+        let unknown = FixedDatarefs::new([]);
+        assert_eq!(next_step(&steps, Some(0), &unknown), Some(1));
+    }
+
+    #[test]
+    fn reads_steps_from_toml() {
+        #[derive(Deserialize)]
+        struct Hint {
+            flow: Vec<FlowStep>,
+        }
+
+        let hint: Hint = toml::from_str(
+            r#"
+            [[flow]]
+            name = "Battery on"
+            rect = [100, 40, 60, 30]
+            verify = '"sim/cockpit/electrical/battery_on" == 1'
+
+            [[flow]]
+            name = "Check the annunciators"
+            rect = [200, 40, 120, 80]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(hint.flow.len(), 2);
+        assert_eq!(hint.flow[0].rect, [100.0, 40.0, 60.0, 30.0]);
+        assert!(hint.flow[0].verify.is_some());
+        assert!(hint.flow[1].verify.is_none());
+    }
+}
@@ -0,0 +1,242 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use imgui::Ui;
+use serde::Deserialize;
+
+use crate::error::HintsError;
+use crate::inputs::{load_inputs, read_calculator, save_inputs};
+use crate::template::DatarefReader;
+use crate::view::{draw_title, HEADING_COLOUR, WARNING_COLOUR};
+
+const RESERVE_INPUT: &str = "Reserve fuel";
+const FINAL_RESERVE_INPUT: &str = "Final reserve minutes";
+const DEFAULT_FINAL_RESERVE_MINUTES: f32 = 30.0;
+/// Fuel at the destination within this fraction above the reserves is shown as a caution.
+const CAUTION_MARGIN: f64 = 0.1;
+const CAUTION_COLOUR: [f32; 4] = [1.0, 0.6, 0.0, 1.0];
+const LB_PER_KG: f64 = 2.204_62;
+const KNOTS_PER_METRE_PER_SECOND: f64 = 1.943_84;
+const SECONDS_PER_HOUR: f64 = 3600.0;
+
+const FUEL_DATAREF: &str = "sim/flightmodel/weight/m_fuel_total";
+const FUEL_FLOW_DATAREF: &str = "sim/cockpit2/engine/indicators/fuel_flow_kg_sec";
+const GROUND_SPEED_DATAREF: &str = "sim/flightmodel/position/groundspeed";
+const DEFAULT_DISTANCE_DATAREF: &str = "sim/cockpit2/radios/indicators/gps_dme_distance_nm";
+
+/// The most engines whose fuel flow is read from the sim.
+const MAX_ENGINES: usize = 16;
+
+/// A kneeboard page counting down the fuel and time to the destination like an operational
+/// flight plan, read from a `.fuel` file containing TOML such as:
+///
+/// ```toml
+/// title = "Fuel"
+/// units = "lb"
+/// reserve = 300.0
+/// final_reserve_minutes = 45.0
+/// ```
+///
+/// The fuel remaining, fuel flow and ground speed are read from the sim every frame. The time to
+/// the destination is the distance to the GPS's active waypoint, or the distance in nm given by
+/// `distance_dataref`, at the current ground speed. The reserves, a fixed amount of fuel plus
+/// enough for a number of minutes at the current flow, can be changed on the page and are saved
+/// like the inputs of calculator hints.
+#[derive(Debug)]
+pub struct Fuel {
+    dir: PathBuf,
+    name: String,
+    file: FuelFile,
+    reserve: Cell<f32>,
+    final_reserve_minutes: Cell<f32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FuelFile {
+    title: Option<String>,
+    #[serde(default)]
+    units: FuelUnits,
+    #[serde(default)]
+    reserve: f32,
+    final_reserve_minutes: Option<f32>,
+    distance_dataref: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum FuelUnits {
+    #[default]
+    Kg,
+    Lb,
+}
+
+impl FuelUnits {
+    fn label(self) -> &'static str {
+        match self {
+            FuelUnits::Kg => "kg",
+            FuelUnits::Lb => "lb",
+        }
+    }
+
+    fn convert_kg(self, kg: f64) -> f64 {
+        match self {
+            FuelUnits::Kg => kg,
+            FuelUnits::Lb => kg * LB_PER_KG,
+        }
+    }
+}
+
+/// The state of the fuel, in the page's units, as read from the sim.
+struct FuelState {
+    fuel: f64,
+    /// The fuel used per hour, if the engines are burning any.
+    flow: Option<f64>,
+    distance_nm: Option<f64>,
+    ground_speed_kt: Option<f64>,
+}
+
+impl Fuel {
+    pub(crate) fn load(path: &Path, settings_dir: &Path) -> Result<Self, HintsError> {
+        let (text, name) = read_calculator(path)?;
+        Self::parse(&text, name, settings_dir)
+    }
+
+    pub(crate) fn parse(text: &str, name: String, settings_dir: &Path) -> Result<Self, HintsError> {
+        let file: FuelFile = toml::from_str(text)?;
+        let dir = settings_dir.to_path_buf();
+        let saved = load_inputs(&dir, &name);
+        let reserve = saved.get(RESERVE_INPUT).copied().unwrap_or(file.reserve);
+        let final_reserve_minutes = saved
+            .get(FINAL_RESERVE_INPUT)
+            .copied()
+            .or(file.final_reserve_minutes)
+            .unwrap_or(DEFAULT_FINAL_RESERVE_MINUTES);
+        Ok(Fuel {
+            dir,
+            name,
+            file,
+            reserve: Cell::new(reserve),
+            final_reserve_minutes: Cell::new(final_reserve_minutes),
+        })
+    }
+
+    pub fn draw(&self, ui: &Ui, datarefs: &dyn DatarefReader) {
+        if let Some(title) = &self.file.title {
+            draw_title(ui, title);
+        }
+        let units = self.file.units.label();
+        let mut reserve = self.reserve.get();
+        let mut final_reserve_minutes = self.final_reserve_minutes.get();
+        ui.input_float(format!("Reserve fuel {units}"), &mut reserve)
+            .step(10.0)
+            .display_format("%.0f")
+            .build();
+        let mut edited = ui.is_item_deactivated_after_edit();
+        ui.input_float("Final reserve min", &mut final_reserve_minutes)
+            .step(5.0)
+            .display_format("%.0f")
+            .build();
+        edited |= ui.is_item_deactivated_after_edit();
+        if edited {
+            self.reserve.set(reserve.max(0.0));
+            self.final_reserve_minutes
+                .set(final_reserve_minutes.max(0.0));
+            let inputs = BTreeMap::from([
+                (RESERVE_INPUT.to_string(), self.reserve.get()),
+                (
+                    FINAL_RESERVE_INPUT.to_string(),
+                    self.final_reserve_minutes.get(),
+                ),
+            ]);
+            save_inputs(&self.dir, &self.name, inputs);
+        }
+
+        ui.separator();
+        match self.read(datarefs) {
+            Some(state) => self.draw_state(ui, &state),
+            None => ui.text_colored(WARNING_COLOUR, "Fuel is only shown in X-Plane"),
+        }
+    }
+
+    fn read(&self, datarefs: &dyn DatarefReader) -> Option<FuelState> {
+        let units = self.file.units;
+        let fuel = units.convert_kg(datarefs.read(FUEL_DATAREF, None)?);
+        let flow_kg_per_second: f64 = (0..MAX_ENGINES)
+            .map_while(|idx| datarefs.read(FUEL_FLOW_DATAREF, Some(idx)))
+            .sum();
+        let distance_dataref = self
+            .file
+            .distance_dataref
+            .as_deref()
+            .unwrap_or(DEFAULT_DISTANCE_DATAREF);
+        Some(FuelState {
+            fuel,
+            flow: Some(units.convert_kg(flow_kg_per_second * SECONDS_PER_HOUR))
+                .filter(|flow| *flow > 0.0),
+            distance_nm: datarefs
+                .read(distance_dataref, None)
+                .filter(|distance| *distance > 0.0),
+            ground_speed_kt: datarefs
+                .read(GROUND_SPEED_DATAREF, None)
+                .map(|speed| speed * KNOTS_PER_METRE_PER_SECOND)
+                .filter(|speed| *speed > 0.0),
+        })
+    }
+
+    fn draw_state(&self, ui: &Ui, state: &FuelState) {
+        let units = self.file.units.label();
+        ui.text(format!("Fuel remaining: {:.0} {units}", state.fuel));
+        let flow = match state.flow {
+            Some(flow) => flow,
+            None => {
+                ui.text("Fuel flow: none");
+                return;
+            }
+        };
+        ui.text(format!("Fuel flow: {flow:.0} {units}/h"));
+        let reserves = f64::from(self.reserve.get())
+            + flow * f64::from(self.final_reserve_minutes.get()) / 60.0;
+        ui.text(format!(
+            "Endurance: {} ({} to reserves of {reserves:.0} {units})",
+            format_hours(state.fuel / flow),
+            format_hours((state.fuel - reserves).max(0.0) / flow)
+        ));
+
+        ui.spacing();
+        ui.text_colored(HEADING_COLOUR, "Destination");
+        let ete_hours = match (state.distance_nm, state.ground_speed_kt) {
+            (Some(distance), Some(speed)) => {
+                ui.text(format!("Distance: {distance:.0} nm at {speed:.0} kt"));
+                distance / speed
+            }
+            _ => {
+                ui.text("ETE: no active waypoint");
+                return;
+            }
+        };
+        ui.text(format!("ETE: {}", format_hours(ete_hours)));
+        let at_destination = state.fuel - flow * ete_hours;
+        let text = format!("Fuel at destination: {at_destination:.0} {units}");
+        if at_destination < reserves {
+            ui.text_colored(WARNING_COLOUR, format!("{text} (below reserves)"));
+        } else if at_destination < reserves * (1.0 + CAUTION_MARGIN) {
+            ui.text_colored(CAUTION_COLOUR, format!("{text} (close to reserves)"));
+        } else {
+            ui.text(text);
+        }
+    }
+}
+
+/// Formats a number of hours as hours and minutes, such as `2:05`.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn format_hours(hours: f64) -> String {
+    let minutes = (hours * 60.0).round().clamp(0.0, f64::from(u32::MAX)) as u32;
+    format!("{}:{:02}", minutes / 60, minutes % 60)
+}
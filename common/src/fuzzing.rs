@@ -0,0 +1,91 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+//! Entry points for the fuzz targets in `common/fuzz`, which feed arbitrary text to the parsers of
+//! files that hint pack authors and users can edit. Each parses the text and then uses the result
+//! the way the app would, so that anything other than an error being returned is a bug.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use image::RgbaImage;
+
+use crate::adjustments::Adjustments;
+use crate::checklist::Checklist;
+use crate::config::Config;
+use crate::expression::Expression;
+use crate::fuel::Fuel;
+use crate::performance::Performance;
+use crate::template::{NoDatarefs, Template};
+use crate::weather::Weather;
+use crate::weight_balance::WeightAndBalance;
+
+/// Where the calculator hints look for the values last typed into them, of which there are none.
+fn settings_dir() -> PathBuf {
+    std::env::temp_dir().join("hints-fuzzing")
+}
+
+/// Parses `text` as `hints.toml`.
+pub fn config(text: &str) {
+    if let Ok(config) = toml::from_str::<Config>(text) {
+        let _ = config.check_version();
+        let _ = config.pack.is_empty();
+    }
+}
+
+/// Parses `text` as a `.checklist` file.
+pub fn checklist(text: &str) {
+    if let Ok(checklist) = Checklist::parse(text) {
+        let _ = checklist.progress();
+        checklist.reset();
+    }
+}
+
+/// Parses `text` as the adjustments and inputs saved in the settings directory, applying any
+/// adjustments to a small image.
+pub fn settings(text: &str) {
+    if let Ok(adjustments) = toml::from_str::<BTreeMap<String, Adjustments>>(text) {
+        let image = RgbaImage::from_raw(2, 2, (0..16).map(|value| value * 17).collect()).unwrap();
+        for adjustments in adjustments.values() {
+            let _ = adjustments.apply(&image);
+        }
+    }
+    let _ = toml::from_str::<BTreeMap<String, BTreeMap<String, f32>>>(text);
+}
+
+/// Parses `text` both as an expression and as templated text, evaluating them with no datarefs.
+pub fn expression(text: &str) {
+    if let Ok(expression) = Expression::parse(text) {
+        let _ = expression.evaluate(&NoDatarefs);
+    }
+    let _ = Template::parse(text).render(&NoDatarefs);
+}
+
+/// Parses `text` as a `.wb` file, calculating the weight and centre of gravity.
+pub fn weight_and_balance(text: &str) {
+    if let Ok(weight_and_balance) = WeightAndBalance::parse(text, String::new(), &settings_dir()) {
+        let _ = weight_and_balance.calculate();
+    }
+}
+
+/// Parses `text` as a `.told` file, looking up each output in its table.
+pub fn performance(text: &str) {
+    if let Ok(performance) = Performance::parse(text, String::new(), &settings_dir()) {
+        let _ = performance.output_values();
+    }
+}
+
+/// Parses `text` as a `.weather` file, reading a briefing with no datarefs.
+pub fn weather(text: &str) {
+    if let Ok(weather) = Weather::parse(text, String::new(), &settings_dir()) {
+        weather.refresh(&NoDatarefs);
+    }
+}
+
+/// Parses `text` as a `.fuel` file.
+pub fn fuel(text: &str) {
+    let _ = Fuel::parse(text, String::new(), &settings_dir());
+}
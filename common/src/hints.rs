@@ -4,10 +4,10 @@
  * All rights reserved.
  */
 
-use std::cell::Cell;
-use std::path::Path;
+use std::cell::{Cell, Ref, RefCell};
+use std::path::{Path, PathBuf};
 
-use image::{ImageError, RgbaImage};
+use image::RgbaImage;
 use imgui::TextureId;
 use imgui_support::deallocate_texture;
 #[cfg(feature = "standalone")]
@@ -16,30 +16,334 @@ use imgui_support_standalone::create_texture;
 use imgui_support_xplane::create_texture;
 use tracing::{error, info};
 
+use crate::adjustments::{Adjustments, DisplayMode};
+use crate::charts::Chart;
+use crate::checklist::Checklist;
+use crate::contents::CONTENTS_NAME;
+use crate::error::HintsError;
+use crate::fuel::Fuel;
+use crate::language::untranslated_name;
+use crate::overview::make_thumbnail;
+use crate::pdf::{page_dimensions, page_name, render_page, PDF_EXTENSION, PDF_SUPPORTED};
+use crate::performance::Performance;
+use crate::prefetch::Request;
+use crate::text::Text;
+use crate::weather::Weather;
+use crate::weight_balance::WeightAndBalance;
+
 #[cfg(not(any(feature = "standalone", feature = "xplane")))]
 compile_error!("At least one of the following features must be enabled: standalone, xplane");
 
+const IMAGE_EXTENSIONS: [&str; 3] = ["jpeg", "jpg", "png"];
+const TEXT_EXTENSIONS: [&str; 2] = ["md", "txt"];
+const CHECKLIST_EXTENSION: &str = "checklist";
+const WEIGHT_AND_BALANCE_EXTENSION: &str = "wb";
+const PERFORMANCE_EXTENSION: &str = "told";
+const WEATHER_EXTENSION: &str = "weather";
+const FUEL_EXTENSION: &str = "fuel";
+/// The names of hints made from charts start with this, to set them apart from the aircraft's.
+const CHARTS_CATEGORY: &str = "Charts";
+
 #[derive(Debug)]
 pub struct Hint {
-    image: RgbaImage,
-    texture_id: Cell<Option<TextureId>>,
+    name: String,
+    content: HintContent,
+}
+
+/// What a hint shows. The kind of content is chosen from the file extension, and each kind is
+/// drawn in its own way.
+#[derive(Debug)]
+pub enum HintContent {
+    Image(Image),
+    Text(Text),
+    Checklist(Checklist),
+    WeightAndBalance(WeightAndBalance),
+    Performance(Performance),
+    Weather(Weather),
+    Fuel(Fuel),
+    /// The contents page, which lists the other hints.
+    Contents,
 }
 
 impl Hint {
-    pub fn new<P: AsRef<Path>>(path: P) -> Result<Self, ImageError> {
-        info!(path = %path.as_ref().display(), "Loading hint");
-        let image = image::open(path)?.into_rgba8();
+    /// Loads the hint at `path`. Values the user types into calculator hints are saved in
+    /// `settings_dir`.
+    pub fn new<P: AsRef<Path>>(path: P, settings_dir: &Path) -> Result<Self, HintsError> {
+        let path = path.as_ref();
+        info!(path = %path.display(), "Loading hint");
+        let content = match extension(path).as_str() {
+            ext if TEXT_EXTENSIONS.contains(&ext) => HintContent::Text(Text::load(path)?),
+            CHECKLIST_EXTENSION => HintContent::Checklist(Checklist::load(path)?),
+            WEIGHT_AND_BALANCE_EXTENSION => {
+                HintContent::WeightAndBalance(WeightAndBalance::load(path, settings_dir)?)
+            }
+            PERFORMANCE_EXTENSION => {
+                HintContent::Performance(Performance::load(path, settings_dir)?)
+            }
+            WEATHER_EXTENSION => HintContent::Weather(Weather::load(path, settings_dir)?),
+            FUEL_EXTENSION => HintContent::Fuel(Fuel::load(path, settings_dir)?),
+            PDF_EXTENSION => {
+                return Err(HintsError::Pdf(format!(
+                    "{} is a PDF, which has a hint for each page",
+                    path.display()
+                )))
+            }
+            _ => HintContent::Image(Image::load(path)?),
+        };
         Ok(Hint {
-            image,
-            texture_id: Cell::new(None),
+            name: untranslated_name(path),
+            content,
+        })
+    }
+
+    /// Loads the hints in the file at `path`: an image hint for each page of a PDF, or the one hint
+    /// that any other file is.
+    pub(crate) fn load_all(path: &Path, settings_dir: &Path) -> Result<Vec<Self>, HintsError> {
+        if extension(path) != PDF_EXTENSION {
+            return Ok(vec![Hint::new(path, settings_dir)?]);
+        }
+        info!(path = %path.display(), "Loading PDF hint");
+        let name = untranslated_name(path);
+        Ok(page_dimensions(path)?
+            .into_iter()
+            .enumerate()
+            .map(|(idx, dimensions)| Hint {
+                name: page_name(&name, idx + 1),
+                content: HintContent::Image(Image::unloaded(
+                    ImageSource::PdfPage(path.to_path_buf(), idx),
+                    dimensions,
+                )),
+            })
+            .collect())
+    }
+
+    /// Creates an image hint showing `chart`, in the charts category.
+    pub(crate) fn from_chart(chart: &Chart) -> Result<Self, HintsError> {
+        let image = image::load_from_memory(&chart.image)?.into_rgba8();
+        Ok(Hint {
+            name: format!("{CHARTS_CATEGORY}/{} {}", chart.airport, chart.name),
+            content: HintContent::Image(Image::new(image)),
         })
     }
 
-    pub fn texture_id(&self) -> Option<TextureId> {
+    /// Creates the contents page, which lists the other hints.
+    pub(crate) fn contents() -> Self {
+        Hint {
+            name: CONTENTS_NAME.to_string(),
+            content: HintContent::Contents,
+        }
+    }
+
+    /// The filename of the hint, used as its key in per-hint settings.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn content(&self) -> &HintContent {
+        &self.content
+    }
+
+    /// Releases any texture held by the hint. It is recreated when the hint is next drawn.
+    pub fn deallocate_texture(&self) {
+        if let HintContent::Image(image) = &self.content {
+            image.deallocate_texture();
+        }
+    }
+
+    /// Releases the texture of the hint's thumbnail in the overview, keeping the thumbnail.
+    pub(crate) fn deallocate_thumbnail_texture(&self) {
+        if let HintContent::Image(image) = &self.content {
+            image.deallocate_thumbnail_texture();
+        }
+    }
+
+    /// Releases the decoded image of an image hint loaded from a file or PDF, keeping its texture.
+    /// It is decoded again when next needed.
+    pub(crate) fn unload(&self) {
+        if let HintContent::Image(image) = &self.content {
+            image.unload();
+        }
+    }
+
+    /// Where the image of an image hint whose image is not decoded comes from, so that it can be
+    /// decoded in the background.
+    pub(crate) fn unloaded_source(&self) -> Option<&ImageSource> {
+        match &self.content {
+            HintContent::Image(image) if image.image.borrow().is_none() && !image.failed.get() => {
+                image.source.as_ref()
+            }
+            _ => None,
+        }
+    }
+
+    /// Where the image of an image hint without a thumbnail comes from, so that one can be made in
+    /// the background.
+    pub(crate) fn unthumbnailed_source(&self) -> Option<&ImageSource> {
+        match &self.content {
+            HintContent::Image(image)
+                if image.thumbnail.borrow().is_none() && !image.failed.get() =>
+            {
+                image.source.as_ref()
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether `request` was for the image of this hint.
+    pub(crate) fn is_requested_by(&self, request: &Request) -> bool {
+        match &self.content {
+            HintContent::Image(image) => image.source.as_ref() == Some(request.source()),
+            _ => false,
+        }
+    }
+
+    /// Keeps the image or thumbnail decoded in the background for `request` from the hint's file
+    /// or PDF page.
+    pub(crate) fn prefetched(&self, request: &Request, decoded: Result<RgbaImage, HintsError>) {
+        if let HintContent::Image(image) = &self.content {
+            match decoded {
+                Ok(decoded) => {
+                    let cache = match request {
+                        Request::Image(_) => &image.image,
+                        Request::Thumbnail(_) => &image.thumbnail,
+                    };
+                    cache.borrow_mut().get_or_insert(decoded);
+                }
+                Err(e) => {
+                    error!(code = e.code(), "Unable to decode {}: {e}", self.name);
+                    image.failed.set(true);
+                }
+            }
+        }
+    }
+}
+
+/// Whether `path` has the extension of one of the kinds of hint.
+pub(crate) fn is_hint_file(path: &Path) -> bool {
+    let extension = extension(path);
+    let extension = extension.as_str();
+    IMAGE_EXTENSIONS.contains(&extension)
+        || TEXT_EXTENSIONS.contains(&extension)
+        || extension == CHECKLIST_EXTENSION
+        || extension == WEIGHT_AND_BALANCE_EXTENSION
+        || extension == PERFORMANCE_EXTENSION
+        || extension == WEATHER_EXTENSION
+        || extension == FUEL_EXTENSION
+        || (PDF_SUPPORTED && extension == PDF_EXTENSION)
+}
+
+/// Returns an error unless `path`, relative to the hints directory `dir`, is inside it once `..`
+/// and symbolic links are resolved, so that a downloaded hint pack cannot read files elsewhere.
+pub(crate) fn check_in_hints_dir(dir: &Path, path: &Path) -> Result<(), HintsError> {
+    if dir
+        .join(path)
+        .canonicalize()?
+        .starts_with(dir.canonicalize()?)
+    {
+        Ok(())
+    } else {
+        Err(HintsError::OutsideHintsDirectory(PathBuf::from(path)))
+    }
+}
+
+/// Whether `path` has the extension of an image hint.
+pub(crate) fn is_image_file(path: &Path) -> bool {
+    IMAGE_EXTENSIONS.contains(&extension(path).as_str())
+}
+
+fn extension(path: &Path) -> String {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default()
+}
+
+/// Where an image hint's image is decoded from, so that it can be decoded again once unloaded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ImageSource {
+    File(PathBuf),
+    /// A page of the PDF at the path, counting from 0.
+    PdfPage(PathBuf, usize),
+}
+
+impl ImageSource {
+    pub(crate) fn decode(&self) -> Result<RgbaImage, HintsError> {
+        match self {
+            ImageSource::File(path) => Ok(image::open(path)?.into_rgba8()),
+            ImageSource::PdfPage(path, page) => render_page(path, *page),
+        }
+    }
+}
+
+/// An image hint. Images loaded from files and PDFs are only decoded when they are needed, so that
+/// large packs don't hold every image in memory, and can be unloaded again.
+#[derive(Debug)]
+pub struct Image {
+    /// Where the image is decoded from, or `None` for images made in memory, such as charts,
+    /// which stay decoded.
+    source: Option<ImageSource>,
+    dimensions: (u32, u32),
+    image: RefCell<Option<RgbaImage>>,
+    /// The image shrunk for the overview, which is kept once made, as it is small.
+    thumbnail: RefCell<Option<RgbaImage>>,
+    /// Whether decoding the image failed, so that it isn't tried again every frame.
+    failed: Cell<bool>,
+    texture_id: Cell<Option<TextureId>>,
+    transform: Cell<(Adjustments, DisplayMode)>,
+    /// The texture of the thumbnail, and the adjustments and display mode it was made with.
+    thumbnail_texture: Cell<Option<(TextureId, (Adjustments, DisplayMode))>>,
+}
+
+impl Image {
+    /// Reads the size of the image at `path`, leaving it to be decoded when it is first shown.
+    fn load(path: &Path) -> Result<Self, HintsError> {
+        let dimensions = image::image_dimensions(path)?;
+        Ok(Image::unloaded(
+            ImageSource::File(path.to_path_buf()),
+            dimensions,
+        ))
+    }
+
+    /// An image of `dimensions` from `source`, to be decoded when it is first shown.
+    fn unloaded(source: ImageSource, dimensions: (u32, u32)) -> Self {
+        Image {
+            source: Some(source),
+            dimensions,
+            image: RefCell::new(None),
+            thumbnail: RefCell::new(None),
+            failed: Cell::new(false),
+            texture_id: Cell::new(None),
+            transform: Cell::new((Adjustments::default(), DisplayMode::default())),
+            thumbnail_texture: Cell::new(None),
+        }
+    }
+
+    fn new(image: RgbaImage) -> Self {
+        Image {
+            source: None,
+            dimensions: image.dimensions(),
+            image: RefCell::new(Some(image)),
+            thumbnail: RefCell::new(None),
+            failed: Cell::new(false),
+            texture_id: Cell::new(None),
+            transform: Cell::new((Adjustments::default(), DisplayMode::default())),
+            thumbnail_texture: Cell::new(None),
+        }
+    }
+
+    /// Returns the texture for the image with `adjustments` and `mode` applied, recreating it if
+    /// either has changed since it was created, or `None` until the image has been decoded in the
+    /// background.
+    pub fn texture_id(&self, adjustments: Adjustments, mode: DisplayMode) -> Option<TextureId> {
+        if self.transform.get() != (adjustments, mode) {
+            self.deallocate_texture();
+            self.transform.set((adjustments, mode));
+        }
         if let Some(texture_id) = self.texture_id.get() {
             Some(texture_id)
         } else {
-            let texture_id = match create_texture(&self.image) {
+            let image = self.image.borrow();
+            let image = mode.apply(adjustments.apply(image.as_ref()?));
+            let texture_id = match create_texture(&image) {
                 Ok(texture_id) => Some(texture_id),
                 Err(e) => {
                     error!(error = %e, "Unable to create texture");
@@ -51,8 +355,65 @@ impl Hint {
         }
     }
 
+    /// Returns the texture for the image's thumbnail with `adjustments` and `mode` applied, or
+    /// `None` until the thumbnail has been made in the background. Thumbnails of images made in
+    /// memory are made here, as they are already decoded.
+    pub(crate) fn thumbnail_texture_id(
+        &self,
+        adjustments: Adjustments,
+        mode: DisplayMode,
+    ) -> Option<TextureId> {
+        match self.thumbnail_texture.get() {
+            Some((texture_id, transform)) if transform == (adjustments, mode) => {
+                return Some(texture_id)
+            }
+            Some(_) => self.deallocate_thumbnail_texture(),
+            None => {}
+        }
+        if self.source.is_none() && self.thumbnail.borrow().is_none() {
+            let thumbnail = self.image.borrow().as_ref().map(make_thumbnail);
+            self.thumbnail.replace(thumbnail);
+        }
+        let thumbnail = self.thumbnail.borrow();
+        let image = mode.apply(adjustments.apply(thumbnail.as_ref()?));
+        match create_texture(&image) {
+            Ok(texture_id) => {
+                self.thumbnail_texture
+                    .set(Some((texture_id, (adjustments, mode))));
+                Some(texture_id)
+            }
+            Err(e) => {
+                error!(error = %e, "Unable to create thumbnail texture");
+                None
+            }
+        }
+    }
+
     pub fn dimensions(&self) -> (u32, u32) {
-        self.image.dimensions()
+        self.dimensions
+    }
+
+    /// Decodes the image if it isn't already, returning it without any adjustments.
+    pub(crate) fn decode(&self) -> Result<Ref<RgbaImage>, HintsError> {
+        if self.image.borrow().is_none() {
+            if let Some(source) = &self.source {
+                self.image.replace(Some(source.decode()?));
+            }
+        }
+        Ok(Ref::map(self.image.borrow(), |image| {
+            image.as_ref().expect("image is decoded")
+        }))
+    }
+
+    /// Whether the image couldn't be decoded, so will never be shown.
+    pub(crate) fn has_failed(&self) -> bool {
+        self.failed.get()
+    }
+
+    fn unload(&self) {
+        if self.source.is_some() {
+            self.image.take();
+        }
     }
 
     pub fn deallocate_texture(&self) {
@@ -60,10 +421,73 @@ impl Hint {
             deallocate_texture(texture_id);
         }
     }
+
+    pub(crate) fn deallocate_thumbnail_texture(&self) {
+        if let Some((texture_id, _)) = self.thumbnail_texture.take() {
+            deallocate_texture(texture_id);
+        }
+    }
 }
 
-impl Drop for Hint {
+impl Drop for Image {
     fn drop(&mut self) {
         self.deallocate_texture();
+        self.deallocate_thumbnail_texture();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::error::HintsError;
+    use crate::test_dir::TestDir;
+
+    use super::check_in_hints_dir;
+
+    /// Creates a `hints` directory containing `hint.png`, beside a file that is not a hint.
+    fn sandbox() -> TestDir {
+        let root = TestDir::new();
+        root.touch(&["hints/hint.png", "secret.txt"]);
+        root
+    }
+
+    fn is_outside(result: Result<(), HintsError>) -> bool {
+        matches!(result, Err(HintsError::OutsideHintsDirectory(_)))
+    }
+
+    #[test]
+    fn allows_files_in_hints_dir() {
+        let root = sandbox();
+        let dir = root.join("hints");
+        assert!(check_in_hints_dir(&dir, Path::new("hint.png")).is_ok());
+        assert!(check_in_hints_dir(&dir, &dir.join("hint.png")).is_ok());
+        assert!(check_in_hints_dir(&dir, Path::new("../hints/hint.png")).is_ok());
+    }
+
+    #[test]
+    fn rejects_parent_dir_escapes() {
+        let root = sandbox();
+        let dir = root.join("hints");
+        assert!(is_outside(check_in_hints_dir(
+            &dir,
+            Path::new("../secret.txt")
+        )));
+        assert!(is_outside(check_in_hints_dir(
+            &dir,
+            &dir.join("../secret.txt")
+        )));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rejects_symlinks_out_of_hints_dir() {
+        let root = sandbox();
+        let dir = root.join("hints");
+        std::os::unix::fs::symlink(dir.join("../secret.txt"), dir.join("secret.png")).unwrap();
+        assert!(is_outside(check_in_hints_dir(
+            &dir,
+            Path::new("secret.png")
+        )));
     }
 }
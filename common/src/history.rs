@@ -0,0 +1,53 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::sync::{Mutex, PoisonError};
+use std::time::Instant;
+
+use tracing::info;
+
+const HISTORY_CAPACITY: usize = 200;
+
+/// The most recent events and commands received, oldest first, kept to help diagnose reports such
+/// as the hints changing page on their own.
+static HISTORY: Mutex<VecDeque<Record>> = Mutex::new(VecDeque::new());
+
+struct Record {
+    time: Instant,
+    source: &'static str,
+    description: String,
+}
+
+/// Records that `description` was received from `source`, such as "input" or "command",
+/// forgetting the oldest record if the history is full.
+pub fn record_event(source: &'static str, description: impl Display) {
+    let mut history = HISTORY.lock().unwrap_or_else(PoisonError::into_inner);
+    if history.len() == HISTORY_CAPACITY {
+        history.pop_front();
+    }
+    history.push_back(Record {
+        time: Instant::now(),
+        source,
+        description: description.to_string(),
+    });
+}
+
+/// Writes the recorded events to the log, each with how long ago it was received.
+pub fn log_event_history() {
+    let history = HISTORY.lock().unwrap_or_else(PoisonError::into_inner);
+    let now = Instant::now();
+    info!("Last {} events received, oldest first:", history.len());
+    for record in history.iter() {
+        info!(
+            "{:>9.3}s ago  {:<8} {}",
+            (now - record.time).as_secs_f64(),
+            record.source,
+            record.description
+        );
+    }
+}
@@ -0,0 +1,225 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+use std::path::{Path, PathBuf};
+
+use imgui::Ui;
+use tracing::{error, info, warn};
+
+use crate::error::HintsError;
+use crate::hints::is_image_file;
+use crate::pdf::{PDF_EXTENSION, PDF_SUPPORTED};
+use crate::view::draw_title;
+
+/// The directories in an aircraft that developers usually put their documentation in, compared
+/// ignoring case.
+const DOCUMENT_DIRNAMES: [&str; 5] = ["documentation", "documents", "docs", "manual", "manuals"];
+/// How many directories deep documents are looked for below a documentation directory.
+const MAX_DEPTH: usize = 3;
+
+/// Offers to build a set of hints from the images in an aircraft's documentation directories, for
+/// aircraft that come with documentation but no hints. The user chooses which images to use, and
+/// they are copied into the hints directory, numbered in the order they were listed.
+///
+/// PDFs can be chosen in builds that can show them. Otherwise they are listed so that the user
+/// knows they were found, but can't be chosen.
+pub(crate) struct ImportWizard {
+    hints_dir: PathBuf,
+    aircraft_dir: PathBuf,
+    documents: Vec<Document>,
+    imported: usize,
+    message: Option<String>,
+}
+
+struct Document {
+    path: PathBuf,
+    selected: bool,
+}
+
+impl Document {
+    fn is_pdf(&self) -> bool {
+        self.path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case(PDF_EXTENSION))
+    }
+}
+
+impl ImportWizard {
+    pub(crate) fn new(aircraft_dir: &Path, hints_dir: &Path) -> Self {
+        let documents: Vec<_> = find_documents(aircraft_dir)
+            .into_iter()
+            .map(|path| Document {
+                path,
+                selected: false,
+            })
+            .collect();
+        info!(
+            count = documents.len(),
+            "Found documents in {aircraft_dir:?}"
+        );
+        ImportWizard {
+            hints_dir: hints_dir.to_path_buf(),
+            aircraft_dir: aircraft_dir.to_path_buf(),
+            documents,
+            imported: 0,
+            message: None,
+        }
+    }
+
+    /// How many hints have been created.
+    pub(crate) fn imported(&self) -> usize {
+        self.imported
+    }
+
+    /// Draws the wizard, returning `true` once the user has closed it.
+    pub(crate) fn draw(&mut self, ui: &Ui) -> bool {
+        draw_title(ui, "Create hints from documentation");
+        if self.documents.is_empty() {
+            ui.text_wrapped(format!(
+                "No images or PDFs were found in the {} directories of this aircraft.",
+                DOCUMENT_DIRNAMES.join(", ")
+            ));
+            return ui.button("Close");
+        }
+        ui.text_wrapped("Choose the images to use as hints. They are shown in the order listed.");
+        ui.spacing();
+        for (idx, document) in self.documents.iter_mut().enumerate() {
+            let name = document
+                .path
+                .strip_prefix(&self.aircraft_dir)
+                .unwrap_or(&document.path)
+                .display()
+                .to_string();
+            if document.is_pdf() && !PDF_SUPPORTED {
+                ui.text_disabled(format!(
+                    "{name} (PDF: save its pages as images to use them)"
+                ));
+            } else {
+                ui.checkbox(format!("{name}##document{idx}"), &mut document.selected);
+            }
+        }
+        ui.spacing();
+        if let Some(message) = &self.message {
+            ui.text_wrapped(message);
+        }
+        if ui.button("Create hints") {
+            match self.import() {
+                Ok(0) => self.message = Some(String::from("Choose at least one image.")),
+                Ok(count) => {
+                    info!(count, "Created hints from documentation");
+                    self.imported = count;
+                    return true;
+                }
+                Err(e) => {
+                    error!(code = e.code(), "Unable to create hints: {e}");
+                    self.message = Some(format!("Error {}: {e}", e.code()));
+                }
+            }
+        }
+        ui.same_line();
+        ui.button("Cancel")
+    }
+
+    /// Copies the selected images into the hints directory, returning how many were copied.
+    fn import(&self) -> Result<usize, HintsError> {
+        std::fs::create_dir_all(&self.hints_dir)?;
+        let mut count = 0;
+        for document in self.documents.iter().filter(|document| document.selected) {
+            let name = match document.path.file_name() {
+                Some(name) => name.to_string_lossy(),
+                None => continue,
+            };
+            let target = self.hints_dir.join(format!("{:03}-{name}", count + 1));
+            if target.exists() {
+                warn!("Not replacing existing hint {target:?}");
+                continue;
+            }
+            std::fs::copy(&document.path, &target)?;
+            count += 1;
+        }
+        Ok(count)
+    }
+}
+
+/// Finds the images and PDFs in the documentation directories of the aircraft in `aircraft_dir`.
+fn find_documents(aircraft_dir: &Path) -> Vec<PathBuf> {
+    let mut documents = vec![];
+    for entry in read_dir(aircraft_dir) {
+        let is_documentation = entry.file_name().is_some_and(|name| {
+            DOCUMENT_DIRNAMES
+                .iter()
+                .any(|dirname| name.eq_ignore_ascii_case(dirname))
+        });
+        if is_documentation && entry.is_dir() {
+            find_documents_in(&entry, 0, &mut documents);
+        }
+    }
+    documents.sort();
+    documents
+}
+
+fn find_documents_in(dir: &Path, depth: usize, documents: &mut Vec<PathBuf>) {
+    for entry in read_dir(dir) {
+        if entry.is_dir() {
+            if depth < MAX_DEPTH {
+                find_documents_in(&entry, depth + 1, documents);
+            }
+        } else if is_image_file(&entry)
+            || entry
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case(PDF_EXTENSION))
+        {
+            documents.push(entry);
+        }
+    }
+}
+
+fn read_dir(dir: &Path) -> Vec<PathBuf> {
+    match std::fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .collect(),
+        Err(e) => {
+            warn!("Unable to read {dir:?}: {e}");
+            vec![]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::test_dir::TestDir;
+
+    use super::find_documents;
+
+    #[test]
+    fn finds_images_and_pdfs_in_documentation_dirs() {
+        let aircraft_dir = TestDir::new();
+        aircraft_dir.touch(&[
+            "Documentation/checklist.PNG",
+            "Documentation/Normal/start.jpg",
+            "Documentation/manual.pdf",
+            "Documentation/readme.txt",
+            "manuals/a/b/c/d/too-deep.png",
+            "objects/livery.png",
+        ]);
+        let found: Vec<PathBuf> = find_documents(&aircraft_dir)
+            .into_iter()
+            .map(|path| path.strip_prefix(&aircraft_dir).unwrap().to_path_buf())
+            .collect();
+        assert_eq!(
+            found,
+            [
+                "Documentation/Normal/start.jpg",
+                "Documentation/checklist.PNG",
+                "Documentation/manual.pdf",
+            ]
+            .map(PathBuf::from)
+        );
+    }
+}
@@ -0,0 +1,137 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+//! The optional `index.toml` in the hints directory, which lists the hints to show in order and
+//! gives each a title, such as:
+//!
+//! ```toml
+//! [[hints]]
+//! file = "preflight.png"
+//! title = "Preflight checks"
+//! ```
+//!
+//! Without it, every hint file is shown in the order of the filenames.
+
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use tracing::{error, info, warn};
+
+use crate::error::read_toml_file;
+use crate::language::untranslated_name;
+
+pub(crate) const INDEX_FILENAME: &str = "index.toml";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub(crate) struct Index {
+    hints: Vec<IndexEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IndexEntry {
+    /// The filename of the hint, without any language, as it is in `hints.toml`.
+    file: String,
+    /// The title shown above the hint, rather than its filename.
+    title: Option<String>,
+}
+
+impl Index {
+    /// Keeps only the hint `files` listed in the index, in its order. Translated files take the
+    /// place of the file they translate.
+    pub(crate) fn apply(&self, files: Vec<PathBuf>) -> Vec<PathBuf> {
+        let mut files: Vec<Option<PathBuf>> = files.into_iter().map(Some).collect();
+        let mut listed = vec![];
+        for entry in &self.hints {
+            let found = files.iter_mut().find(|file| {
+                file.as_deref()
+                    .is_some_and(|file| untranslated_name(file) == entry.file)
+            });
+            match found.and_then(Option::take) {
+                Some(file) => listed.push(file),
+                None => warn!(
+                    "Ignoring {:?} in {INDEX_FILENAME}, which is not a hint file",
+                    entry.file
+                ),
+            }
+        }
+        let unlisted = files.iter().flatten().count();
+        if unlisted > 0 {
+            info!("Not showing {unlisted} hint files that are not in {INDEX_FILENAME}");
+        }
+        listed
+    }
+
+    /// The title of the hint called `name`, if the index gives it one.
+    pub(crate) fn title(&self, name: &str) -> Option<&str> {
+        self.hints
+            .iter()
+            .find(|entry| entry.file == name)
+            .and_then(|entry| entry.title.as_deref())
+    }
+}
+
+/// Loads `index.toml` from `dir`, if there is one.
+pub(crate) fn load_index(dir: &Path) -> Option<Index> {
+    let filename = dir.join(INDEX_FILENAME);
+    if !filename.is_file() {
+        return None;
+    }
+    match read_toml_file::<Index>(&filename) {
+        Ok(index) => {
+            info!(
+                count = index.hints.len(),
+                "Ordering hints by index {filename:?}"
+            );
+            Some(index)
+        }
+        Err(e) => {
+            error!(code = e.code(), "Unable to load hints index: {e}");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::Index;
+
+    fn index() -> Index {
+        toml::from_str(
+            r#"
+            [[hints]]
+            file = "c.png"
+            title = "Engine start"
+
+            [[hints]]
+            file = "missing.png"
+
+            [[hints]]
+            file = "a.png"
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn shows_listed_hints_in_order() {
+        let files = ["a.png", "b.png", "c.de.png"].map(PathBuf::from).to_vec();
+        assert_eq!(
+            index().apply(files),
+            ["c.de.png", "a.png"].map(PathBuf::from).to_vec()
+        );
+    }
+
+    #[test]
+    fn titles_hints() {
+        let index = index();
+        assert_eq!(index.title("c.png"), Some("Engine start"));
+        assert_eq!(index.title("a.png"), None);
+        assert_eq!(index.title("b.png"), None);
+    }
+}
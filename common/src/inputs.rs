@@ -0,0 +1,69 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use tracing::{error, info};
+
+use crate::documented::TomlDocs;
+use crate::error::{read_toml_file, write_toml_file, HintsError};
+
+const INPUTS_FILENAME: &str = "inputs.toml";
+const INPUTS_DOCS: TomlDocs = TomlDocs {
+    header:
+        "The values last typed into each calculator hint, keyed by the hint's filename and then by
+the input's label. Values are numbers in the units shown on the hint.",
+    fields: &[],
+};
+
+type Inputs = BTreeMap<String, BTreeMap<String, f32>>;
+
+/// Loads the values last typed into the calculator hint `hint_name` from `inputs.toml` in `dir`.
+pub(crate) fn load_inputs(dir: &Path, hint_name: &str) -> BTreeMap<String, f32> {
+    read_inputs(dir)
+        .and_then(|mut inputs| inputs.remove(hint_name))
+        .unwrap_or_default()
+}
+
+/// Reads the calculator hint at `path`, returning its text and the name that the values typed
+/// into it are saved under by [`load_inputs`] and [`save_inputs`].
+pub(crate) fn read_calculator(path: &Path) -> Result<(String, String), HintsError> {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    Ok((std::fs::read_to_string(path)?, name))
+}
+
+/// Saves the values typed into the calculator hint `hint_name`, keeping those of other hints.
+pub(crate) fn save_inputs(dir: &Path, hint_name: &str, values: BTreeMap<String, f32>) {
+    let filename = dir.join(INPUTS_FILENAME);
+    let mut inputs = read_inputs(dir).unwrap_or_default();
+    inputs.insert(hint_name.to_string(), values);
+    match write_toml_file(&filename, &inputs, &INPUTS_DOCS) {
+        Ok(()) => info!("Saved inputs for {hint_name} to {filename:?}"),
+        Err(e) => error!(
+            code = e.code(),
+            "Unable to save inputs for {hint_name}: {e}"
+        ),
+    }
+}
+
+fn read_inputs(dir: &Path) -> Option<Inputs> {
+    let filename = dir.join(INPUTS_FILENAME);
+    if !filename.is_file() {
+        return None;
+    }
+    read_toml_file(&filename)
+        .map_err(|e: HintsError| {
+            error!(
+                code = e.code(),
+                "Unable to load inputs from {filename:?}: {e}"
+            );
+        })
+        .ok()
+}
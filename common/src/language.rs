@@ -0,0 +1,154 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+//! Hints in several languages in one pack. A hint is translated either by a file of the same name
+//! in a folder named for the language, such as `de/before-start.png`, or by a file with the
+//! language before its extension, such as `before-start.de.png`. Languages are two-letter codes
+//! such as `de`. Hints without a translation are shown in the pack's default language.
+
+use std::path::{Path, PathBuf};
+
+use tracing::warn;
+
+use crate::hints::{check_in_hints_dir, is_hint_file};
+
+/// Chooses the files to show in `language` from the hint files `files` in `dir`, in their order,
+/// with any only in the language's folder after them. Translations into other languages are left
+/// out, and without a language only the untranslated files are shown.
+pub(crate) fn select_language(
+    dir: &Path,
+    files: Vec<PathBuf>,
+    language: Option<&str>,
+) -> Vec<PathBuf> {
+    let (translations, defaults): (Vec<_>, Vec<_>) = files
+        .into_iter()
+        .partition(|file| file_language(file).is_some());
+    let language = match language {
+        Some(language) => language,
+        None => return defaults,
+    };
+    let mut folder_files = list_folder(dir, language);
+    let mut selected: Vec<PathBuf> = defaults
+        .into_iter()
+        .map(|file| {
+            let name = file.file_name().unwrap_or_default();
+            if let Some(idx) = folder_files
+                .iter()
+                .position(|folder_file| folder_file.file_name() == Some(name))
+            {
+                return folder_files.remove(idx);
+            }
+            translations
+                .iter()
+                .find(|translation| {
+                    file_language(translation) == Some(language)
+                        && untranslated_name(translation) == name.to_string_lossy()
+                })
+                .cloned()
+                .unwrap_or(file)
+        })
+        .collect();
+    selected.append(&mut folder_files);
+    selected
+}
+
+/// The language of a file such as `before-start.de.png`, or `None` if it isn't a translation.
+fn file_language(file: &Path) -> Option<&str> {
+    let (_, language) = file.file_stem()?.to_str()?.rsplit_once('.')?;
+    let is_code = language.len() == 2 && language.bytes().all(|b| b.is_ascii_lowercase());
+    is_code.then_some(language)
+}
+
+/// The name of the hint that `file` is, without the language of a translation such as
+/// `before-start.de.png`, so that settings for a hint apply to its translations too.
+pub(crate) fn untranslated_name(file: &Path) -> String {
+    let translated = file_language(file).and_then(|_| {
+        let (stem, _) = file.file_stem()?.to_str()?.rsplit_once('.')?;
+        Some(format!("{stem}.{}", file.extension()?.to_string_lossy()))
+    });
+    translated.unwrap_or_else(|| {
+        file.file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    })
+}
+
+/// The hint files in the folder for `language` in the hints directory `dir`, sorted, or none if
+/// there is no folder for it.
+fn list_folder(dir: &Path, language: &str) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir.join(language))
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| is_hint_file(path))
+                .filter(|path| match check_in_hints_dir(dir, path) {
+                    Ok(()) => true,
+                    Err(e) => {
+                        warn!(code = e.code(), "Not loading hint: {e}");
+                        false
+                    }
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort();
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+
+    fn hints_dir() -> TestDir {
+        let dir = TestDir::new();
+        dir.touch(&[
+            "a.png", "b.png", "b.fr.png", "c.png", "c.de.png", "de/a.png", "de/z.png",
+        ]);
+        dir
+    }
+
+    fn top_level(dir: &Path) -> Vec<PathBuf> {
+        ["a.png", "b.png", "b.fr.png", "c.png", "c.de.png"]
+            .iter()
+            .map(|name| dir.join(name))
+            .collect()
+    }
+
+    #[test]
+    fn prefers_translations() {
+        let dir = hints_dir();
+        assert_eq!(
+            select_language(&dir, top_level(&dir), Some("de")),
+            [
+                dir.join("de/a.png"),
+                dir.join("b.png"),
+                dir.join("c.de.png"),
+                dir.join("de/z.png"),
+            ]
+        );
+        assert_eq!(
+            select_language(&dir, top_level(&dir), Some("fr")),
+            [dir.join("a.png"), dir.join("b.fr.png"), dir.join("c.png")]
+        );
+    }
+
+    #[test]
+    fn falls_back_to_the_default_language() {
+        let dir = hints_dir();
+        let defaults = [dir.join("a.png"), dir.join("b.png"), dir.join("c.png")];
+        assert_eq!(select_language(&dir, top_level(&dir), None), defaults);
+        assert_eq!(select_language(&dir, top_level(&dir), Some("ja")), defaults);
+    }
+
+    #[test]
+    fn translations_have_the_name_of_the_hint() {
+        assert_eq!(untranslated_name(Path::new("/hints/c.de.png")), "c.png");
+        assert_eq!(untranslated_name(Path::new("/hints/de/c.png")), "c.png");
+        assert_eq!(untranslated_name(Path::new("/hints/v1.2.png")), "v1.2.png");
+        assert_eq!(untranslated_name(Path::new("/hints/c.png")), "c.png");
+    }
+}
@@ -8,14 +8,75 @@
 #![warn(clippy::pedantic)]
 #![allow(clippy::missing_panics_doc)]
 
-use thiserror::Error;
-
-pub use crate::app::{Hints, HintsEvent};
+pub use crate::add_ons::AddOns;
+pub use crate::app::{Hints, HintsEvent, HintsOptions};
+pub use crate::charts::{
+    AccessToken, Chart, ChartProvider, DeviceAuthorization, DeviceFlow, DeviceFlowClient, TokenPoll,
+};
+pub use crate::checklist::ChecklistProgress;
+pub use crate::concurrent::{run_on_main_thread, MainThreadTasks};
+pub use crate::config::parse_version;
+pub use crate::documented::{to_documented_toml, TomlDocs};
+pub use crate::error::{read_toml_file, write_toml_file, HintsError};
+pub use crate::expression::{Expression, ExpressionError};
+pub use crate::history::{log_event_history, record_event};
+pub use crate::library::is_writable;
+pub use crate::navdata::{Airport, NavData, Navaid};
+pub use crate::panic::{catch_panic, install_panic_hook};
+pub use crate::speech::Speaker;
+pub use crate::template::DatarefReader;
 
+mod about;
+mod accessibility;
+mod add_ons;
+mod adjustments;
 mod app;
+mod charts;
+mod checklist;
+mod clipboard;
 mod concurrent;
+mod config;
+mod contents;
+mod conversions;
+mod diagnose;
+mod documented;
+mod error;
+mod expression;
+mod flow;
+mod fuel;
 mod hints;
+mod history;
+mod import;
+mod index;
+mod inputs;
+mod language;
+mod library;
+mod links;
+mod migrate;
+mod navdata;
+mod overview;
+mod panel_state;
+mod panic;
+mod pdf;
+mod performance;
+mod prefetch;
+mod schedule;
+mod snapshot;
+mod speech;
+mod template;
+#[cfg(test)]
+mod test_dir;
+mod text;
+mod usage;
+mod variants;
+mod view;
+mod watch;
+mod watermark;
+mod weather;
+mod weight_balance;
 
+#[cfg(feature = "fuzzing")]
+pub mod fuzzing;
 pub mod logging;
 
 pub const TITLE: &str = "Hints";
@@ -26,19 +87,6 @@ pub const FROM_EDGE_MIN: u32 = 50;
 
 pub const LOGGING_ENV_VAR: &str = "HINTS_LOG";
 
-#[derive(Error, Debug)]
-#[error("Unable to load hints: {msg}")]
-pub struct ConfigError {
-    msg: String,
-}
-
-impl ConfigError {
-    #[must_use]
-    pub fn new(msg: String) -> Self {
-        ConfigError { msg }
-    }
-}
-
 #[must_use]
 pub fn get_offset_from_edge(size: u32, proportion: u32, min: u32) -> u32 {
     (size / proportion).min(min)
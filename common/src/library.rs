@@ -0,0 +1,99 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+//! A library of hints kept outside the aircraft, for aircraft whose directory can't be written to,
+//! such as those installed by Steam or owned by an administrator. Hints the app creates, and the
+//! user's settings, are written to the library instead, and its hints are shown along with those
+//! in the aircraft's hints directory.
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use tracing::debug;
+
+use crate::language::untranslated_name;
+
+/// The file created to check whether a directory can be written to, which is removed straight
+/// away.
+const WRITE_CHECK_FILENAME: &str = ".hints-write-check";
+
+/// Whether files can be created in `dir`. This is checked by creating one, as read-only flags and
+/// permissions don't show whether, for example, Windows will let the user write to the directory.
+#[must_use]
+pub fn is_writable(dir: &Path) -> bool {
+    let filename = dir.join(WRITE_CHECK_FILENAME);
+    match File::create(&filename) {
+        Ok(_) => {
+            let _ = std::fs::remove_file(&filename);
+            true
+        }
+        Err(e) => {
+            debug!("Unable to write to {dir:?}: {e}");
+            false
+        }
+    }
+}
+
+/// Adds the hint `library_files` to the `files` in the hints directory. A hint in the library
+/// takes the place of the hint of the same name in the hints directory, and the others follow the
+/// hints directory's hints.
+pub(crate) fn overlay(files: Vec<PathBuf>, library_files: Vec<PathBuf>) -> Vec<PathBuf> {
+    let mut library_files: Vec<Option<PathBuf>> = library_files.into_iter().map(Some).collect();
+    let mut overlaid: Vec<PathBuf> = files
+        .into_iter()
+        .map(|file| {
+            let name = untranslated_name(&file);
+            library_files
+                .iter_mut()
+                .find(|library_file| {
+                    library_file
+                        .as_deref()
+                        .is_some_and(|library_file| untranslated_name(library_file) == name)
+                })
+                .and_then(Option::take)
+                .unwrap_or(file)
+        })
+        .collect();
+    overlaid.extend(library_files.into_iter().flatten());
+    overlaid
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::test_dir::TestDir;
+
+    use super::{is_writable, overlay};
+
+    #[test]
+    fn library_hints_replace_and_follow_the_aircraft_hints() {
+        let files = ["/aircraft/hints/a.png", "/aircraft/hints/b.png"]
+            .map(PathBuf::from)
+            .to_vec();
+        let library_files = ["/library/001-notes.png", "/library/b.de.png"]
+            .map(PathBuf::from)
+            .to_vec();
+        assert_eq!(
+            overlay(files, library_files),
+            [
+                "/aircraft/hints/a.png",
+                "/library/b.de.png",
+                "/library/001-notes.png"
+            ]
+            .map(PathBuf::from)
+            .to_vec()
+        );
+    }
+
+    #[test]
+    fn checks_directories_can_be_written_to() {
+        let dir = TestDir::new();
+        assert!(is_writable(&dir));
+        assert!(std::fs::read_dir(&dir).unwrap().next().is_none());
+        assert!(!is_writable(&dir.join("missing")));
+    }
+}
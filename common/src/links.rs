@@ -0,0 +1,114 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+//! Links from regions of a hint to other hints, such as "continue on page 7" or an index page
+//! with a link to each section, so that a pack of hints can be read like a document.
+
+use imgui::{MouseButton, MouseCursor, Ui};
+use serde::Deserialize;
+
+use crate::view::{image_rect_to_screen, pixel_rect_to_uv, screen_to_image, Viewport};
+
+const HOVERED_COLOUR: [f32; 4] = [0.3, 0.6, 1.0, 0.8];
+const HOVERED_THICKNESS: f32 = 2.0;
+
+/// A region of a hint that goes to another hint when clicked, set in `hints.toml`.
+#[derive(Debug, Deserialize)]
+pub struct HintLink {
+    /// The region of the image, as the left, top, width and height in pixels.
+    pub(crate) rect: [f32; 4],
+    /// The filename of the hint to go to.
+    pub(crate) to: String,
+}
+
+/// The first of `links` whose region of an image of `image_size` contains `uv`, in normalised
+/// image coordinates.
+pub(crate) fn link_at(
+    links: &[HintLink],
+    uv: [f32; 2],
+    image_size: (u32, u32),
+) -> Option<&HintLink> {
+    links.iter().find(|link| {
+        let (top_left, bottom_right) = pixel_rect_to_uv(link.rect, image_size);
+        (0..2).all(|axis| (top_left[axis]..=bottom_right[axis]).contains(&uv[axis]))
+    })
+}
+
+/// Outlines the link under the pointer in an image of `image_size` that was drawn into `region`
+/// with `viewport`, and returns the hint it goes to if it was clicked. A link is followed when the
+/// mouse button is released without having dragged, so that dragging to pan a zoomed hint can
+/// start over a link.
+pub(crate) fn draw_links<'a>(
+    ui: &Ui,
+    links: &'a [HintLink],
+    image_size: (u32, u32),
+    viewport: Viewport,
+    region: ([f32; 2], [f32; 2]),
+) -> Option<&'a str> {
+    // items drawn over the image, such as the minimap, take the click instead
+    if !ui.is_window_hovered() || ui.is_any_item_hovered() {
+        return None;
+    }
+    let uv = screen_to_image(ui.io().mouse_pos, image_size, viewport, region)?;
+    let link = link_at(links, uv, image_size)?;
+    let uv_rect = pixel_rect_to_uv(link.rect, image_size);
+    if let Some((min, max)) = image_rect_to_screen(uv_rect, image_size, viewport, region) {
+        ui.get_window_draw_list()
+            .add_rect(min, max, HOVERED_COLOUR)
+            .thickness(HOVERED_THICKNESS)
+            .build();
+    }
+    ui.set_mouse_cursor(Some(MouseCursor::Hand));
+    ui.tooltip_text(format!("Go to {}", link.to));
+    // the drag delta is exactly zero unless the drag threshold was passed
+    #[allow(clippy::float_cmp)]
+    let clicked = ui.is_mouse_released(MouseButton::Left)
+        && ui.mouse_drag_delta_with_button(MouseButton::Left) == [0.0, 0.0];
+    clicked.then_some(link.to.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(rect: [f32; 4], to: &str) -> HintLink {
+        HintLink {
+            rect,
+            to: String::from(to),
+        }
+    }
+
+    #[test]
+    fn finds_the_link_under_a_point() {
+        let links = [
+            link([0.0, 0.0, 50.0, 20.0], "engines.png"),
+            link([0.0, 20.0, 50.0, 20.0], "fuel.png"),
+        ];
+        let to = |uv| link_at(&links, uv, (100, 100)).map(|link| link.to.as_str());
+        assert_eq!(to([0.25, 0.1]), Some("engines.png"));
+        assert_eq!(to([0.25, 0.3]), Some("fuel.png"));
+        assert_eq!(to([0.75, 0.1]), None);
+    }
+
+    #[test]
+    fn reads_links_from_toml() {
+        #[derive(Deserialize)]
+        struct Hint {
+            links: Vec<HintLink>,
+        }
+
+        let hint: Hint = toml::from_str(
+            r#"
+            [[links]]
+            rect = [20, 300, 200, 30]
+            to = "page7.png"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(hint.links[0].to, "page7.png");
+        assert_eq!(hint.links[0].rect, [20.0, 300.0, 200.0, 30.0]);
+    }
+}
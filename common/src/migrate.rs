@@ -0,0 +1,126 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+//! Keeping the user's settings for each hint when a pack is updated. Settings are keyed by the
+//! hint's filename, and updates often renumber the hints, such as `003-starting.png` becoming
+//! `004-starting.png` when a page is added before it. Settings for a hint that is no longer in the
+//! pack are moved to the hint with the same title, its filename without the number.
+
+use std::collections::BTreeMap;
+
+/// The outcome of moving settings to the hints of an updated pack.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct Migration {
+    /// The hints whose settings were moved, from their old name to their new one.
+    pub(crate) moved: Vec<(String, String)>,
+    /// The hints whose settings match no hint in the pack, which are kept in case the hint comes
+    /// back.
+    pub(crate) orphans: Vec<String>,
+}
+
+/// Moves the settings in `settings` for hints that aren't among `names` to the hint of the same
+/// title, if there is exactly one and it has no settings of its own. Settings for hints that
+/// aren't files, such as charts and the pages of PDFs, whose names have a category, are left
+/// alone.
+pub(crate) fn migrate_settings<T>(
+    settings: &mut BTreeMap<String, T>,
+    names: &[String],
+) -> Migration {
+    let mut migration = Migration::default();
+    let missing: Vec<String> = settings
+        .keys()
+        .filter(|name| !name.contains('/') && !names.contains(name))
+        .cloned()
+        .collect();
+    for old in missing {
+        let mut matches = names.iter().filter(|name| title(name) == title(&old));
+        let new = match (matches.next(), matches.next()) {
+            (Some(new), None) if !settings.contains_key(new) => new.clone(),
+            _ => {
+                migration.orphans.push(old);
+                continue;
+            }
+        };
+        if let Some(value) = settings.remove(&old) {
+            settings.insert(new.clone(), value);
+            migration.moved.push((old, new));
+        }
+    }
+    migration
+}
+
+/// The title of a hint, its filename without any leading number and the separator after it. A
+/// hint named only by a number, such as `747.png`, is its own title.
+fn title(name: &str) -> &str {
+    let rest = name.trim_start_matches(|c: char| c.is_ascii_digit());
+    if rest.len() == name.len() || rest.starts_with('.') {
+        return name;
+    }
+    rest.trim_start_matches(['-', '_', ' '])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| (*name).to_string()).collect()
+    }
+
+    #[test]
+    fn settings_follow_renumbered_hints() {
+        let mut settings = BTreeMap::from([
+            (String::from("003-starting.png"), 1),
+            (String::from("010-taxi.png"), 2),
+            (String::from("notes.png"), 3),
+            (String::from("manual/Page 3"), 4),
+        ]);
+        let migration = migrate_settings(
+            &mut settings,
+            &names(&["004-starting.png", "011_taxi.png", "notes.png"]),
+        );
+        assert_eq!(
+            settings,
+            BTreeMap::from([
+                (String::from("004-starting.png"), 1),
+                (String::from("011_taxi.png"), 2),
+                (String::from("notes.png"), 3),
+                (String::from("manual/Page 3"), 4),
+            ])
+        );
+        assert_eq!(migration.moved.len(), 2);
+        assert!(migration.orphans.is_empty());
+    }
+
+    #[test]
+    fn unmatched_settings_are_orphans() {
+        let mut settings = BTreeMap::from([
+            (String::from("003-starting.png"), 1),
+            (String::from("004-starting.png"), 2),
+            (String::from("005-removed.png"), 3),
+        ]);
+        let migration = migrate_settings(
+            &mut settings,
+            &names(&["001-starting.png", "002-starting.png", "004-starting.png"]),
+        );
+        assert_eq!(
+            migration,
+            Migration {
+                moved: vec![],
+                orphans: names(&["003-starting.png", "005-removed.png"]),
+            }
+        );
+        assert_eq!(settings.len(), 3);
+    }
+
+    #[test]
+    fn titles_leave_out_numbers() {
+        assert_eq!(title("003-starting.png"), "starting.png");
+        assert_eq!(title("12 taxi.png"), "taxi.png");
+        assert_eq!(title("taxi.png"), "taxi.png");
+        assert_eq!(title("747.png"), "747.png");
+    }
+}
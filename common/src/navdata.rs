@@ -0,0 +1,134 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+use std::cell::RefCell;
+
+use imgui::Ui;
+
+use crate::view::{HEADING_COLOUR, WARNING_COLOUR};
+
+/// Looks up airports in the sim's navigation data. Only available in X-Plane.
+pub trait NavData {
+    /// Returns the airport with the identifier `ident`, such as `EGLL`.
+    fn find_airport(&self, ident: &str) -> Option<Airport>;
+
+    /// Returns the airport nearest to the aircraft.
+    fn nearest_airport(&self) -> Option<Airport>;
+
+    /// Returns the identifiers of the airports in the flight plan, such as the origin and
+    /// destination, in the order they are flown.
+    fn flight_plan_airports(&self) -> Vec<String> {
+        vec![]
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Airport {
+    pub ident: String,
+    pub name: String,
+    pub elevation_ft: f32,
+    /// Radio navigation aids at or near the airport, such as ILS localizers and VORs.
+    pub navaids: Vec<Navaid>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Navaid {
+    pub kind: &'static str,
+    pub ident: String,
+    pub name: String,
+    /// The frequency, formatted with its units.
+    pub frequency: String,
+    /// The course of a localizer, in degrees.
+    pub course: Option<f32>,
+}
+
+/// A panel showing the navaids of an airport typed in by the user or nearest to the aircraft.
+#[derive(Debug, Default)]
+pub(crate) struct NavPanel {
+    ident: RefCell<String>,
+    airport: RefCell<Option<Result<Airport, String>>>,
+}
+
+impl NavPanel {
+    /// Draws the panel, with a button to show the airport's charts if `charts` is `true`.
+    /// Returns the identifier of the airport if the button was clicked.
+    pub(crate) fn draw(
+        &self,
+        ui: &Ui,
+        nav_data: Option<&dyn NavData>,
+        charts: bool,
+    ) -> Option<String> {
+        let nav_data = match nav_data {
+            Some(nav_data) => nav_data,
+            None => {
+                ui.text_wrapped("Navigation data is only available in X-Plane.");
+                ui.separator();
+                return None;
+            }
+        };
+        let search = {
+            let mut ident = self.ident.borrow_mut();
+            ui.input_text("Airport##navdata", &mut ident)
+                .chars_uppercase(true)
+                .auto_select_all(true)
+                .enter_returns_true(true)
+                .build()
+        };
+        ui.same_line();
+        let find = ui.button("Find");
+        ui.same_line();
+        if ui.button("Nearest") {
+            let airport = nav_data
+                .nearest_airport()
+                .ok_or_else(|| String::from("No airport found near the aircraft"));
+            if let Ok(airport) = &airport {
+                self.ident.replace(airport.ident.clone());
+            }
+            self.airport.replace(Some(airport));
+        } else if search || find {
+            let ident = self.ident.borrow().trim().to_string();
+            let airport = nav_data
+                .find_airport(&ident)
+                .ok_or_else(|| format!("No airport found with identifier {ident}"));
+            self.airport.replace(Some(airport));
+        }
+
+        let mut charts_for = None;
+        match self.airport.borrow().as_ref() {
+            Some(Ok(airport)) => {
+                draw_airport(ui, airport);
+                if charts && ui.button("Show charts") {
+                    charts_for = Some(airport.ident.clone());
+                }
+            }
+            Some(Err(message)) => ui.text_colored(WARNING_COLOUR, message),
+            None => {}
+        }
+        ui.separator();
+        charts_for
+    }
+}
+
+fn draw_airport(ui: &Ui, airport: &Airport) {
+    ui.text_colored(
+        HEADING_COLOUR,
+        format!("{} {}", airport.ident, airport.name),
+    );
+    ui.text(format!("Elevation {:.0} ft", airport.elevation_ft));
+    if airport.navaids.is_empty() {
+        ui.text("No navaids found");
+    }
+    for navaid in &airport.navaids {
+        let course = navaid
+            .course
+            .map(|course| format!(" {course:03.0}°"))
+            .unwrap_or_default();
+        ui.bullet_text(format!(
+            "{} {} {}{course} {}",
+            navaid.kind, navaid.ident, navaid.frequency, navaid.name
+        ));
+    }
+}
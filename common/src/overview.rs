@@ -0,0 +1,133 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+//! The overview, a grid of thumbnails of every hint for finding a page in aircraft with many of
+//! them. Clicking a thumbnail goes to its hint. Thumbnails are made in the background, and a
+//! placeholder of the same size is drawn until each is ready.
+
+use image::imageops;
+use image::RgbaImage;
+use imgui::{TextureId, Ui};
+
+use crate::view::HEADING_COLOUR;
+
+/// The width and height that thumbnails are fitted into.
+const THUMBNAIL_SIZE: f32 = 120.0;
+const THUMBNAIL_SPACING: f32 = 8.0;
+const CURRENT_COLOUR: [f32; 4] = [0.3, 0.6, 1.0, 1.0];
+
+/// A hint as shown in the overview.
+pub(crate) struct Thumbnail<'a> {
+    pub(crate) title: &'a str,
+    /// The size of the hint's image, or `None` if it isn't an image.
+    pub(crate) dimensions: Option<(u32, u32)>,
+    /// The texture of the thumbnail, once it has been made.
+    pub(crate) texture: Option<TextureId>,
+}
+
+/// Draws a thumbnail of each of `thumbnails`, outlining the one at `current`, and returns the
+/// index of the one clicked, if any.
+pub(crate) fn draw_overview(ui: &Ui, thumbnails: &[Thumbnail], current: usize) -> Option<usize> {
+    ui.text_colored(HEADING_COLOUR, "All hints");
+    let columns = grid_columns(ui.content_region_avail()[0]);
+    let mut clicked = None;
+    for (idx, thumbnail) in thumbnails.iter().enumerate() {
+        if idx % columns != 0 {
+            ui.same_line_with_spacing(0.0, THUMBNAIL_SPACING);
+        }
+        let _id = ui.push_id_usize(idx);
+        let pressed = match (thumbnail.texture, thumbnail.dimensions) {
+            (Some(texture_id), Some(image_size)) => {
+                let pressed = ui.image_button("thumbnail", texture_id, fitted_size(image_size));
+                if ui.is_item_hovered() {
+                    ui.tooltip_text(thumbnail.title);
+                }
+                pressed
+            }
+            // a placeholder, the size the thumbnail will be
+            (None, Some(image_size)) => {
+                ui.button_with_size(thumbnail.title, fitted_size(image_size))
+            }
+            (_, None) => ui.button_with_size(thumbnail.title, [THUMBNAIL_SIZE, THUMBNAIL_SIZE]),
+        };
+        if idx == current {
+            ui.get_window_draw_list()
+                .add_rect(ui.item_rect_min(), ui.item_rect_max(), CURRENT_COLOUR)
+                .thickness(2.0)
+                .build();
+        }
+        if pressed {
+            clicked = Some(idx);
+        }
+    }
+    clicked
+}
+
+/// Shrinks `image` to the size of its thumbnail.
+pub(crate) fn make_thumbnail(image: &RgbaImage) -> RgbaImage {
+    let [width, height] = fitted_size(image.dimensions());
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let (width, height) = (
+        width.round().max(1.0) as u32,
+        height.round().max(1.0) as u32,
+    );
+    imageops::thumbnail(image, width, height)
+}
+
+/// The number of thumbnails that fit across a region `width` wide, which is at least one.
+fn grid_columns(width: f32) -> usize {
+    let columns = ((width + THUMBNAIL_SPACING) / (THUMBNAIL_SIZE + THUMBNAIL_SPACING)).floor();
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let columns = columns.max(1.0) as usize;
+    columns
+}
+
+/// The size of a thumbnail of an image of `image_size`, keeping its aspect ratio.
+#[allow(clippy::cast_precision_loss)]
+fn fitted_size(image_size: (u32, u32)) -> [f32; 2] {
+    let (width, height) = (image_size.0 as f32, image_size.1 as f32);
+    let scale = THUMBNAIL_SIZE / width.max(height).max(1.0);
+    [width * scale, height * scale]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fits_as_many_columns_as_there_is_room_for() {
+        assert_eq!(grid_columns(50.0), 1);
+        assert_eq!(grid_columns(THUMBNAIL_SIZE), 1);
+        assert_eq!(
+            grid_columns(THUMBNAIL_SIZE * 3.0 + THUMBNAIL_SPACING * 2.0),
+            3
+        );
+    }
+
+    #[test]
+    fn thumbnails_are_made_at_their_drawn_size() {
+        assert_eq!(
+            make_thumbnail(&RgbaImage::new(1200, 600)).dimensions(),
+            (120, 60)
+        );
+        assert_eq!(
+            make_thumbnail(&RgbaImage::new(1, 1000)).dimensions(),
+            (1, 120)
+        );
+    }
+
+    #[test]
+    fn thumbnails_keep_the_aspect_ratio() {
+        assert_eq!(
+            fitted_size((1200, 600)),
+            [THUMBNAIL_SIZE, THUMBNAIL_SIZE / 2.0]
+        );
+        assert_eq!(
+            fitted_size((300, 600)),
+            [THUMBNAIL_SIZE / 2.0, THUMBNAIL_SIZE]
+        );
+    }
+}
@@ -0,0 +1,163 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+//! Checking a panel diagram against the state of the panel in the sim. A hint can list the
+//! switches shown in it along with where they are and the expression that is true when each is
+//! set as the diagram shows, and each is marked green or red over the image accordingly, such as
+//! for a cold and dark flow.
+
+use imgui::Ui;
+use serde::Deserialize;
+
+use crate::expression::Expression;
+use crate::template::DatarefReader;
+use crate::view::{image_to_screen, Viewport};
+
+const SET_COLOUR: [f32; 4] = [0.2, 0.9, 0.2, 0.8];
+const NOT_SET_COLOUR: [f32; 4] = [0.9, 0.2, 0.2, 0.8];
+/// The colour of switches whose datarefs can't be read, such as when there is no sim.
+const UNKNOWN_COLOUR: [f32; 4] = [0.6, 0.6, 0.6, 0.8];
+const OUTLINE_COLOUR: [f32; 4] = [0.0, 0.0, 0.0, 0.8];
+const MARKER_RADIUS: f32 = 7.0;
+
+/// A switch shown in a panel diagram, set in `hints.toml`.
+#[derive(Debug, Deserialize)]
+pub struct PanelSwitch {
+    /// The name of the switch, shown when the pointer is over its marker.
+    pub(crate) name: String,
+    /// Where the marker is drawn, in pixels from the top left of the image.
+    pub(crate) at: [f32; 2],
+    /// True when the switch is set as the diagram shows.
+    pub(crate) expect: Expression,
+}
+
+/// Whether each of `switches` is set as expected, or `None` where its datarefs can't be read.
+pub(crate) fn switch_states(
+    switches: &[PanelSwitch],
+    datarefs: &dyn DatarefReader,
+) -> Vec<Option<bool>> {
+    switches
+        .iter()
+        .map(|switch| switch.expect.evaluate(datarefs).map(|value| value != 0.0))
+        .collect()
+}
+
+/// Draws a line saying how many of `switches` are set, for drawing above the image.
+pub(crate) fn draw_summary(ui: &Ui, switches: &[PanelSwitch], datarefs: &dyn DatarefReader) {
+    let states = switch_states(switches, datarefs);
+    if states.iter().any(Option::is_none) {
+        ui.text_colored(UNKNOWN_COLOUR, "Panel state unavailable");
+        return;
+    }
+    let set = states.iter().filter(|state| **state == Some(true)).count();
+    let colour = if set == states.len() {
+        SET_COLOUR
+    } else {
+        NOT_SET_COLOUR
+    };
+    ui.text_colored(colour, format!("{set} of {} switches set", states.len()));
+}
+
+/// Draws a marker over each of `switches` in an image of `image_size` that was drawn into
+/// `region` with `viewport`, showing the name of a switch when the pointer is over its marker.
+pub(crate) fn draw_panel_state(
+    ui: &Ui,
+    switches: &[PanelSwitch],
+    datarefs: &dyn DatarefReader,
+    image_size: (u32, u32),
+    viewport: Viewport,
+    region: ([f32; 2], [f32; 2]),
+) {
+    let draw_list = ui.get_window_draw_list();
+    let mouse = ui.io().mouse_pos;
+    let states = switch_states(switches, datarefs);
+    #[allow(clippy::cast_precision_loss)]
+    let (width, height) = (image_size.0 as f32, image_size.1 as f32);
+    if width <= 0.0 || height <= 0.0 {
+        return;
+    }
+    for (switch, state) in switches.iter().zip(states) {
+        let uv = [switch.at[0] / width, switch.at[1] / height];
+        let centre = match image_to_screen(uv, image_size, viewport, region) {
+            Some(centre) => centre,
+            None => continue,
+        };
+        let (colour, status) = match state {
+            Some(true) => (SET_COLOUR, "set"),
+            Some(false) => (NOT_SET_COLOUR, "not set"),
+            None => (UNKNOWN_COLOUR, "unknown"),
+        };
+        draw_list
+            .add_circle(centre, MARKER_RADIUS, colour)
+            .filled(true)
+            .build();
+        draw_list
+            .add_circle(centre, MARKER_RADIUS, OUTLINE_COLOUR)
+            .build();
+        let hovered = (mouse[0] - centre[0]).hypot(mouse[1] - centre[1]) <= MARKER_RADIUS;
+        if hovered && ui.is_window_hovered() {
+            ui.tooltip_text(format!("{}: {status}", switch.name));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::template::FixedDatarefs;
+
+    fn switch(expect: &str) -> PanelSwitch {
+        PanelSwitch {
+            name: String::from("Battery"),
+            at: [10.0, 20.0],
+            expect: Expression::parse(expect).unwrap(),
+        }
+    }
+
+    #[test]
+    fn compares_switches_with_datarefs() {
+        let values = FixedDatarefs::new([("sim/battery", 0.0), ("sim/beacon", 1.0)]);
+        let switches = [
+            switch("\"sim/battery\" == 0"),
+            switch("\"sim/beacon\" == 0"),
+            switch("\"sim/missing\" == 0"),
+        ];
+        assert_eq!(
+            switch_states(&switches, &values),
+            [Some(true), Some(false), None]
+        );
+    }
+
+    #[test]
+    fn reads_switches_from_toml() {
+        #[derive(Deserialize)]
+        struct Hint {
+            switches: Vec<PanelSwitch>,
+        }
+
+        let hint: Hint = toml::from_str(
+            r#"
+            [[switches]]
+            name = "Battery"
+            at = [120, 45.5]
+            expect = '"sim/cockpit/electrical/battery_on" == 0'
+            "#,
+        )
+        .unwrap();
+        assert_eq!(hint.switches[0].name, "Battery");
+        assert_eq!(hint.switches[0].at, [120.0, 45.5]);
+
+        let invalid: Result<Hint, _> = toml::from_str(
+            r#"
+            [[switches]]
+            name = "Battery"
+            at = [120, 45]
+            expect = '"sim/battery" =='
+            "#,
+        );
+        assert!(invalid.is_err());
+    }
+}
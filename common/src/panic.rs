@@ -0,0 +1,45 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+use std::any::Any;
+use std::backtrace::Backtrace;
+use std::panic::{self, AssertUnwindSafe};
+
+use tracing::error;
+
+/// Runs `f`, catching any panic so that it cannot unwind into the host application.
+///
+/// # Errors
+///
+/// Returns the panic message if `f` panics.
+pub fn catch_panic<R, F: FnOnce() -> R>(context: &str, f: F) -> Result<R, String> {
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(|payload| {
+        let message = panic_message(payload.as_ref());
+        error!(context, message, "Hints encountered an error");
+        message
+    })
+}
+
+/// Replaces the default panic hook, which writes to stderr, with one that logs the panic
+/// location and a backtrace.
+pub fn install_panic_hook() {
+    panic::set_hook(Box::new(|info| {
+        let location = info.location().map(ToString::to_string).unwrap_or_default();
+        let message = panic_message(info.payload());
+        let backtrace = Backtrace::force_capture();
+        error!(location, message, %backtrace, "Panic");
+    }));
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        (*message).to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        String::from("Unknown error")
+    }
+}
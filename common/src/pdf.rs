@@ -0,0 +1,95 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+//! PDF hints, such as an aircraft's manual, shown as an image hint for each page. Pages are
+//! rendered when they are needed, as other images are decoded, with pdfium, which is only built
+//! with the `pdf` feature and needs the pdfium library installed where the system can find it,
+//! such as alongside the X-Plane executable.
+
+use std::path::Path;
+
+use image::RgbaImage;
+
+use crate::error::HintsError;
+
+pub(crate) const PDF_EXTENSION: &str = "pdf";
+/// Whether this build can show PDFs. Without it, PDFs in the hints directory are ignored.
+pub(crate) const PDF_SUPPORTED: bool = cfg!(feature = "pdf");
+/// The width pages are rendered at, which is enough to read a page zoomed in on a large window.
+#[cfg(feature = "pdf")]
+const PAGE_WIDTH: i32 = 1600;
+
+/// The size each page of the PDF at `path` is rendered at, which gives the number of pages without
+/// rendering any of them.
+#[cfg(feature = "pdf")]
+pub(crate) fn page_dimensions(path: &Path) -> Result<Vec<(u32, u32)>, HintsError> {
+    use pdfium_render::prelude::Pdfium;
+
+    let pdfium = Pdfium::new(Pdfium::bind_to_system_library()?);
+    let document = pdfium.load_pdf_from_file(path, None)?;
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss
+    )]
+    let dimensions = document
+        .pages()
+        .iter()
+        .map(|page| {
+            let height = PAGE_WIDTH as f32 * page.height().value / page.width().value;
+            (PAGE_WIDTH as u32, height as u32)
+        })
+        .collect();
+    Ok(dimensions)
+}
+
+/// Renders page `page` of the PDF at `path`, counting from 0.
+#[cfg(feature = "pdf")]
+pub(crate) fn render_page(path: &Path, page: usize) -> Result<RgbaImage, HintsError> {
+    use pdfium_render::prelude::{PdfRenderConfig, Pdfium};
+
+    let index = u16::try_from(page)
+        .map_err(|_| HintsError::Pdf(format!("{} has too many pages", path.display())))?;
+    let pdfium = Pdfium::new(Pdfium::bind_to_system_library()?);
+    let document = pdfium.load_pdf_from_file(path, None)?;
+    let config = PdfRenderConfig::new().set_target_width(PAGE_WIDTH);
+    let page = document.pages().get(index)?;
+    Ok(page.render_with_config(&config)?.as_image().into_rgba8())
+}
+
+#[cfg(not(feature = "pdf"))]
+pub(crate) fn page_dimensions(_path: &Path) -> Result<Vec<(u32, u32)>, HintsError> {
+    Err(unsupported())
+}
+
+#[cfg(not(feature = "pdf"))]
+pub(crate) fn render_page(_path: &Path, _page: usize) -> Result<RgbaImage, HintsError> {
+    Err(unsupported())
+}
+
+#[cfg(not(feature = "pdf"))]
+fn unsupported() -> HintsError {
+    HintsError::Pdf(String::from("this build does not support PDFs"))
+}
+
+/// The name of page `page` of the PDF hint `name`, counting from 1, such as `manual/Page 3`. The
+/// PDF's name is the category of its pages, so they are listed under it on the contents page.
+pub(crate) fn page_name(name: &str, page: usize) -> String {
+    let title = Path::new(name)
+        .file_stem()
+        .map_or_else(|| name.into(), |stem| stem.to_string_lossy());
+    format!("{title}/Page {page}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pages_are_named_after_the_pdf() {
+        assert_eq!(page_name("010-manual.pdf", 3), "010-manual/Page 3");
+    }
+}
@@ -0,0 +1,293 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+use std::cell::{Cell, RefCell};
+use std::path::{Path, PathBuf};
+
+use imgui::Ui;
+use serde::Deserialize;
+
+use crate::error::HintsError;
+use crate::inputs::{load_inputs, read_calculator, save_inputs};
+use crate::template::DatarefReader;
+use crate::view::draw_title;
+
+/// A takeoff and landing data (TOLD) calculator, read from a `.told` file containing TOML such as:
+///
+/// ```toml
+/// title = "Takeoff"
+///
+/// [[inputs]]
+/// name = "Weight"
+/// units = "kg"
+/// dataref = "sim/flightmodel/weight/m_total"
+///
+/// [[inputs]]
+/// name = "Temperature"
+/// units = "C"
+/// dataref = "sim/weather/aircraft/temperature_ambient_deg_c"
+///
+/// [[outputs]]
+/// name = "Ground roll"
+/// units = "m"
+/// inputs = ["Weight", "Temperature"]
+/// breakpoints = [[900.0, 1100.0], [0.0, 40.0]]
+/// values = [180.0, 220.0, 260.0, 320.0]
+/// ```
+///
+/// Each output is a table over one or more of the inputs, interpolated linearly between
+/// breakpoints and clamped at the edges of the table. `values` lists the table in order with the
+/// last input varying fastest. Inputs with a `dataref` are filled in from the sim, multiplied by
+/// `scale` if given, when no value has been saved for them and when the user asks.
+#[derive(Debug)]
+pub struct Performance {
+    dir: PathBuf,
+    name: String,
+    file: PerformanceFile,
+    values: RefCell<Vec<f32>>,
+    unfilled: Cell<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PerformanceFile {
+    title: Option<String>,
+    inputs: Vec<Input>,
+    outputs: Vec<Output>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Input {
+    name: String,
+    #[serde(default)]
+    units: String,
+    #[serde(default)]
+    default: f32,
+    dataref: Option<String>,
+    scale: Option<f32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Output {
+    name: String,
+    #[serde(default)]
+    units: String,
+    inputs: Vec<String>,
+    breakpoints: Vec<Vec<f32>>,
+    values: Vec<f32>,
+    #[serde(skip)]
+    input_indexes: Vec<usize>,
+}
+
+impl Performance {
+    pub(crate) fn load(path: &Path, settings_dir: &Path) -> Result<Self, HintsError> {
+        let (text, name) = read_calculator(path)?;
+        Self::parse(&text, name, settings_dir)
+    }
+
+    pub(crate) fn parse(text: &str, name: String, settings_dir: &Path) -> Result<Self, HintsError> {
+        let mut file: PerformanceFile = toml::from_str(text)?;
+        for output in &mut file.outputs {
+            output.input_indexes = validate(output, &file.inputs)
+                .map_err(|e| HintsError::Config(format!("Output '{}': {e}", output.name)))?;
+        }
+        let dir = settings_dir.to_path_buf();
+        let saved = load_inputs(&dir, &name);
+        let values = file
+            .inputs
+            .iter()
+            .map(|input| saved.get(&input.name).copied().unwrap_or(input.default))
+            .collect();
+        Ok(Performance {
+            dir,
+            name,
+            unfilled: Cell::new(saved.is_empty()),
+            file,
+            values: RefCell::new(values),
+        })
+    }
+
+    /// Sets each input that has a dataref to the sim's current value.
+    fn fill_from_sim(&self, datarefs: &dyn DatarefReader) {
+        let mut values = self.values.borrow_mut();
+        for (input, value) in self.file.inputs.iter().zip(values.iter_mut()) {
+            if let Some(sim_value) = input
+                .dataref
+                .as_ref()
+                .and_then(|dataref| datarefs.read(dataref, None))
+            {
+                #[allow(clippy::cast_possible_truncation)]
+                let sim_value = sim_value as f32;
+                *value = sim_value * input.scale.unwrap_or(1.0);
+            }
+        }
+    }
+
+    fn save(&self) {
+        let values = self.values.borrow();
+        let inputs = self
+            .file
+            .inputs
+            .iter()
+            .zip(values.iter())
+            .map(|(input, value)| (input.name.clone(), *value))
+            .collect();
+        save_inputs(&self.dir, &self.name, inputs);
+    }
+
+    pub fn draw(&self, ui: &Ui, datarefs: &dyn DatarefReader) {
+        if self.unfilled.take() {
+            self.fill_from_sim(datarefs);
+        }
+        if let Some(title) = &self.file.title {
+            draw_title(ui, title);
+        }
+        let mut edited = false;
+        {
+            let mut values = self.values.borrow_mut();
+            for (idx, (input, value)) in self.file.inputs.iter().zip(values.iter_mut()).enumerate()
+            {
+                ui.input_float(format!("{} {}##input{idx}", input.name, input.units), value)
+                    .display_format("%.1f")
+                    .build();
+                edited |= ui.is_item_deactivated_after_edit();
+            }
+        }
+        if self.file.inputs.iter().any(|input| input.dataref.is_some())
+            && ui.button("Use sim values")
+        {
+            self.fill_from_sim(datarefs);
+            edited = true;
+        }
+        if edited {
+            self.save();
+        }
+
+        ui.separator();
+        for (output, value) in self.file.outputs.iter().zip(self.output_values()) {
+            ui.text(format!("{}: {value:.0} {}", output.name, output.units));
+        }
+    }
+
+    /// The value of each output for the current inputs.
+    pub(crate) fn output_values(&self) -> Vec<f32> {
+        let values = self.values.borrow();
+        self.file
+            .outputs
+            .iter()
+            .map(|output| {
+                let point: Vec<f32> = output
+                    .input_indexes
+                    .iter()
+                    .map(|idx| values[*idx])
+                    .collect();
+                interpolate(&output.breakpoints, &output.values, &point)
+            })
+            .collect()
+    }
+}
+
+/// Checks that the table for `output` is well formed, returning the index of each of its inputs.
+fn validate(output: &Output, inputs: &[Input]) -> Result<Vec<usize>, String> {
+    if output.inputs.is_empty() || output.inputs.len() != output.breakpoints.len() {
+        return Err(String::from(
+            "there must be one list of breakpoints for each input",
+        ));
+    }
+    for breakpoints in &output.breakpoints {
+        if breakpoints.is_empty() || breakpoints.windows(2).any(|pair| pair[0] >= pair[1]) {
+            return Err(String::from("breakpoints must be in increasing order"));
+        }
+    }
+    let expected: usize = output.breakpoints.iter().map(Vec::len).product();
+    if output.values.len() != expected {
+        return Err(format!(
+            "expected {expected} values but found {}",
+            output.values.len()
+        ));
+    }
+    output
+        .inputs
+        .iter()
+        .map(|name| {
+            inputs
+                .iter()
+                .position(|input| input.name == *name)
+                .ok_or_else(|| format!("unknown input '{name}'"))
+        })
+        .collect()
+}
+
+/// Linearly interpolates the table `values`, laid out with the last axis varying fastest, at
+/// `point`. Points outside the breakpoints are clamped to the edge of the table.
+fn interpolate(breakpoints: &[Vec<f32>], values: &[f32], point: &[f32]) -> f32 {
+    let (axis, x) = match (breakpoints.first(), point.first()) {
+        (Some(axis), Some(&x)) => (axis, x),
+        _ => return values.first().copied().unwrap_or_default(),
+    };
+    let stride = values.len() / axis.len();
+    let value_at = |idx: usize| {
+        interpolate(
+            &breakpoints[1..],
+            &values[idx * stride..(idx + 1) * stride],
+            &point[1..],
+        )
+    };
+    let upper = axis.partition_point(|breakpoint| *breakpoint < x);
+    if upper == 0 {
+        value_at(0)
+    } else if upper == axis.len() {
+        value_at(axis.len() - 1)
+    } else {
+        let lower = upper - 1;
+        let fraction = (x - axis[lower]) / (axis[upper] - axis[lower]);
+        value_at(lower) + (value_at(upper) - value_at(lower)) * fraction
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::interpolate;
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn interpolates_between_breakpoints() {
+        let breakpoints = [vec![0.0, 10.0, 20.0]];
+        let values = [100.0, 200.0, 400.0];
+        assert_eq!(interpolate(&breakpoints, &values, &[5.0]), 150.0);
+        assert_eq!(interpolate(&breakpoints, &values, &[15.0]), 300.0);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn uses_the_value_at_a_breakpoint() {
+        let breakpoints = [vec![0.0, 10.0, 20.0]];
+        let values = [100.0, 200.0, 400.0];
+        assert_eq!(interpolate(&breakpoints, &values, &[0.0]), 100.0);
+        assert_eq!(interpolate(&breakpoints, &values, &[10.0]), 200.0);
+        assert_eq!(interpolate(&breakpoints, &values, &[20.0]), 400.0);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn clamps_points_outside_the_table() {
+        let breakpoints = [vec![0.0, 10.0, 20.0]];
+        let values = [100.0, 200.0, 400.0];
+        assert_eq!(interpolate(&breakpoints, &values, &[-5.0]), 100.0);
+        assert_eq!(interpolate(&breakpoints, &values, &[25.0]), 400.0);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn interpolates_each_axis_in_turn() {
+        // rows are the first axis, columns the second
+        let breakpoints = [vec![0.0, 10.0], vec![0.0, 100.0]];
+        let values = [0.0, 1.0, 10.0, 11.0];
+        assert_eq!(interpolate(&breakpoints, &values, &[5.0, 50.0]), 5.5);
+        assert_eq!(interpolate(&breakpoints, &values, &[10.0, 0.0]), 10.0);
+        assert_eq!(interpolate(&breakpoints, &values, &[20.0, 50.0]), 10.5);
+        assert_eq!(interpolate(&breakpoints, &values, &[5.0, -50.0]), 5.0);
+    }
+}
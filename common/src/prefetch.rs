@@ -0,0 +1,137 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+//! Decoding of the images of hints near the current one on a background thread, so that turning
+//! to them doesn't wait for them to be decoded. The pages of PDFs are rendered here too, as are
+//! the thumbnails in the overview.
+
+use std::sync::mpsc::{Receiver, Sender};
+
+use image::RgbaImage;
+use tracing::trace;
+
+use crate::concurrent::thread_loader;
+use crate::error::HintsError;
+use crate::hints::ImageSource;
+use crate::overview::make_thumbnail;
+use crate::panic::catch_panic;
+
+type Decoded = (Request, Result<RgbaImage, HintsError>);
+
+/// An image to decode in the background.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Request {
+    /// The image at full size, to be shown.
+    Image(ImageSource),
+    /// A thumbnail of the image, for the overview.
+    Thumbnail(ImageSource),
+}
+
+impl Request {
+    pub(crate) fn source(&self) -> &ImageSource {
+        match self {
+            Request::Image(source) | Request::Thumbnail(source) => source,
+        }
+    }
+
+    fn decode(&self) -> Result<RgbaImage, HintsError> {
+        match self {
+            Request::Image(source) => source.decode(),
+            Request::Thumbnail(source) => Ok(make_thumbnail(&source.decode()?)),
+        }
+    }
+}
+
+pub(crate) struct Prefetcher {
+    tx: Sender<Request>,
+    rx: Receiver<Decoded>,
+    /// The image being decoded, if any. Only one is decoded at a time, so that the next one
+    /// requested is the nearest to the current hint even as the hints are turned.
+    in_flight: Option<Request>,
+}
+
+impl Prefetcher {
+    pub(crate) fn new() -> Self {
+        let (tx, rx) = thread_loader(true, |request: Request| {
+            let decoded = catch_panic("prefetch", || request.decode())
+                .unwrap_or_else(|message| Err(HintsError::Panic(message)));
+            (request, decoded)
+        });
+        Prefetcher {
+            tx,
+            rx,
+            in_flight: None,
+        }
+    }
+
+    /// Whether another image can be requested.
+    pub(crate) fn is_idle(&self) -> bool {
+        self.in_flight.is_none()
+    }
+
+    /// Starts decoding the image for `request`.
+    pub(crate) fn request(&mut self, request: Request) {
+        trace!(?request, "Prefetching image");
+        if self.tx.send(request.clone()).is_ok() {
+            self.in_flight = Some(request);
+        }
+    }
+
+    /// The image that has finished decoding since the last call, if any.
+    pub(crate) fn decoded(&mut self) -> Option<Decoded> {
+        let decoded = self.rx.try_recv().ok()?;
+        self.in_flight = None;
+        Some(decoded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use image::RgbaImage;
+
+    use super::{Prefetcher, Request};
+    use crate::hints::ImageSource;
+    use crate::test_dir::TestDir;
+
+    fn wait_for(prefetcher: &mut Prefetcher) -> (Request, RgbaImage) {
+        let started = Instant::now();
+        loop {
+            if let Some((request, image)) = prefetcher.decoded() {
+                return (request, image.unwrap());
+            }
+            assert!(started.elapsed() < Duration::from_secs(10));
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn decodes_in_the_background() {
+        let dir = TestDir::new();
+        let path = dir.join("hint.png");
+        RgbaImage::new(3, 2).save(&path).unwrap();
+        let mut prefetcher = Prefetcher::new();
+        prefetcher.request(Request::Image(ImageSource::File(path.clone())));
+        assert!(!prefetcher.is_idle());
+        let (request, image) = wait_for(&mut prefetcher);
+        assert_eq!(request, Request::Image(ImageSource::File(path)));
+        assert_eq!(image.dimensions(), (3, 2));
+        assert!(prefetcher.is_idle());
+    }
+
+    #[test]
+    fn makes_thumbnails_in_the_background() {
+        let dir = TestDir::new();
+        let path = dir.join("hint.png");
+        RgbaImage::new(600, 300).save(&path).unwrap();
+        let mut prefetcher = Prefetcher::new();
+        prefetcher.request(Request::Thumbnail(ImageSource::File(path.clone())));
+        let (request, image) = wait_for(&mut prefetcher);
+        assert_eq!(request, Request::Thumbnail(ImageSource::File(path)));
+        assert_eq!(image.dimensions(), (120, 60));
+    }
+}
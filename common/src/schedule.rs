@@ -0,0 +1,254 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Deserialize;
+
+use crate::template::DatarefReader;
+
+/// The day of the year in the sim, counting from 0 for 1 January.
+const DAY_OF_YEAR_DATAREF: &str = "sim/time/local_date_days";
+const DAYS_IN_MONTH: [u32; 12] = [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// A date in the sim.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct SimDate {
+    year: i32,
+    month: u32,
+    day: u32,
+}
+
+impl SimDate {
+    /// Reads the sim's date. X-Plane only gives the day of the year, so the year is taken from the
+    /// computer's clock, which is the year X-Plane uses.
+    pub(crate) fn read(datarefs: &dyn DatarefReader) -> Option<Self> {
+        let day_of_year = datarefs.read(DAY_OF_YEAR_DATAREF, None)?;
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let day_of_year = day_of_year.max(0.0) as u32;
+        Self::from_day_of_year(current_year(), day_of_year)
+    }
+
+    /// The date `day_of_year` days after 1 January of `year`.
+    fn from_day_of_year(year: i32, day_of_year: u32) -> Option<Self> {
+        let mut remaining = day_of_year;
+        for month in 1..=12 {
+            let days = days_in_month(year, month);
+            if remaining < days {
+                return Some(SimDate {
+                    year,
+                    month,
+                    day: remaining + 1,
+                });
+            }
+            remaining -= days;
+        }
+        None
+    }
+}
+
+fn is_leap_year(year: i32) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    if month == 2 && is_leap_year(year) {
+        29
+    } else {
+        DAYS_IN_MONTH[month as usize - 1]
+    }
+}
+
+/// The current year by the computer's clock, in UTC.
+fn current_year() -> i32 {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs() / SECONDS_PER_DAY);
+    let mut year = 1970;
+    let mut remaining = days;
+    loop {
+        let days_in_year = if is_leap_year(year) { 366 } else { 365 };
+        if remaining < days_in_year {
+            return year;
+        }
+        remaining -= days_in_year;
+        year += 1;
+    }
+}
+
+/// A date a hint is valid from or until: either a fixed date such as `2024-02-22`, for example
+/// the end of a chart cycle, or a day each year such as `11-01`, for seasonal procedures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub(crate) enum ValidDate {
+    Fixed(SimDate),
+    Annual { month: u32, day: u32 },
+}
+
+impl ValidDate {
+    /// The date in `year`, for comparing with a date in that year.
+    fn in_year(self, year: i32) -> SimDate {
+        match self {
+            ValidDate::Fixed(date) => date,
+            ValidDate::Annual { month, day } => SimDate { year, month, day },
+        }
+    }
+}
+
+impl TryFrom<String> for ValidDate {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let invalid = || format!("{value:?} is not a date such as 2024-02-22 or 11-01");
+        let parts: Vec<&str> = value.trim().split('-').collect();
+        let (year, month, day) = match parts.as_slice() {
+            [year, month, day] => (
+                Some(year.parse::<i32>().map_err(|_| invalid())?),
+                month,
+                day,
+            ),
+            [month, day] => (None, month, day),
+            _ => return Err(invalid()),
+        };
+        let month: u32 = month.parse().map_err(|_| invalid())?;
+        let day: u32 = day.parse().map_err(|_| invalid())?;
+        // an annual date can be 29 February, which is only reached in leap years
+        let valid_day = (1..=12).contains(&month)
+            && (1..=days_in_month(year.unwrap_or(2000), month)).contains(&day);
+        if !valid_day {
+            return Err(invalid());
+        }
+        Ok(match year {
+            Some(year) => ValidDate::Fixed(SimDate { year, month, day }),
+            None => ValidDate::Annual { month, day },
+        })
+    }
+}
+
+impl fmt::Display for ValidDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidDate::Fixed(date) => {
+                write!(f, "{}-{:02}-{:02}", date.year, date.month, date.day)
+            }
+            ValidDate::Annual { month, day } => write!(f, "{month:02}-{day:02} each year"),
+        }
+    }
+}
+
+/// When a hint can be used, as set in `hints.toml`. A hint with annual dates whose
+/// `valid_until` is earlier in the year than its `valid_from` is valid over the new year, such as
+/// from `11-01` until `03-31` for winter operations.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(default)]
+pub struct Validity {
+    pub(crate) valid_from: Option<ValidDate>,
+    pub(crate) valid_until: Option<ValidDate>,
+}
+
+impl Validity {
+    pub(crate) fn is_valid(&self, date: SimDate) -> bool {
+        let after_from = !self
+            .valid_from
+            .is_some_and(|from| date < from.in_year(date.year));
+        let before_until = !self
+            .valid_until
+            .is_some_and(|until| date > until.in_year(date.year));
+        match (self.valid_from, self.valid_until) {
+            (
+                Some(ValidDate::Annual {
+                    month: from_month,
+                    day: from_day,
+                }),
+                Some(ValidDate::Annual {
+                    month: until_month,
+                    day: until_day,
+                }),
+            ) if (from_month, from_day) > (until_month, until_day) => after_from || before_until,
+            _ => after_from && before_until,
+        }
+    }
+
+    /// Describes when the hint is valid, such as `valid until 2024-02-22`.
+    pub(crate) fn describe(&self) -> String {
+        match (self.valid_from, self.valid_until) {
+            (Some(from), Some(until)) => format!("valid from {from} until {until}"),
+            (Some(from), None) => format!("valid from {from}"),
+            (None, Some(until)) => format!("valid until {until}"),
+            (None, None) => String::from("always valid"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SimDate, ValidDate, Validity};
+
+    fn date(year: i32, month: u32, day: u32) -> SimDate {
+        SimDate { year, month, day }
+    }
+
+    fn validity(from: Option<&str>, until: Option<&str>) -> Validity {
+        Validity {
+            valid_from: from.map(|from| ValidDate::try_from(from.to_string()).unwrap()),
+            valid_until: until.map(|until| ValidDate::try_from(until.to_string()).unwrap()),
+        }
+    }
+
+    #[test]
+    fn converts_day_of_year() {
+        assert_eq!(SimDate::from_day_of_year(2023, 0), Some(date(2023, 1, 1)));
+        assert_eq!(SimDate::from_day_of_year(2023, 59), Some(date(2023, 3, 1)));
+        assert_eq!(SimDate::from_day_of_year(2024, 59), Some(date(2024, 2, 29)));
+        assert_eq!(
+            SimDate::from_day_of_year(2024, 365),
+            Some(date(2024, 12, 31))
+        );
+        assert_eq!(SimDate::from_day_of_year(2023, 365), None);
+    }
+
+    #[test]
+    fn parses_dates() {
+        assert_eq!(
+            ValidDate::try_from(String::from("2024-02-22")),
+            Ok(ValidDate::Fixed(date(2024, 2, 22)))
+        );
+        assert_eq!(
+            ValidDate::try_from(String::from("02-29")),
+            Ok(ValidDate::Annual { month: 2, day: 29 })
+        );
+        for invalid in ["2023-02-29", "13-01", "2024-1", "today", ""] {
+            assert!(
+                ValidDate::try_from(invalid.to_string()).is_err(),
+                "{invalid}"
+            );
+        }
+    }
+
+    #[test]
+    fn checks_fixed_dates() {
+        let cycle = validity(Some("2024-01-25"), Some("2024-02-21"));
+        assert!(!cycle.is_valid(date(2024, 1, 24)));
+        assert!(cycle.is_valid(date(2024, 1, 25)));
+        assert!(cycle.is_valid(date(2024, 2, 21)));
+        assert!(!cycle.is_valid(date(2024, 2, 22)));
+        assert!(!cycle.is_valid(date(2025, 2, 1)));
+        assert!(validity(None, None).is_valid(date(2024, 1, 1)));
+    }
+
+    #[test]
+    fn checks_seasons() {
+        let summer = validity(Some("04-01"), Some("10-31"));
+        assert!(summer.is_valid(date(2023, 7, 1)));
+        assert!(!summer.is_valid(date(2024, 12, 1)));
+        let winter = validity(Some("11-01"), Some("03-31"));
+        assert!(winter.is_valid(date(2023, 12, 25)));
+        assert!(winter.is_valid(date(2024, 2, 1)));
+        assert!(!winter.is_valid(date(2024, 7, 1)));
+    }
+}
@@ -0,0 +1,153 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+//! Renders hints to images in software, without a window or graphics context, for the headless
+//! render mode of the standalone app.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use image::{Rgba, RgbaImage};
+use imgui::{Condition, Context, DrawCmd, DrawVert, TextureId, Ui};
+
+const FONT_TEXTURE: TextureId = TextureId::new(usize::MAX);
+const BACKGROUND: Rgba<u8> = Rgba([0, 0, 0, 255]);
+
+/// imgui has a single current context, so renders take turns.
+static CONTEXT_LOCK: Mutex<()> = Mutex::new(());
+
+/// Draws one frame with `f` into a window filling an image of `size`, using `textures` for the
+/// textures it refers to.
+pub(crate) fn render<F: FnOnce(&Ui)>(
+    size: [u32; 2],
+    textures: &HashMap<TextureId, &RgbaImage>,
+    f: F,
+) -> RgbaImage {
+    let _lock = CONTEXT_LOCK
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    let mut context = Context::create();
+    context.set_ini_filename(None);
+    #[allow(clippy::cast_precision_loss)]
+    let display_size = [size[0] as f32, size[1] as f32];
+    context.io_mut().display_size = display_size;
+    let font = {
+        let fonts = context.fonts();
+        let texture = fonts.build_rgba32_texture();
+        let font = RgbaImage::from_raw(texture.width, texture.height, texture.data.to_vec())
+            .expect("Font atlas is RGBA");
+        fonts.tex_id = FONT_TEXTURE;
+        font
+    };
+
+    let ui = context.new_frame();
+    ui.window("render test")
+        .position([0.0, 0.0], Condition::Always)
+        .size(display_size, Condition::Always)
+        .no_decoration()
+        .build(|| f(ui));
+    let draw_data = context.render();
+
+    let mut target = RgbaImage::from_pixel(size[0], size[1], BACKGROUND);
+    for draw_list in draw_data.draw_lists() {
+        let vertices = draw_list.vtx_buffer();
+        let indices = draw_list.idx_buffer();
+        for command in draw_list.commands() {
+            if let DrawCmd::Elements { count, cmd_params } = command {
+                let texture = if cmd_params.texture_id == FONT_TEXTURE {
+                    &font
+                } else {
+                    textures
+                        .get(&cmd_params.texture_id)
+                        .expect("Texture drawn has been given")
+                };
+                let indices = &indices[cmd_params.idx_offset..cmd_params.idx_offset + count];
+                for triangle in indices.chunks_exact(3) {
+                    let vertex =
+                        |i: usize| vertices[cmd_params.vtx_offset + usize::from(triangle[i])];
+                    rasterise(
+                        &mut target,
+                        [vertex(0), vertex(1), vertex(2)],
+                        texture,
+                        cmd_params.clip_rect,
+                    );
+                }
+            }
+        }
+    }
+    target
+}
+
+/// Draws a textured, coloured triangle, blending it over `target`, sampling the texture at each
+/// pixel centre inside both the triangle and `clip_rect`.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss
+)]
+fn rasterise(target: &mut RgbaImage, v: [DrawVert; 3], texture: &RgbaImage, clip_rect: [f32; 4]) {
+    let edge = |a: [f32; 2], b: [f32; 2], p: [f32; 2]| {
+        (b[0] - a[0]) * (p[1] - a[1]) - (b[1] - a[1]) * (p[0] - a[0])
+    };
+    let area = edge(v[0].pos, v[1].pos, v[2].pos);
+    if area == 0.0 {
+        return;
+    }
+    let xs = v.map(|v| v.pos[0]);
+    let ys = v.map(|v| v.pos[1]);
+    let min_x = xs
+        .into_iter()
+        .fold(f32::MAX, f32::min)
+        .max(clip_rect[0])
+        .max(0.0);
+    let min_y = ys
+        .into_iter()
+        .fold(f32::MAX, f32::min)
+        .max(clip_rect[1])
+        .max(0.0);
+    let max_x = xs
+        .into_iter()
+        .fold(f32::MIN, f32::max)
+        .min(clip_rect[2])
+        .min(target.width() as f32);
+    let max_y = ys
+        .into_iter()
+        .fold(f32::MIN, f32::max)
+        .min(clip_rect[3])
+        .min(target.height() as f32);
+    for y in min_y.floor() as u32..max_y.ceil() as u32 {
+        for x in min_x.floor() as u32..max_x.ceil() as u32 {
+            let p = [x as f32 + 0.5, y as f32 + 0.5];
+            let weights = [
+                edge(v[1].pos, v[2].pos, p) / area,
+                edge(v[2].pos, v[0].pos, p) / area,
+                edge(v[0].pos, v[1].pos, p) / area,
+            ];
+            if weights.iter().any(|&w| w < 0.0) {
+                continue;
+            }
+            let interpolate = |value: &dyn Fn(&DrawVert) -> f32| {
+                weights[0] * value(&v[0]) + weights[1] * value(&v[1]) + weights[2] * value(&v[2])
+            };
+            let u = interpolate(&|v| v.uv[0]);
+            let w = interpolate(&|v| v.uv[1]);
+            let texel = texture.get_pixel(
+                ((u * texture.width() as f32) as u32).min(texture.width() - 1),
+                ((w * texture.height() as f32) as u32).min(texture.height() - 1),
+            );
+            let source: [f32; 4] = std::array::from_fn(|channel| {
+                interpolate(&|v| f32::from(v.col[channel])) / 255.0 * f32::from(texel[channel])
+                    / 255.0
+            });
+            let pixel = target.get_pixel_mut(x, y);
+            for channel in 0..3 {
+                let blended = source[channel] * source[3]
+                    + f32::from(pixel[channel]) / 255.0 * (1.0 - source[3]);
+                pixel[channel] = (blended * 255.0).round() as u8;
+            }
+        }
+    }
+}
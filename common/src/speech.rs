@@ -0,0 +1,12 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+/// Speaks text hints aloud, for users who can't read the hints window. The X-Plane plugin uses
+/// X-Plane's text-to-speech; elsewhere there is none, and text can only be copied to the
+/// clipboard for a screen reader.
+pub trait Speaker {
+    fn speak(&self, text: &str);
+}
@@ -0,0 +1,189 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+use std::borrow::Cow;
+use std::fmt::Write;
+
+use tracing::error;
+
+use crate::error::HintsError;
+use crate::expression::Expression;
+
+const DATAREF_PREFIX: &str = "dataref";
+const EXPRESSION_PREFIX: &str = "expr";
+const MISSING_VALUE: &str = "?";
+/// The most decimal places a placeholder can ask for.
+const MAX_PLACES: usize = 10;
+
+/// Reads dataref values for templated text hints. The X-Plane plugin reads them from the sim;
+/// elsewhere there are no datarefs and placeholders are shown as `?`.
+pub trait DatarefReader {
+    /// Returns the value of the dataref `name`, or of element `index` if it is an array.
+    fn read(&self, name: &str, index: Option<usize>) -> Option<f64>;
+}
+
+/// A [`DatarefReader`] with no datarefs.
+pub struct NoDatarefs;
+
+impl DatarefReader for NoDatarefs {
+    fn read(&self, _name: &str, _index: Option<usize>) -> Option<f64> {
+        None
+    }
+}
+
+/// A [`DatarefReader`] with fixed values, for tests.
+#[cfg(test)]
+pub(crate) struct FixedDatarefs(Vec<((&'static str, Option<usize>), f64)>);
+
+#[cfg(test)]
+impl FixedDatarefs {
+    /// Datarefs with the given values, read without an index.
+    pub(crate) fn new<const N: usize>(values: [(&'static str, f64); N]) -> Self {
+        FixedDatarefs(values.map(|(name, value)| ((name, None), value)).to_vec())
+    }
+
+    /// Also gives element `index` of the array dataref `name` a value.
+    pub(crate) fn with_element(mut self, name: &'static str, index: usize, value: f64) -> Self {
+        self.0.push(((name, Some(index)), value));
+        self
+    }
+}
+
+#[cfg(test)]
+impl DatarefReader for FixedDatarefs {
+    fn read(&self, name: &str, index: Option<usize>) -> Option<f64> {
+        self.0
+            .iter()
+            .find(|((n, i), _)| *n == name && *i == index)
+            .map(|(_, value)| *value)
+    }
+}
+
+/// Text containing placeholders, which are replaced by their current value each time the text is
+/// rendered:
+///
+/// - `{dataref:<name>}` is the value of a dataref. An array element is written `<name>[<index>]`.
+/// - `{expr:<expression>}` is the value of an [`Expression`], for example
+///   `{expr:"sim/flightmodel/weight/m_total" / 1000 * 0.6 + 85}`.
+///
+/// The number of decimal places, up to 10, can be fixed by appending `:<places>` to either, for
+/// example `{dataref:sim/cockpit2/engine/indicators/N1_percent[0]:1}`.
+#[derive(Debug)]
+pub struct Template {
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug)]
+enum Segment {
+    Literal(String),
+    Value {
+        expression: Expression,
+        places: Option<usize>,
+    },
+}
+
+impl Template {
+    /// Parses `text`. Anything that is not a well-formed placeholder is kept as literal text,
+    /// except that an invalid expression is logged and replaced by its error so that it can be
+    /// seen in the hint.
+    #[must_use]
+    pub fn parse(text: &str) -> Self {
+        let mut segments = vec![];
+        let mut literal = String::new();
+        let mut rest = text;
+        while let Some(start) = rest.find('{') {
+            let after_brace = &rest[start + 1..];
+            let placeholder = after_brace
+                .find('}')
+                .and_then(|end| parse_placeholder(&after_brace[..end]).map(|p| (end, p)));
+            if let Some((end, placeholder)) = placeholder {
+                literal.push_str(&rest[..start]);
+                match placeholder {
+                    Ok(segment) => {
+                        if !literal.is_empty() {
+                            segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                        }
+                        segments.push(segment);
+                    }
+                    Err(e) => {
+                        error!(code = e.code(), "Invalid placeholder in text hint: {e}");
+                        write!(literal, "[Error {}: {e}]", e.code()).unwrap();
+                    }
+                }
+                rest = &after_brace[end + 1..];
+            } else {
+                literal.push_str(&rest[..=start]);
+                rest = after_brace;
+            }
+        }
+        literal.push_str(rest);
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+        Template { segments }
+    }
+
+    /// Returns the text with each placeholder replaced by the value read from `datarefs`.
+    pub fn render(&self, datarefs: &dyn DatarefReader) -> Cow<str> {
+        if let [Segment::Literal(text)] = self.segments.as_slice() {
+            return Cow::Borrowed(text);
+        }
+        let mut rendered = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(text) => rendered.push_str(text),
+                Segment::Value { expression, places } => match expression.evaluate(datarefs) {
+                    Some(value) => {
+                        let places = places.unwrap_or(if value.fract().abs() < f64::EPSILON {
+                            0
+                        } else {
+                            2
+                        });
+                        write!(rendered, "{value:.places$}").unwrap();
+                    }
+                    None => rendered.push_str(MISSING_VALUE),
+                },
+            }
+        }
+        Cow::Owned(rendered)
+    }
+}
+
+/// Parses the text between the braces of a placeholder, returning `None` if it is not a
+/// placeholder at all.
+fn parse_placeholder(placeholder: &str) -> Option<Result<Segment, HintsError>> {
+    let (kind, value) = placeholder.split_once(':')?;
+    let (value, places) = match value.rsplit_once(':') {
+        Some((value, places)) => (
+            value,
+            Some(
+                places
+                    .trim()
+                    .parse()
+                    .ok()
+                    .filter(|places| *places <= MAX_PLACES)?,
+            ),
+        ),
+        None => (value, None),
+    };
+    let expression = match kind {
+        DATAREF_PREFIX => Ok(parse_dataref(value)?),
+        EXPRESSION_PREFIX => Expression::parse(value).map_err(HintsError::from),
+        _ => return None,
+    };
+    Some(expression.map(|expression| Segment::Value { expression, places }))
+}
+
+fn parse_dataref(dataref: &str) -> Option<Expression> {
+    let (name, index) = match dataref.strip_suffix(']').and_then(|d| d.split_once('[')) {
+        Some((name, index)) => (name, Some(index.parse().ok()?)),
+        None => (dataref, None),
+    };
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        return None;
+    }
+    Some(Expression::dataref(name, index))
+}
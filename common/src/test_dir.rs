@@ -0,0 +1,65 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+//! Directories for tests that read and write files.
+
+use std::io::ErrorKind;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// An empty directory of a test's own, which is removed when it is dropped. Its name is unique to
+/// the test, so tests running at the same time, in this run or another, don't share files.
+pub(crate) struct TestDir(PathBuf);
+
+impl TestDir {
+    pub(crate) fn new() -> Self {
+        loop {
+            let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+            let dir = std::env::temp_dir().join(format!("hints-test-{}-{id}", std::process::id()));
+            match std::fs::create_dir(&dir) {
+                Ok(()) => return TestDir(dir),
+                // left by an earlier run whose process had the same id
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+                Err(e) => panic!("Unable to create {dir:?}: {e}"),
+            }
+        }
+    }
+
+    /// Creates empty files at each of `paths` in the directory, along with the folders they are
+    /// in.
+    pub(crate) fn touch(&self, paths: &[&str]) {
+        for path in paths {
+            let path = self.0.join(path);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent).unwrap();
+            }
+            std::fs::write(path, b"").unwrap();
+        }
+    }
+}
+
+impl Deref for TestDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for TestDir {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
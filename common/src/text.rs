@@ -0,0 +1,62 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+use std::path::Path;
+
+use imgui::Ui;
+
+use crate::error::HintsError;
+use crate::template::{DatarefReader, Template};
+use crate::view::HEADING_COLOUR;
+
+/// A text hint, read from a `.txt` or `.md` file. A small subset of Markdown is understood:
+/// lines starting with `#` are headings and lines starting with `-` or `*` are bullet points.
+/// The text may contain dataref placeholders, which are filled in each time it is drawn.
+#[derive(Debug)]
+pub struct Text {
+    template: Template,
+}
+
+impl Text {
+    pub(crate) fn load(path: &Path) -> Result<Self, HintsError> {
+        Ok(Text {
+            template: Template::parse(&std::fs::read_to_string(path)?),
+        })
+    }
+
+    pub fn draw(&self, ui: &Ui, datarefs: &dyn DatarefReader) {
+        draw_text(ui, &self.template.render(datarefs));
+    }
+
+    /// The text with placeholders filled in and heading markers removed, for screen readers and
+    /// text-to-speech.
+    pub(crate) fn plain_text(&self, datarefs: &dyn DatarefReader) -> String {
+        self.template
+            .render(datarefs)
+            .lines()
+            .map(|line| line.trim_start().trim_start_matches('#').trim_start())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+fn draw_text(ui: &Ui, text: &str) {
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            ui.spacing();
+        } else if trimmed.starts_with('#') {
+            ui.text_colored(HEADING_COLOUR, trimmed.trim_start_matches('#').trim_start());
+        } else if let Some(item) = trimmed
+            .strip_prefix("- ")
+            .or_else(|| trimmed.strip_prefix("* "))
+        {
+            ui.bullet_text(item);
+        } else {
+            ui.text_wrapped(line);
+        }
+    }
+}
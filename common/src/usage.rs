@@ -0,0 +1,264 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use imgui::Ui;
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+
+use crate::documented::TomlDocs;
+use crate::error::{read_toml_file, write_toml_file, HintsError};
+use crate::view::HEADING_COLOUR;
+
+const USAGE_DIRNAME: &str = "usage";
+const COUNTS_FILENAME: &str = "counts.toml";
+const COUNTS_DOCS: TomlDocs = TomlDocs {
+    header: "How often each command was used and each hint was turned to, across all flights.
+Delete this file to start counting again.",
+    fields: Counts::FIELD_DOCS,
+};
+
+/// A hint is no longer being viewed if the window has not been drawn for this long, for example
+/// because it was closed.
+const VIEWING_GAP: Duration = Duration::from_secs(2);
+
+/// A log of which hints were viewed during a flight and for how long, written as CSV and JSON
+/// to the `usage` directory when the flight ends so that it can be reviewed after the session.
+pub(crate) struct UsageLog {
+    dir: PathBuf,
+    started: SystemTime,
+    views: Vec<View>,
+    viewing: Option<Viewing>,
+}
+
+struct View {
+    hint: String,
+    started: SystemTime,
+    duration: Duration,
+}
+
+struct Viewing {
+    hint: String,
+    started: SystemTime,
+    first_drawn: Instant,
+    last_drawn: Instant,
+}
+
+impl UsageLog {
+    pub(crate) fn new(settings_dir: &Path) -> Self {
+        UsageLog {
+            dir: settings_dir.join(USAGE_DIRNAME),
+            started: SystemTime::now(),
+            views: vec![],
+            viewing: None,
+        }
+    }
+
+    /// Records that `hint` was drawn this frame, or that no hint was drawn if it is `None`.
+    pub(crate) fn drawn(&mut self, hint: Option<&str>) {
+        let now = Instant::now();
+        if let Some(viewing) = &mut self.viewing {
+            if Some(viewing.hint.as_str()) == hint && now - viewing.last_drawn < VIEWING_GAP {
+                viewing.last_drawn = now;
+                return;
+            }
+        }
+        self.finish_viewing();
+        self.viewing = hint.map(|hint| Viewing {
+            hint: hint.to_string(),
+            started: SystemTime::now(),
+            first_drawn: now,
+            last_drawn: now,
+        });
+    }
+
+    fn finish_viewing(&mut self) {
+        if let Some(viewing) = self.viewing.take() {
+            self.views.push(View {
+                hint: viewing.hint,
+                started: viewing.started,
+                duration: viewing.last_drawn - viewing.first_drawn,
+            });
+        }
+    }
+
+    fn save(&self) -> Result<PathBuf, HintsError> {
+        std::fs::create_dir_all(&self.dir)?;
+        let path = self
+            .dir
+            .join(format!("flight-{}", unix_seconds(self.started)));
+        std::fs::write(path.with_extension("csv"), self.to_csv())?;
+        std::fs::write(path.with_extension("json"), self.to_json())?;
+        Ok(path)
+    }
+
+    fn to_csv(&self) -> String {
+        let mut csv = String::from("hint,started,seconds\n");
+        for view in &self.views {
+            writeln!(
+                csv,
+                "\"{}\",{},{:.1}",
+                view.hint.replace('"', "\"\""),
+                unix_seconds(view.started),
+                view.duration.as_secs_f64()
+            )
+            .unwrap();
+        }
+        csv
+    }
+
+    fn to_json(&self) -> String {
+        let views: Vec<String> = self
+            .views
+            .iter()
+            .map(|view| {
+                format!(
+                    "    {{\"hint\": \"{}\", \"started\": {}, \"seconds\": {:.1}}}",
+                    escape_json(&view.hint),
+                    unix_seconds(view.started),
+                    view.duration.as_secs_f64()
+                )
+            })
+            .collect();
+        format!(
+            "{{\n  \"started\": {},\n  \"views\": [\n{}\n  ]\n}}\n",
+            unix_seconds(self.started),
+            views.join(",\n")
+        )
+    }
+}
+
+impl Drop for UsageLog {
+    fn drop(&mut self) {
+        self.finish_viewing();
+        if self.views.is_empty() {
+            return;
+        }
+        match self.save() {
+            Ok(path) => info!("Saved hint usage log to {path:?}"),
+            Err(e) => error!(code = e.code(), "Unable to save hint usage log: {e}"),
+        }
+    }
+}
+
+/// Times are written as seconds since the Unix epoch.
+fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+fn escape_json(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if c.is_control() => write!(escaped, "\\u{:04x}", u32::from(c)).unwrap(),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Running totals of how often each command was used and each hint was shown, kept across flights
+/// in `usage/counts.toml` so that users can see which bindings they actually use. Nothing is sent
+/// anywhere.
+pub(crate) struct UsageCounts {
+    filename: PathBuf,
+    counts: Counts,
+    last_hint: Option<String>,
+    changed: bool,
+}
+
+crate::documented_struct! {
+    #[derive(Debug, Default, Serialize, Deserialize)]
+    #[serde(default)]
+    struct Counts {
+        /// Number of times each command was used, by command name
+        commands: BTreeMap<String, u64>,
+        /// Number of times each hint was turned to, by filename
+        hints: BTreeMap<String, u64>,
+    }
+}
+
+impl UsageCounts {
+    pub(crate) fn load(settings_dir: &Path) -> Self {
+        let filename = settings_dir.join(USAGE_DIRNAME).join(COUNTS_FILENAME);
+        let counts = if filename.is_file() {
+            read_toml_file(&filename).unwrap_or_else(|e| {
+                error!(code = e.code(), "Unable to load usage counts: {e}");
+                Counts::default()
+            })
+        } else {
+            Counts::default()
+        };
+        UsageCounts {
+            filename,
+            counts,
+            last_hint: None,
+            changed: false,
+        }
+    }
+
+    pub(crate) fn command_used(&mut self, command: &str) {
+        *self.counts.commands.entry(command.to_string()).or_default() += 1;
+        self.changed = true;
+    }
+
+    /// Records that `hint` was drawn this frame, counting it once each time it is turned to.
+    pub(crate) fn drawn(&mut self, hint: Option<&str>) {
+        if self.last_hint.as_deref() == hint {
+            return;
+        }
+        self.last_hint = hint.map(str::to_string);
+        if let Some(hint) = hint {
+            *self.counts.hints.entry(hint.to_string()).or_default() += 1;
+            self.changed = true;
+        }
+    }
+
+    pub(crate) fn draw(&self, ui: &Ui) {
+        for (heading, counts) in [
+            ("Commands used", &self.counts.commands),
+            ("Hints shown", &self.counts.hints),
+        ] {
+            ui.text_colored(HEADING_COLOUR, heading);
+            if counts.is_empty() {
+                ui.text_disabled("None yet");
+            }
+            let mut counts: Vec<_> = counts.iter().collect();
+            counts.sort_by(|a, b| b.1.cmp(a.1));
+            for (name, count) in counts {
+                ui.text(format!("{count:>6}  {name}"));
+            }
+            ui.spacing();
+        }
+    }
+
+    fn save(&self) -> Result<(), HintsError> {
+        if let Some(dir) = self.filename.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        write_toml_file(&self.filename, &self.counts, &COUNTS_DOCS)
+    }
+}
+
+impl Drop for UsageCounts {
+    fn drop(&mut self) {
+        if !self.changed {
+            return;
+        }
+        match self.save() {
+            Ok(()) => info!("Saved usage counts to {:?}", self.filename),
+            Err(e) => error!(code = e.code(), "Unable to save usage counts: {e}"),
+        }
+    }
+}
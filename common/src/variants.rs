@@ -0,0 +1,147 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+//! Variants of an aircraft covered by one pack of hints, such as the `-700` and `-800` of an
+//! aircraft family or analog and glass cockpits. The pack lists the variants in groups, and each
+//! hint can list the variants it is for. The user chooses a variant from each group, which is
+//! remembered for the aircraft, and hints for other variants are hidden.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use imgui::Ui;
+use tracing::{error, info};
+
+use crate::documented::TomlDocs;
+use crate::error::{read_toml_file, write_toml_file};
+use crate::view::HEADING_COLOUR;
+
+const VARIANTS_FILENAME: &str = "variants.toml";
+const VARIANTS_DOCS: TomlDocs = TomlDocs {
+    header: "The variant of the aircraft chosen from each group of variants in the hints, keyed
+by the group. Hints for all variants of groups without an entry are shown.",
+    fields: &[],
+};
+/// The choice of every variant in a group, which shows the hints for all of them.
+const ALL_VARIANTS: &str = "All";
+
+/// Whether a hint for the variants `hint_variants` is shown, given the `groups` of variants in
+/// the hints and the variant `chosen` from each group. A hint is hidden only if it lists variants
+/// from a group and the chosen one isn't among them.
+pub(crate) fn is_shown(
+    hint_variants: &[String],
+    groups: &BTreeMap<String, Vec<String>>,
+    chosen: &BTreeMap<String, String>,
+) -> bool {
+    groups.iter().all(|(group, variants)| {
+        let choice = match chosen.get(group) {
+            Some(choice) => choice,
+            None => return true,
+        };
+        let listed: Vec<&String> = hint_variants
+            .iter()
+            .filter(|variant| variants.contains(variant))
+            .collect();
+        listed.is_empty() || listed.contains(&choice)
+    })
+}
+
+/// Draws a choice of variant for each of `groups`, updating `chosen`. Returns whether any choice
+/// changed.
+pub(crate) fn draw_variant_chooser(
+    ui: &Ui,
+    groups: &BTreeMap<String, Vec<String>>,
+    chosen: &mut BTreeMap<String, String>,
+) -> bool {
+    ui.text_colored(HEADING_COLOUR, "Aircraft variant");
+    if groups.is_empty() {
+        ui.text_disabled("These hints are the same for every variant");
+        return false;
+    }
+    let mut changed = false;
+    for (group, variants) in groups {
+        let items: Vec<&str> = std::iter::once(ALL_VARIANTS)
+            .chain(variants.iter().map(String::as_str))
+            .collect();
+        let mut selected = chosen
+            .get(group)
+            .and_then(|choice| variants.iter().position(|variant| variant == choice))
+            .map_or(0, |idx| idx + 1);
+        if ui.combo_simple_string(group, &mut selected, &items) {
+            match selected.checked_sub(1).and_then(|idx| variants.get(idx)) {
+                Some(variant) => chosen.insert(group.clone(), variant.clone()),
+                None => chosen.remove(group),
+            };
+            changed = true;
+        }
+    }
+    ui.separator();
+    changed
+}
+
+/// Loads the variants chosen for the aircraft whose settings are in `dir`.
+pub(crate) fn load_variants(dir: &Path) -> BTreeMap<String, String> {
+    let filename = dir.join(VARIANTS_FILENAME);
+    if !filename.is_file() {
+        return BTreeMap::new();
+    }
+    read_toml_file(&filename).unwrap_or_else(|e| {
+        error!(
+            code = e.code(),
+            "Unable to load aircraft variants from {filename:?}: {e}"
+        );
+        BTreeMap::new()
+    })
+}
+
+pub(crate) fn save_variants(dir: &Path, chosen: &BTreeMap<String, String>) {
+    let filename = dir.join(VARIANTS_FILENAME);
+    match write_toml_file(&filename, chosen, &VARIANTS_DOCS) {
+        Ok(()) => info!("Saved aircraft variants to {filename:?}"),
+        Err(e) => error!(code = e.code(), "Unable to save aircraft variants: {e}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| (*value).to_string()).collect()
+    }
+
+    fn groups() -> BTreeMap<String, Vec<String>> {
+        BTreeMap::from([
+            (String::from("model"), strings(&["-700", "-800"])),
+            (String::from("cockpit"), strings(&["analog", "glass"])),
+        ])
+    }
+
+    #[test]
+    fn hides_hints_for_other_variants() {
+        let chosen = BTreeMap::from([(String::from("model"), String::from("-800"))]);
+        assert!(is_shown(&strings(&[]), &groups(), &chosen));
+        assert!(is_shown(&strings(&["-800"]), &groups(), &chosen));
+        assert!(is_shown(&strings(&["-700", "-800"]), &groups(), &chosen));
+        assert!(!is_shown(&strings(&["-700"]), &groups(), &chosen));
+        // no cockpit has been chosen
+        assert!(is_shown(&strings(&["-800", "glass"]), &groups(), &chosen));
+    }
+
+    #[test]
+    fn shows_every_hint_until_a_variant_is_chosen() {
+        assert!(is_shown(&strings(&["-700"]), &groups(), &BTreeMap::new()));
+    }
+
+    #[test]
+    fn saved_variants_are_loaded() {
+        let dir = TestDir::new();
+        let chosen = BTreeMap::from([(String::from("model"), String::from("-800"))]);
+        save_variants(&dir, &chosen);
+        assert_eq!(load_variants(&dir), chosen);
+    }
+}
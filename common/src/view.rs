@@ -0,0 +1,470 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+use std::cell::Cell;
+
+use imgui::{Image, MouseButton, StyleColor, TextureId, Ui};
+
+const MAX_ZOOM: f32 = 8.0;
+const ZOOM_STEP: f32 = 1.25;
+
+const IMAGE_MENU_ID: &str = "image_menu";
+
+const MINIMAP_PROPORTION: f32 = 0.25;
+const MINIMAP_MAX_SIZE: f32 = 120.0;
+const MINIMAP_MARGIN: f32 = 4.0;
+const MINIMAP_BACKGROUND: [f32; 4] = [0.0, 0.0, 0.0, 0.6];
+const MINIMAP_FRAME: [f32; 4] = [1.0, 0.8, 0.0, 1.0];
+
+pub(crate) const HEADING_COLOUR: [f32; 4] = [1.0, 0.8, 0.3, 1.0];
+pub(crate) const WARNING_COLOUR: [f32; 4] = [1.0, 0.3, 0.3, 1.0];
+
+/// The part of a hint that is currently visible, as a zoom level relative to fit-to-window and
+/// the centre of the visible region in normalised (0.0 - 1.0) image coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    zoom: f32,
+    centre: [f32; 2],
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Viewport {
+            zoom: 1.0,
+            centre: [0.5, 0.5],
+        }
+    }
+}
+
+impl Viewport {
+    pub fn is_zoomed(&self) -> bool {
+        self.zoom > 1.0
+    }
+
+    pub fn zoom_in(&mut self) {
+        self.set_zoom(self.zoom * ZOOM_STEP);
+    }
+
+    pub fn zoom_out(&mut self) {
+        self.set_zoom(self.zoom / ZOOM_STEP);
+    }
+
+    pub fn reset(&mut self) {
+        *self = Viewport::default();
+    }
+
+    /// Zooms in to at least `zoom`, leaving the viewport as it is if it is zoomed in further.
+    pub(crate) fn magnify(&mut self, zoom: f32) {
+        if self.zoom < zoom {
+            self.set_zoom(zoom);
+        }
+    }
+
+    fn set_zoom(&mut self, zoom: f32) {
+        self.zoom = zoom.clamp(1.0, MAX_ZOOM);
+    }
+
+    /// Zooms in by `steps` zoom steps, or out if it is negative, keeping the part of the image at
+    /// `anchor` in the same place. `anchor` is a fraction (0.0 - 1.0) of the way across the
+    /// drawn region, and the sizes are as for [`Viewport::visible_uv`].
+    fn zoom_about(
+        &mut self,
+        steps: f32,
+        anchor: [f32; 2],
+        displayed_size: [f32; 2],
+        region: [f32; 2],
+    ) {
+        let (uv0, uv1) = self.visible_uv(displayed_size, region);
+        let old_zoom = self.zoom;
+        self.set_zoom(self.zoom * ZOOM_STEP.powf(steps));
+        for axis in 0..2 {
+            let target = uv0[axis] + anchor[axis] * (uv1[axis] - uv0[axis]);
+            let zoomed_size = displayed_size[axis] * self.zoom / old_zoom;
+            let extent = (region[axis] / zoomed_size).min(1.0);
+            self.centre[axis] = (target - anchor[axis] * extent + extent / 2.0)
+                .clamp(extent / 2.0, 1.0 - extent / 2.0);
+        }
+    }
+
+    /// Zooms so that the image is drawn at its actual size, given the scale at which it fits the
+    /// window. Images smaller than the window are left fitted to it.
+    fn actual_size(&mut self, fit_scale_factor: f32) {
+        self.set_zoom(1.0 / fit_scale_factor);
+    }
+
+    /// Moves the visible region by `delta` screen pixels, as when the image is dragged by
+    /// `-delta`. The sizes are as for [`Viewport::visible_uv`].
+    fn pan(&mut self, delta: [f32; 2], displayed_size: [f32; 2], region: [f32; 2]) {
+        for axis in 0..2 {
+            let half = (region[axis] / displayed_size[axis]).min(1.0) / 2.0;
+            self.centre[axis] =
+                (self.centre[axis] + delta[axis] / displayed_size[axis]).clamp(half, 1.0 - half);
+        }
+    }
+
+    /// Moves the visible region so that it is centred on `uv`, as far as the image edges allow.
+    pub fn centre_on(&mut self, uv: [f32; 2]) {
+        self.centre = uv;
+    }
+
+    /// Returns the top-left and bottom-right of the visible region in normalised image
+    /// coordinates, given the size of the image when drawn at the current zoom level and the size
+    /// of the region it is drawn into.
+    fn visible_uv(&self, displayed_size: [f32; 2], region: [f32; 2]) -> ([f32; 2], [f32; 2]) {
+        let mut uv0 = [0.0; 2];
+        let mut uv1 = [1.0; 2];
+        for axis in 0..2 {
+            let extent = (region[axis] / displayed_size[axis]).min(1.0);
+            let half = extent / 2.0;
+            let centre = self.centre[axis].clamp(half, 1.0 - half);
+            uv0[axis] = centre - half;
+            uv1[axis] = centre + half;
+        }
+        (uv0, uv1)
+    }
+}
+
+/// Draws the visible region of a hint, scaled to fit the window and then zoomed by `viewport`,
+/// followed by a minimap if the hint is zoomed in. Scrolling over the hint with `Ctrl` held zooms
+/// about the mouse cursor, dragging with the middle mouse button, or the left one while zoomed in,
+/// pans, and right-clicking opens a menu of zoom options. If `follow_pointer` is set, a zoomed-in
+/// hint pans to follow the mouse cursor like a screen magnifier. Returns whether the user chose to
+/// copy the hint from the menu.
+#[must_use]
+pub fn draw_image(
+    ui: &Ui,
+    texture_id: TextureId,
+    image_size: (u32, u32),
+    viewport: &Cell<Viewport>,
+    follow_pointer: bool,
+) -> bool {
+    let origin = ui.cursor_screen_pos();
+    let region = ui.content_region_avail();
+    let current = viewport.get();
+    let fit_scale_factor = get_scale_factor(image_size, region);
+    let displayed_size = displayed_size(image_size, current, region);
+    let (uv0, uv1) = current.visible_uv(displayed_size, region);
+    let drawn_size = [
+        displayed_size[0] * (uv1[0] - uv0[0]),
+        displayed_size[1] * (uv1[1] - uv0[1]),
+    ];
+    Image::new(texture_id, drawn_size)
+        .uv0(uv0)
+        .uv1(uv1)
+        .build(ui);
+
+    let io = ui.io();
+    let anchor = [
+        ((io.mouse_pos[0] - origin[0]) / drawn_size[0]).clamp(0.0, 1.0),
+        ((io.mouse_pos[1] - origin[1]) / drawn_size[1]).clamp(0.0, 1.0),
+    ];
+    if io.key_ctrl && io.mouse_wheel != 0.0 && ui.is_item_hovered() {
+        let mut zoomed = current;
+        zoomed.zoom_about(io.mouse_wheel, anchor, displayed_size, region);
+        viewport.set(zoomed);
+    }
+    if follow_pointer && current.is_zoomed() && ui.is_item_hovered() {
+        let mut followed = viewport.get();
+        followed.centre_on(anchor);
+        viewport.set(followed);
+    }
+    let dragging = ui.is_mouse_dragging(MouseButton::Middle)
+        || (current.is_zoomed() && ui.is_mouse_dragging(MouseButton::Left));
+    if ui.is_item_hovered() && dragging {
+        let mut panned = current;
+        panned.pan(
+            [-io.mouse_delta[0], -io.mouse_delta[1]],
+            displayed_size,
+            region,
+        );
+        viewport.set(panned);
+    }
+    if ui.is_item_clicked_with_button(MouseButton::Right) {
+        ui.open_popup(IMAGE_MENU_ID);
+    }
+    let mut copy = false;
+    ui.popup(IMAGE_MENU_ID, || {
+        let mut chosen = viewport.get();
+        if ui.menu_item("Fit to window") {
+            chosen.reset();
+        }
+        if ui.menu_item("Actual size") {
+            chosen.actual_size(fit_scale_factor);
+        }
+        if ui.menu_item("Zoom in") {
+            chosen.zoom_in();
+        }
+        if ui.menu_item("Zoom out") {
+            chosen.zoom_out();
+        }
+        viewport.set(chosen);
+        ui.separator();
+        copy = ui.menu_item("Copy to clipboard");
+    });
+
+    if current.is_zoomed() {
+        draw_minimap(
+            ui,
+            texture_id,
+            image_size,
+            (origin, region),
+            (uv0, uv1),
+            viewport,
+        );
+    }
+    copy
+}
+
+/// The size of an image of `image_size` when drawn into `region` at the zoom of `viewport`.
+fn displayed_size(image_size: (u32, u32), viewport: Viewport, region: [f32; 2]) -> [f32; 2] {
+    let scale_factor = get_scale_factor(image_size, region) * viewport.zoom;
+    #[allow(clippy::cast_precision_loss)]
+    [
+        image_size.0 as f32 * scale_factor,
+        image_size.1 as f32 * scale_factor,
+    ]
+}
+
+/// Where [`draw_image`] draws the point `uv` of an image, in normalised image coordinates, when
+/// drawing it into the region at `origin` of `region` size with `viewport`, or `None` if the
+/// point is not visible.
+pub(crate) fn image_to_screen(
+    uv: [f32; 2],
+    image_size: (u32, u32),
+    viewport: Viewport,
+    (origin, region): ([f32; 2], [f32; 2]),
+) -> Option<[f32; 2]> {
+    let displayed_size = displayed_size(image_size, viewport, region);
+    let (uv0, uv1) = viewport.visible_uv(displayed_size, region);
+    let visible = (0..2).all(|axis| (uv0[axis]..=uv1[axis]).contains(&uv[axis]));
+    visible.then(|| {
+        [
+            origin[0] + (uv[0] - uv0[0]) * displayed_size[0],
+            origin[1] + (uv[1] - uv0[1]) * displayed_size[1],
+        ]
+    })
+}
+
+/// The point of an image, in normalised image coordinates, that [`draw_image`] draws at the screen
+/// position `point`, as for [`image_to_screen`], or `None` if the image isn't drawn there.
+pub(crate) fn screen_to_image(
+    point: [f32; 2],
+    image_size: (u32, u32),
+    viewport: Viewport,
+    (origin, region): ([f32; 2], [f32; 2]),
+) -> Option<[f32; 2]> {
+    let displayed_size = displayed_size(image_size, viewport, region);
+    let (uv0, uv1) = viewport.visible_uv(displayed_size, region);
+    let uv = [
+        uv0[0] + (point[0] - origin[0]) / displayed_size[0],
+        uv0[1] + (point[1] - origin[1]) / displayed_size[1],
+    ];
+    let drawn = (0..2).all(|axis| (uv0[axis]..=uv1[axis]).contains(&uv[axis]));
+    drawn.then_some(uv)
+}
+
+/// The top left and bottom right, in normalised image coordinates, of a rectangle of an image of
+/// `image_size` given as the left, top, width and height in pixels, as set in `hints.toml`.
+pub(crate) fn pixel_rect_to_uv(rect: [f32; 4], image_size: (u32, u32)) -> ([f32; 2], [f32; 2]) {
+    #[allow(clippy::cast_precision_loss)]
+    let (width, height) = (image_size.0 as f32, image_size.1 as f32);
+    let [left, top, rect_width, rect_height] = rect;
+    (
+        [left / width, top / height],
+        [(left + rect_width) / width, (top + rect_height) / height],
+    )
+}
+
+/// Where [`draw_image`] draws the part of the rectangle from `top_left` to `bottom_right` of an
+/// image that is visible, as for [`image_to_screen`], or `None` if none of it is visible.
+pub(crate) fn image_rect_to_screen(
+    (top_left, bottom_right): ([f32; 2], [f32; 2]),
+    image_size: (u32, u32),
+    viewport: Viewport,
+    (origin, region): ([f32; 2], [f32; 2]),
+) -> Option<([f32; 2], [f32; 2])> {
+    let displayed_size = displayed_size(image_size, viewport, region);
+    let (uv0, uv1) = viewport.visible_uv(displayed_size, region);
+    let mut min = [0.0; 2];
+    let mut max = [0.0; 2];
+    for axis in 0..2 {
+        let start = top_left[axis].max(uv0[axis]);
+        let end = bottom_right[axis].min(uv1[axis]);
+        if start >= end {
+            return None;
+        }
+        min[axis] = origin[axis] + (start - uv0[axis]) * displayed_size[axis];
+        max[axis] = origin[axis] + (end - uv0[axis]) * displayed_size[axis];
+    }
+    Some((min, max))
+}
+
+/// Draws the whole hint in the top-right corner with a frame around the visible region. Clicking
+/// or dragging on the minimap moves the visible region.
+fn draw_minimap(
+    ui: &Ui,
+    texture_id: TextureId,
+    image_size: (u32, u32),
+    (origin, region): ([f32; 2], [f32; 2]),
+    (uv0, uv1): ([f32; 2], [f32; 2]),
+    viewport: &Cell<Viewport>,
+) {
+    let bounds = [
+        (region[0] * MINIMAP_PROPORTION).min(MINIMAP_MAX_SIZE),
+        (region[1] * MINIMAP_PROPORTION).min(MINIMAP_MAX_SIZE),
+    ];
+    let scale_factor = get_scale_factor(image_size, bounds);
+    #[allow(clippy::cast_precision_loss)]
+    let size = [
+        image_size.0 as f32 * scale_factor,
+        image_size.1 as f32 * scale_factor,
+    ];
+    let min = [
+        origin[0] + region[0].max(size[0]) - size[0] - MINIMAP_MARGIN,
+        origin[1] + MINIMAP_MARGIN,
+    ];
+    let max = [min[0] + size[0], min[1] + size[1]];
+    let to_screen = |uv: [f32; 2]| [min[0] + uv[0] * size[0], min[1] + uv[1] * size[1]];
+
+    {
+        let draw_list = ui.get_window_draw_list();
+        draw_list
+            .add_rect(min, max, MINIMAP_BACKGROUND)
+            .filled(true)
+            .build();
+        draw_list.add_image(texture_id, min, max).build();
+        draw_list
+            .add_rect(to_screen(uv0), to_screen(uv1), MINIMAP_FRAME)
+            .build();
+    }
+
+    ui.set_cursor_screen_pos(min);
+    ui.invisible_button("minimap", size);
+    if ui.is_item_active() {
+        let mouse_pos = ui.io().mouse_pos;
+        let mut current = viewport.get();
+        current.centre_on([
+            ((mouse_pos[0] - min[0]) / size[0]).clamp(0.0, 1.0),
+            ((mouse_pos[1] - min[1]) / size[1]).clamp(0.0, 1.0),
+        ]);
+        viewport.set(current);
+    }
+}
+
+/// Draws the title at the top of a text-based hint.
+pub(crate) fn draw_title(ui: &Ui, title: &str) {
+    ui.text_colored(HEADING_COLOUR, title);
+    ui.separator();
+}
+
+/// Draws the contents of the current window with `f` on a background of `colour`, leaving the
+/// style of other windows as it is.
+pub(crate) fn draw_with_background<F: FnOnce()>(ui: &Ui, colour: [f32; 4], f: F) {
+    let pos = ui.window_pos();
+    let size = ui.window_size();
+    // the window's own background was drawn when it began, so cover it
+    ui.get_window_draw_list()
+        .add_rect(pos, [pos[0] + size[0], pos[1] + size[1]], colour)
+        .filled(true)
+        .build();
+    let _background = ui.push_style_color(StyleColor::ChildBg, colour);
+    f();
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn get_scale_factor(image_size: (u32, u32), window_size: [f32; 2]) -> f32 {
+    let (width, height) = image_size;
+    let width_scale = window_size[0] / width as f32;
+    let height_scale = window_size[1] / height as f32;
+    width_scale.min(height_scale)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        get_scale_factor, image_rect_to_screen, image_to_screen, pixel_rect_to_uv, screen_to_image,
+        Viewport, MAX_ZOOM,
+    };
+
+    #[test]
+    fn magnify_only_zooms_in() {
+        let mut viewport = Viewport::default();
+        viewport.magnify(2.0);
+        assert!((viewport.zoom - 2.0).abs() < f32::EPSILON);
+        viewport.set_zoom(3.0);
+        viewport.magnify(2.0);
+        assert!((viewport.zoom - 3.0).abs() < f32::EPSILON);
+        viewport.magnify(100.0);
+        assert!((viewport.zoom - MAX_ZOOM).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn scale_factor_fits_the_limiting_dimension() {
+        assert!((get_scale_factor((200, 100), [100.0, 100.0]) - 0.5).abs() < f32::EPSILON);
+        assert!((get_scale_factor((100, 200), [100.0, 100.0]) - 0.5).abs() < f32::EPSILON);
+        assert!((get_scale_factor((50, 25), [200.0, 200.0]) - 4.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn image_points_are_mapped_to_the_screen() {
+        let region = ([10.0, 20.0], [200.0, 150.0]);
+        let fitted = Viewport::default();
+        assert_eq!(
+            image_to_screen([0.5, 0.5], (64, 32), fitted, region),
+            Some([110.0, 70.0])
+        );
+        let mut zoomed = Viewport::default();
+        zoomed.set_zoom(2.0);
+        zoomed.centre_on([0.0, 0.0]);
+        assert_eq!(
+            image_to_screen([0.25, 0.25], (64, 32), zoomed, region),
+            Some([110.0, 70.0])
+        );
+        assert_eq!(image_to_screen([0.75, 0.5], (64, 32), zoomed, region), None);
+    }
+
+    #[test]
+    fn image_rects_are_cropped_to_the_visible_region() {
+        let region = ([10.0, 20.0], [200.0, 150.0]);
+        let mut zoomed = Viewport::default();
+        zoomed.set_zoom(2.0);
+        zoomed.centre_on([0.0, 0.0]);
+        assert_eq!(
+            image_rect_to_screen(([0.25, 0.25], [0.75, 0.5]), (64, 32), zoomed, region),
+            Some(([110.0, 70.0], [210.0, 120.0]))
+        );
+        assert_eq!(
+            image_rect_to_screen(([0.5, 0.0], [0.75, 0.5]), (64, 32), zoomed, region),
+            None
+        );
+    }
+
+    #[test]
+    fn screen_points_are_mapped_to_the_image() {
+        let region = ([10.0, 20.0], [200.0, 150.0]);
+        let mut zoomed = Viewport::default();
+        zoomed.set_zoom(2.0);
+        zoomed.centre_on([0.0, 0.0]);
+        assert_eq!(
+            screen_to_image([110.0, 70.0], (64, 32), zoomed, region),
+            Some([0.25, 0.25])
+        );
+        assert_eq!(screen_to_image([5.0, 70.0], (64, 32), zoomed, region), None);
+        // below the image, which is fitted to the width of the window
+        assert_eq!(
+            screen_to_image([110.0, 160.0], (64, 32), Viewport::default(), region),
+            None
+        );
+    }
+
+    #[test]
+    fn pixel_rects_are_normalised() {
+        assert_eq!(
+            pixel_rect_to_uv([16.0, 8.0, 32.0, 16.0], (64, 32)),
+            ([0.25, 0.25], [0.75, 0.75])
+        );
+    }
+}
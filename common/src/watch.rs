@@ -0,0 +1,129 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+//! Watching the hints directory, so that hints are reloaded as soon as their author saves a
+//! change rather than when the reload command is triggered.
+
+use std::path::Path;
+
+use crate::config::CONFIG_FILENAME;
+use crate::error::HintsError;
+use crate::hints::is_hint_file;
+use crate::index::INDEX_FILENAME;
+
+/// How long the hints directory must go unchanged before reloading, so that an editor saving a
+/// file in several steps, or a pack being copied in, causes a single reload.
+#[cfg(feature = "watch")]
+const SETTLE_TIME: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Whether a change to `path` changes the hints shown. Settings that the app saves alongside the
+/// hints, such as adjustments, are ignored so that saving them doesn't reload the hints.
+#[cfg_attr(not(feature = "watch"), allow(dead_code))]
+fn is_relevant(path: &Path) -> bool {
+    let name = path.file_name().and_then(|name| name.to_str());
+    is_hint_file(path) || name == Some(CONFIG_FILENAME) || name == Some(INDEX_FILENAME)
+}
+
+#[cfg(feature = "watch")]
+pub(crate) struct Watcher {
+    /// Watching continues until this is dropped.
+    watcher: notify::RecommendedWatcher,
+    rx: std::sync::mpsc::Receiver<notify::Result<notify::Event>>,
+    last_change: Option<std::time::Instant>,
+}
+
+#[cfg(feature = "watch")]
+impl Watcher {
+    /// Watches `dir` and the language directories in it.
+    pub(crate) fn new(dir: &Path) -> Result<Self, HintsError> {
+        use notify::Watcher as _;
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        let mut watcher = notify::recommended_watcher(tx)?;
+        watcher.watch(dir, notify::RecursiveMode::Recursive)?;
+        tracing::info!("Watching {dir:?} for changes to hints");
+        Ok(Watcher {
+            watcher,
+            rx,
+            last_change: None,
+        })
+    }
+
+    /// Also watches `dir`, such as the library of hints kept outside the aircraft.
+    pub(crate) fn watch(&mut self, dir: &Path) -> Result<(), HintsError> {
+        use notify::Watcher as _;
+
+        self.watcher.watch(dir, notify::RecursiveMode::Recursive)?;
+        tracing::info!("Watching {dir:?} for changes to hints");
+        Ok(())
+    }
+
+    /// Whether the hints have changed since the last call, once they have stopped changing.
+    pub(crate) fn changed(&mut self) -> bool {
+        while let Ok(event) = self.rx.try_recv() {
+            match event {
+                Ok(event) if event.kind.is_access() => {}
+                Ok(event) => {
+                    if event.paths.iter().any(|path| is_relevant(path)) {
+                        tracing::debug!(paths = ?event.paths, "Hints changed");
+                        self.last_change = Some(std::time::Instant::now());
+                    }
+                }
+                Err(e) => {
+                    let e = HintsError::from(e);
+                    tracing::warn!(code = e.code(), "{e}");
+                }
+            }
+        }
+        match self.last_change {
+            Some(last_change) if last_change.elapsed() >= SETTLE_TIME => {
+                self.last_change = None;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(not(feature = "watch"))]
+pub(crate) struct Watcher;
+
+#[cfg(not(feature = "watch"))]
+impl Watcher {
+    pub(crate) fn new(_dir: &Path) -> Result<Self, HintsError> {
+        Err(HintsError::Watch(String::from(
+            "this build of FLC Hints doesn't include the watch feature",
+        )))
+    }
+
+    #[allow(clippy::unused_self, clippy::unnecessary_wraps)]
+    pub(crate) fn watch(&mut self, _dir: &Path) -> Result<(), HintsError> {
+        Ok(())
+    }
+
+    #[allow(clippy::unused_self)]
+    pub(crate) fn changed(&mut self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::is_relevant;
+
+    #[test]
+    fn only_hints_and_their_config_are_relevant() {
+        assert!(is_relevant(Path::new("/hints/preflight.png")));
+        assert!(is_relevant(Path::new("/hints/de/preflight.png")));
+        assert!(is_relevant(Path::new("/hints/hints.toml")));
+        assert!(is_relevant(Path::new("/hints/index.toml")));
+        assert!(!is_relevant(Path::new("/hints/adjustments.toml")));
+        assert!(!is_relevant(Path::new("/hints/usage/2023-10-01.csv")));
+        assert!(!is_relevant(Path::new("/hints/.preflight.png.swp")));
+    }
+}
@@ -0,0 +1,85 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+use std::ffi::c_char;
+
+use imgui::sys::{
+    igGetFont, igGetFontSize, igGetWindowDrawList, ImDrawList_AddText_FontPtr,
+    ImDrawList_PopClipRect, ImDrawList_PushClipRect, ImVec2,
+};
+use imgui::{ImColor32, Ui};
+
+/// The watermark drawn across hints that are out of date.
+pub(crate) const EXPIRED_WATERMARK: &str = "Expired";
+/// The colour of watermarks unless the hints config sets one: a translucent red.
+pub(crate) const DEFAULT_WATERMARK_COLOUR: [f32; 4] = [1.0, 0.2, 0.2, 0.3];
+/// How much of the diagonal of the hint the watermark spans.
+const DIAGONAL_FRACTION: f32 = 0.7;
+
+/// Draws `text` diagonally across the region of the current window at `origin` with `size`, from
+/// the bottom left to the top right, sized to span most of it.
+pub(crate) fn draw_watermark(
+    ui: &Ui,
+    text: &str,
+    (origin, size): ([f32; 2], [f32; 2]),
+    colour: [f32; 4],
+) {
+    let [width, height] = size;
+    let text_size = ui.calc_text_size(text);
+    if text.is_empty() || width <= 0.0 || height <= 0.0 || text_size[0] <= 0.0 {
+        return;
+    }
+    let diagonal = width.hypot(height);
+    let scale = diagonal * DIAGONAL_FRACTION / text_size[0];
+    let scaled = [text_size[0] * scale, text_size[1] * scale];
+    let centre = [origin[0] + width / 2.0, origin[1] + height / 2.0];
+    let (sin, cos) = (-height / diagonal, width / diagonal);
+    let [r, g, b, a] = colour;
+    let colour = ImColor32::from_rgba_f32s(r, g, b, a).to_bits();
+    // imgui can't draw rotated text, so the text is drawn level and then its vertices are rotated
+    // about the centre of the region. Glyphs outside the clip rectangle are not drawn at all, so
+    // it is widened while the level text is drawn.
+    unsafe {
+        let draw_list = igGetWindowDrawList();
+        let clip_min = ImVec2 {
+            x: origin[0].min(centre[0] - scaled[0] / 2.0),
+            y: origin[1],
+        };
+        let clip_max = ImVec2 {
+            x: (origin[0] + width).max(centre[0] + scaled[0] / 2.0),
+            y: origin[1] + height,
+        };
+        ImDrawList_PushClipRect(draw_list, clip_min, clip_max, false);
+        let first_vertex = (*draw_list).VtxBuffer.Size;
+        let text_start = text.as_ptr().cast::<c_char>();
+        ImDrawList_AddText_FontPtr(
+            draw_list,
+            igGetFont(),
+            igGetFontSize() * scale,
+            ImVec2 {
+                x: centre[0] - scaled[0] / 2.0,
+                y: centre[1] - scaled[1] / 2.0,
+            },
+            colour,
+            text_start,
+            text_start.add(text.len()),
+            0.0,
+            std::ptr::null(),
+        );
+        let buffer = &mut (*draw_list).VtxBuffer;
+        if let (Ok(first), Ok(end)) = (usize::try_from(first_vertex), usize::try_from(buffer.Size))
+        {
+            for idx in first..end {
+                let vertex = &mut *buffer.Data.add(idx);
+                let x = vertex.pos.x - centre[0];
+                let y = vertex.pos.y - centre[1];
+                vertex.pos.x = centre[0] + x * cos - y * sin;
+                vertex.pos.y = centre[1] + x * sin + y * cos;
+            }
+        }
+        ImDrawList_PopClipRect(draw_list);
+    }
+}
@@ -0,0 +1,234 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use imgui::Ui;
+use serde::Deserialize;
+
+use crate::error::HintsError;
+use crate::inputs::{load_inputs, read_calculator, save_inputs};
+use crate::template::DatarefReader;
+use crate::view::{draw_title, HEADING_COLOUR};
+
+const CRUISE_ALTITUDE_INPUT: &str = "Cruise altitude";
+const DEFAULT_CRUISE_ALTITUDE_FT: f32 = 5000.0;
+const FEET_PER_METRE: f64 = 3.280_84;
+const HPA_PER_INHG: f64 = 33.8639;
+
+const WIND_ALTITUDE_DATAREF: &str = "sim/weather/aircraft/wind_altitude_msl_m";
+const WIND_DIRECTION_DATAREF: &str = "sim/weather/aircraft/wind_direction_degt";
+const WIND_SPEED_DATAREF: &str = "sim/weather/aircraft/wind_speed_kts";
+const ELEVATION_DATAREF: &str = "sim/flightmodel/position/elevation";
+const HEIGHT_AGL_DATAREF: &str = "sim/flightmodel/position/y_agl";
+const QNH_DATAREF: &str = "sim/weather/aircraft/qnh_pas";
+const TEMPERATURE_DATAREF: &str = "sim/weather/aircraft/temperature_ambient_deg_c";
+const VISIBILITY_DATAREF: &str = "sim/weather/aircraft/visibility_reported_sm";
+
+/// The most wind layers read from the sim; X-Plane 12 has 13.
+const MAX_WIND_LAYERS: usize = 32;
+
+/// A briefing card summarising the sim's weather around the aircraft, read from a `.weather` file
+/// containing TOML such as:
+///
+/// ```toml
+/// title = "Departure weather"
+/// cruise_altitude = 8000.0
+/// ```
+///
+/// The weather is read when the card is first shown and whenever the user asks, rather than every
+/// frame, so that it can be read like a briefing. The cruise altitude, in feet, can be changed on
+/// the card and is saved like the inputs of calculator hints.
+#[derive(Debug)]
+pub struct Weather {
+    dir: PathBuf,
+    name: String,
+    title: Option<String>,
+    cruise_altitude_ft: Cell<f32>,
+    briefing: RefCell<Option<Briefing>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WeatherFile {
+    title: Option<String>,
+    cruise_altitude: Option<f32>,
+}
+
+#[derive(Debug)]
+struct Briefing {
+    surface_wind: Option<Wind>,
+    cruise_wind: Option<Wind>,
+    qnh_hpa: Option<f64>,
+    temperature_c: Option<f64>,
+    visibility_sm: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Wind {
+    direction: f64,
+    speed_kt: f64,
+}
+
+struct WindLayer {
+    altitude_m: f64,
+    wind: Wind,
+}
+
+impl Weather {
+    pub(crate) fn load(path: &Path, settings_dir: &Path) -> Result<Self, HintsError> {
+        let (text, name) = read_calculator(path)?;
+        Self::parse(&text, name, settings_dir)
+    }
+
+    pub(crate) fn parse(text: &str, name: String, settings_dir: &Path) -> Result<Self, HintsError> {
+        let file: WeatherFile = toml::from_str(text)?;
+        let dir = settings_dir.to_path_buf();
+        let cruise_altitude_ft = load_inputs(&dir, &name)
+            .get(CRUISE_ALTITUDE_INPUT)
+            .copied()
+            .or(file.cruise_altitude)
+            .unwrap_or(DEFAULT_CRUISE_ALTITUDE_FT);
+        Ok(Weather {
+            dir,
+            name,
+            title: file.title,
+            cruise_altitude_ft: Cell::new(cruise_altitude_ft),
+            briefing: RefCell::new(None),
+        })
+    }
+
+    pub fn draw(&self, ui: &Ui, datarefs: &dyn DatarefReader) {
+        if let Some(title) = &self.title {
+            draw_title(ui, title);
+        }
+        let mut refresh = self.briefing.borrow().is_none();
+        let mut cruise_altitude_ft = self.cruise_altitude_ft.get();
+        ui.input_float("Cruise altitude ft", &mut cruise_altitude_ft)
+            .step(1000.0)
+            .display_format("%.0f")
+            .build();
+        if ui.is_item_deactivated_after_edit() {
+            self.cruise_altitude_ft.set(cruise_altitude_ft);
+            let inputs = BTreeMap::from([(CRUISE_ALTITUDE_INPUT.to_string(), cruise_altitude_ft)]);
+            save_inputs(&self.dir, &self.name, inputs);
+            refresh = true;
+        }
+        refresh |= ui.button("Refresh");
+        if refresh {
+            self.refresh(datarefs);
+        }
+
+        ui.separator();
+        if let Some(briefing) = self.briefing.borrow().as_ref() {
+            briefing.draw(ui, self.cruise_altitude_ft.get());
+        }
+    }
+
+    /// Reads the briefing from the sim's current weather.
+    pub(crate) fn refresh(&self, datarefs: &dyn DatarefReader) {
+        self.briefing.replace(Some(Briefing::read(
+            datarefs,
+            f64::from(self.cruise_altitude_ft.get()) / FEET_PER_METRE,
+        )));
+    }
+}
+
+impl Briefing {
+    fn read(datarefs: &dyn DatarefReader, cruise_altitude_m: f64) -> Self {
+        let layers = read_wind_layers(datarefs);
+        let ground_m = datarefs
+            .read(ELEVATION_DATAREF, None)
+            .zip(datarefs.read(HEIGHT_AGL_DATAREF, None))
+            .map(|(elevation, height)| elevation - height);
+        Briefing {
+            surface_wind: ground_m.and_then(|ground_m| wind_at(&layers, ground_m)),
+            cruise_wind: wind_at(&layers, cruise_altitude_m),
+            qnh_hpa: datarefs
+                .read(QNH_DATAREF, None)
+                .map(|pascals| pascals / 100.0),
+            temperature_c: datarefs.read(TEMPERATURE_DATAREF, None),
+            visibility_sm: datarefs.read(VISIBILITY_DATAREF, None),
+        }
+    }
+
+    fn draw(&self, ui: &Ui, cruise_altitude_ft: f32) {
+        ui.text_colored(HEADING_COLOUR, "Winds");
+        ui.text(format!("Surface: {}", format_wind(self.surface_wind)));
+        ui.text(format!(
+            "At {cruise_altitude_ft:.0} ft: {}",
+            format_wind(self.cruise_wind)
+        ));
+        ui.spacing();
+        ui.text_colored(HEADING_COLOUR, "Conditions");
+        ui.text(match self.qnh_hpa {
+            Some(hpa) => format!("QNH: {hpa:.0} hPa / {:.2} inHg", hpa / HPA_PER_INHG),
+            None => String::from("QNH: ?"),
+        });
+        ui.text(match self.temperature_c {
+            Some(celsius) => format!("Temperature: {celsius:.0} C"),
+            None => String::from("Temperature: ?"),
+        });
+        ui.text(match self.visibility_sm {
+            Some(miles) => format!("Visibility: {miles:.0} sm"),
+            None => String::from("Visibility: ?"),
+        });
+    }
+}
+
+fn format_wind(wind: Option<Wind>) -> String {
+    match wind {
+        Some(wind) if wind.speed_kt < 0.5 => String::from("calm"),
+        Some(wind) => format!("{:03.0}° at {:.0} kt", wind.direction, wind.speed_kt),
+        None => String::from("?"),
+    }
+}
+
+/// Reads the sim's wind layers, lowest first.
+fn read_wind_layers(datarefs: &dyn DatarefReader) -> Vec<WindLayer> {
+    let mut layers: Vec<WindLayer> = (0..MAX_WIND_LAYERS)
+        .map_while(|idx| {
+            Some(WindLayer {
+                altitude_m: datarefs.read(WIND_ALTITUDE_DATAREF, Some(idx))?,
+                wind: Wind {
+                    direction: datarefs.read(WIND_DIRECTION_DATAREF, Some(idx))?,
+                    speed_kt: datarefs.read(WIND_SPEED_DATAREF, Some(idx))?,
+                },
+            })
+        })
+        .collect();
+    layers.sort_by(|a, b| a.altitude_m.total_cmp(&b.altitude_m));
+    layers
+}
+
+/// The wind at `altitude_m`, interpolated between the layers either side of it and taken from
+/// the lowest or highest layer outside them. Winds are interpolated as vectors so that, for
+/// example, 350° and 010° give 360° rather than 180°.
+fn wind_at(layers: &[WindLayer], altitude_m: f64) -> Option<Wind> {
+    let upper = layers.partition_point(|layer| layer.altitude_m < altitude_m);
+    if upper == 0 {
+        return layers.first().map(|layer| layer.wind);
+    }
+    if upper == layers.len() {
+        return layers.last().map(|layer| layer.wind);
+    }
+    let (below, above) = (&layers[upper - 1], &layers[upper]);
+    let fraction = (altitude_m - below.altitude_m) / (above.altitude_m - below.altitude_m);
+    let (below_x, below_y) = components(below.wind);
+    let (above_x, above_y) = components(above.wind);
+    let x = below_x + (above_x - below_x) * fraction;
+    let y = below_y + (above_y - below_y) * fraction;
+    Some(Wind {
+        direction: x.atan2(y).to_degrees().rem_euclid(360.0),
+        speed_kt: x.hypot(y),
+    })
+}
+
+fn components(wind: Wind) -> (f64, f64) {
+    let radians = wind.direction.to_radians();
+    (wind.speed_kt * radians.sin(), wind.speed_kt * radians.cos())
+}
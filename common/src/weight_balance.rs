@@ -0,0 +1,222 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+
+use imgui::Ui;
+use serde::Deserialize;
+
+use crate::error::HintsError;
+use crate::inputs::{load_inputs, read_calculator, save_inputs};
+use crate::view::{draw_title, WARNING_COLOUR};
+
+/// A weight and balance calculator, read from a `.wb` file containing TOML such as:
+///
+/// ```toml
+/// title = "Weight and balance"
+/// units = "kg"
+/// empty_weight = 767.0
+/// empty_arm = 1.0
+/// max_weight = 1111.0
+/// cg_limits = [0.89, 1.20]
+/// mac = { leading_edge = 0.79, length = 1.49 }
+///
+/// [[stations]]
+/// name = "Pilot"
+/// arm = 0.94
+/// default = 80.0
+/// max = 120.0
+/// ```
+///
+/// The user types the weight at each station, and the total weight, centre of gravity and, if the
+/// mean aerodynamic chord is given, the CG as a percentage of MAC are shown. Weights are saved so
+/// that they are kept between sessions.
+#[derive(Debug)]
+pub struct WeightAndBalance {
+    dir: PathBuf,
+    name: String,
+    file: WeightAndBalanceFile,
+    weights: RefCell<Vec<f32>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WeightAndBalanceFile {
+    title: Option<String>,
+    #[serde(default)]
+    units: String,
+    empty_weight: f32,
+    empty_arm: f32,
+    max_weight: Option<f32>,
+    cg_limits: Option<[f32; 2]>,
+    mac: Option<MeanAerodynamicChord>,
+    stations: Vec<Station>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MeanAerodynamicChord {
+    leading_edge: f32,
+    length: f32,
+}
+
+#[derive(Debug, Deserialize)]
+struct Station {
+    name: String,
+    arm: f32,
+    #[serde(default)]
+    default: f32,
+    max: Option<f32>,
+}
+
+impl WeightAndBalance {
+    pub(crate) fn load(path: &Path, settings_dir: &Path) -> Result<Self, HintsError> {
+        let (text, name) = read_calculator(path)?;
+        Self::parse(&text, name, settings_dir)
+    }
+
+    /// Parses the text of the `.wb` file `name`, with the values last typed into it in
+    /// `settings_dir`.
+    pub(crate) fn parse(text: &str, name: String, settings_dir: &Path) -> Result<Self, HintsError> {
+        let file: WeightAndBalanceFile = toml::from_str(text)?;
+        if let Some(mac) = &file.mac {
+            if mac.length <= 0.0 {
+                return Err(HintsError::Config(String::from(
+                    "The length of the mean aerodynamic chord must be greater than 0",
+                )));
+            }
+        }
+        if let Some(station) = file
+            .stations
+            .iter()
+            .find(|station| station.max.is_some_and(|max| max.is_nan() || max < 0.0))
+        {
+            return Err(HintsError::Config(format!(
+                "The maximum weight at {} must be 0 or more",
+                station.name
+            )));
+        }
+        let dir = settings_dir.to_path_buf();
+        let saved = load_inputs(&dir, &name);
+        let weights = file
+            .stations
+            .iter()
+            .map(|station| saved.get(&station.name).copied().unwrap_or(station.default))
+            .collect();
+        Ok(WeightAndBalance {
+            dir,
+            name,
+            file,
+            weights: RefCell::new(weights),
+        })
+    }
+
+    /// The total weight and the arm of the centre of gravity.
+    pub(crate) fn calculate(&self) -> (f32, f32) {
+        let weights = self.weights.borrow();
+        let (weight, moment) = self.file.stations.iter().zip(weights.iter()).fold(
+            (
+                self.file.empty_weight,
+                self.file.empty_weight * self.file.empty_arm,
+            ),
+            |(weight, moment), (station, station_weight)| {
+                (
+                    weight + station_weight,
+                    moment + station_weight * station.arm,
+                )
+            },
+        );
+        let cg = if weight > 0.0 { moment / weight } else { 0.0 };
+        (weight, cg)
+    }
+
+    fn save(&self) {
+        let weights = self.weights.borrow();
+        let values = self
+            .file
+            .stations
+            .iter()
+            .zip(weights.iter())
+            .map(|(station, weight)| (station.name.clone(), *weight))
+            .collect();
+        save_inputs(&self.dir, &self.name, values);
+    }
+
+    pub fn draw(&self, ui: &Ui) {
+        if let Some(title) = &self.file.title {
+            draw_title(ui, title);
+        }
+        let mut edited = false;
+        {
+            let mut weights = self.weights.borrow_mut();
+            for (idx, (station, weight)) in self
+                .file
+                .stations
+                .iter()
+                .zip(weights.iter_mut())
+                .enumerate()
+            {
+                ui.input_float(format!("{}##station{idx}", station.name), weight)
+                    .step(1.0)
+                    .display_format("%.1f")
+                    .build();
+                // max rather than clamp, which panics on NaN
+                *weight = weight.max(0.0).min(station.max.unwrap_or(f32::MAX));
+                edited |= ui.is_item_deactivated_after_edit();
+            }
+        }
+        if edited {
+            self.save();
+        }
+
+        ui.separator();
+        let (weight, cg) = self.calculate();
+        let units = &self.file.units;
+        let weight_text = format!("Total weight: {weight:.1} {units}");
+        if self.file.max_weight.is_some_and(|max| weight > max) {
+            ui.text_colored(WARNING_COLOUR, format!("{weight_text} (over maximum)"));
+        } else {
+            ui.text(weight_text);
+        }
+        let cg_text = format!("CG: {cg:.3}");
+        if self
+            .file
+            .cg_limits
+            .is_some_and(|[forward, aft]| cg < forward || cg > aft)
+        {
+            ui.text_colored(WARNING_COLOUR, format!("{cg_text} (out of limits)"));
+        } else {
+            ui.text(cg_text);
+        }
+        if let Some(mac) = &self.file.mac {
+            let percent_mac = (cg - mac.leading_edge) / mac.length * 100.0;
+            ui.text(format!("CG: {percent_mac:.1}% MAC"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_dir::TestDir;
+
+    use super::WeightAndBalance;
+
+    #[test]
+    fn rejects_invalid_station_maximum() {
+        let dir = TestDir::new();
+        for max in ["-1.0", "nan"] {
+            let path = dir.join("invalid.wb");
+            std::fs::write(
+                &path,
+                format!(
+                    "empty_weight = 767.0\nempty_arm = 1.0\n\n[[stations]]\nname = \"Pilot\"\n\
+                     arm = 0.94\nmax = {max}\n"
+                ),
+            )
+            .unwrap();
+            assert!(WeightAndBalance::load(&path, &dir).is_err());
+        }
+    }
+}
@@ -0,0 +1,28 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+use std::ffi::CString;
+
+use xplm_sys::{XPLMFindDataRef, XPLMFindPluginBySignature, XPLM_NO_PLUGIN_ID};
+
+use hints_common::AddOns;
+
+/// Detects add-ons by asking X-Plane for their plugins and datarefs. They are only checked when
+/// the hints are loaded, so an add-on whose plugin creates its datarefs later is only detected
+/// once the hints are reloaded.
+pub struct SimAddOns;
+
+impl AddOns for SimAddOns {
+    fn has_plugin(&self, signature: &str) -> bool {
+        CString::new(signature).is_ok_and(|signature| unsafe {
+            XPLMFindPluginBySignature(signature.as_ptr()) != XPLM_NO_PLUGIN_ID
+        })
+    }
+
+    fn has_dataref(&self, name: &str) -> bool {
+        CString::new(name).is_ok_and(|name| unsafe { !XPLMFindDataRef(name.as_ptr()).is_null() })
+    }
+}
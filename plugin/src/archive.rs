@@ -0,0 +1,106 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use tracing::{error, info, warn};
+
+use crate::settings::{read_toml, write_toml};
+use crate::utils::get_prefs_path;
+use crate::{get_save_directory, State, STATE_DOCS};
+
+const ARCHIVE_FILENAME: &str = "hints-export.toml";
+
+/// The saved state of every aircraft, keyed by the name of the state file it came from, so it can
+/// be copied to another machine as a single file.
+type Archive = BTreeMap<String, toml::Value>;
+
+fn get_archive_path() -> PathBuf {
+    get_prefs_path().join(ARCHIVE_FILENAME)
+}
+
+/// Writes the saved state of all aircraft to `hints-export.toml` in the X-Plane preferences
+/// directory.
+pub fn export_states() {
+    let save_dir = match get_save_directory() {
+        Some(save_dir) => save_dir,
+        None => return,
+    };
+    let archive_path = get_archive_path();
+    match read_states(&save_dir).and_then(|archive| {
+        let toml = toml::to_string_pretty(&archive).map_err(|e| e.to_string())?;
+        std::fs::write(&archive_path, toml).map_err(|e| e.to_string())?;
+        Ok(archive.len())
+    }) {
+        Ok(count) => info!("Exported hints state for {count} aircraft to {archive_path:?}"),
+        Err(e) => error!("Unable to export hints state to {archive_path:?}: {e}"),
+    }
+}
+
+/// Restores the saved state of all aircraft from `hints-export.toml` in the X-Plane preferences
+/// directory, replacing any existing state for the same aircraft. Returns whether any state was
+/// imported.
+pub fn import_states() -> bool {
+    let save_dir = match get_save_directory() {
+        Some(save_dir) => save_dir,
+        None => return false,
+    };
+    let archive_path = get_archive_path();
+    if !archive_path.is_file() {
+        warn!("Unable to find hints state to import at {archive_path:?}");
+        return false;
+    }
+    let archive: Archive = match read_toml(&archive_path) {
+        Some(archive) => archive,
+        None => return false,
+    };
+    let mut imported = 0;
+    for (name, state) in archive {
+        // names come from the archive, so reject any that would write outside the save directory
+        if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+            warn!("Ignoring invalid state name {name:?} in {archive_path:?}");
+            continue;
+        }
+        let filename = save_dir.join(format!("{name}.toml"));
+        if write_toml(&filename, &state, &STATE_DOCS) {
+            imported += 1;
+        }
+    }
+    info!("Imported hints state for {imported} aircraft from {archive_path:?}");
+    imported > 0
+}
+
+fn read_states(save_dir: &Path) -> Result<Archive, String> {
+    let mut archive = Archive::new();
+    for entry in std::fs::read_dir(save_dir).map_err(|e| e.to_string())? {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        if path.extension().is_some_and(|ext| ext == "toml") {
+            let name = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            match std::fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|toml| toml::from_str(&toml).map_err(|e| e.to_string()))
+            {
+                Ok(state) if is_window_state(&state) => {
+                    archive.insert(name, state);
+                }
+                Ok(_) => {}
+                Err(e) => warn!("Skipping unreadable state file {path:?}: {e}"),
+            }
+        }
+    }
+    Ok(archive)
+}
+
+/// Whether `value` is the saved state of an aircraft's window, or the layouts saved for its views,
+/// rather than one of the plugin's settings files kept in the same directory.
+fn is_window_state(value: &toml::Value) -> bool {
+    value.clone().try_into::<State>().is_ok()
+        || value.clone().try_into::<BTreeMap<String, State>>().is_ok()
+}
@@ -0,0 +1,292 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+#[cfg(any(feature = "charts", feature = "update"))]
+use std::io::Read;
+#[cfg(feature = "charts")]
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+#[cfg(feature = "charts")]
+use std::time::{Duration, SystemTime};
+
+#[cfg(feature = "charts")]
+use serde::Deserialize;
+#[cfg(feature = "charts")]
+use tracing::{debug, error, info, warn};
+
+#[cfg(feature = "charts")]
+use crate::settings::{read_settings, settings_path};
+use hints_common::ChartProvider;
+#[cfg(feature = "charts")]
+use hints_common::{Chart, HintsError};
+
+#[cfg(feature = "charts")]
+const CHARTS_CONFIG_FILENAME: &str = "charts.toml";
+#[cfg(feature = "charts")]
+const CACHE_DIRNAME: &str = "charts";
+#[cfg(feature = "charts")]
+const TIMEOUT: Duration = Duration::from_secs(20);
+/// Charts are downloaded again after one AIRAC cycle unless set otherwise.
+#[cfg(feature = "charts")]
+const DEFAULT_MAX_AGE_DAYS: u64 = 28;
+#[cfg(feature = "charts")]
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+/// Larger downloads are abandoned, as they are unlikely to be a chart.
+#[cfg(feature = "charts")]
+const MAX_CHART_BYTES: u64 = 32 * 1024 * 1024;
+#[cfg(feature = "charts")]
+const ICAO_PLACEHOLDER: &str = "{icao}";
+
+/// Downloads charts from URL templates, such as those of openflightmaps or a user's own chart
+/// collection, with `{icao}` replaced by the airport's identifier. Downloaded charts are cached in
+/// `Output/preferences/hints/charts`, and used instead of downloading them again until they
+/// expire, or for as long as they can't be downloaded again, so that they are available offline.
+///
+/// The source is only built with the `charts` feature, and only enabled when `charts.toml` in
+/// `Output/preferences/hints` lists at least one source.
+#[cfg(feature = "charts")]
+struct UrlChartSource {
+    agent: ureq::Agent,
+    sources: Vec<ChartSource>,
+    max_age: Duration,
+    cache_dir: PathBuf,
+}
+
+#[cfg(feature = "charts")]
+#[derive(Deserialize)]
+struct ChartsConfig {
+    #[serde(default = "default_max_age_days")]
+    max_age_days: u64,
+    sources: Vec<ChartSource>,
+}
+
+#[cfg(feature = "charts")]
+#[derive(Deserialize)]
+struct ChartSource {
+    name: String,
+    url: String,
+}
+
+#[cfg(feature = "charts")]
+fn default_max_age_days() -> u64 {
+    DEFAULT_MAX_AGE_DAYS
+}
+
+#[cfg(not(feature = "charts"))]
+pub fn load_chart_source() -> Option<Arc<dyn ChartProvider>> {
+    None
+}
+
+#[cfg(feature = "charts")]
+pub fn load_chart_source() -> Option<Arc<dyn ChartProvider>> {
+    let config: ChartsConfig = read_settings(CHARTS_CONFIG_FILENAME)?;
+    let sources: Vec<_> = config
+        .sources
+        .into_iter()
+        .filter(|source| {
+            let valid = (source.url.starts_with("https://") || source.url.starts_with("http://"))
+                && source.url.contains(ICAO_PLACEHOLDER);
+            if !valid {
+                error!(
+                    "Ignoring chart source {:?}, as its URL is not an HTTP URL containing {}",
+                    source.name, ICAO_PLACEHOLDER
+                );
+            }
+            valid
+        })
+        .collect();
+    if sources.is_empty() {
+        return None;
+    }
+    info!(count = sources.len(), "Loaded chart sources");
+    Some(Arc::new(UrlChartSource {
+        agent: ureq::AgentBuilder::new().timeout(TIMEOUT).build(),
+        sources,
+        max_age: Duration::from_secs(config.max_age_days.saturating_mul(SECONDS_PER_DAY)),
+        cache_dir: settings_path(CACHE_DIRNAME),
+    }))
+}
+
+#[cfg(feature = "charts")]
+impl ChartProvider for UrlChartSource {
+    fn name(&self) -> &str {
+        "URL templates"
+    }
+
+    fn charts(&self, ident: &str) -> Result<Vec<Chart>, HintsError> {
+        if !is_valid_ident(ident) {
+            return Err(HintsError::Charts(format!(
+                "{ident:?} is not an airport identifier"
+            )));
+        }
+        let ident = ident.to_ascii_uppercase();
+        Ok(self
+            .sources
+            .iter()
+            .filter_map(|source| {
+                let path = self
+                    .cache_dir
+                    .join(&ident)
+                    .join(cache_filename(&source.name));
+                self.chart(source, &ident, &path).map(|image| Chart {
+                    airport: ident.clone(),
+                    name: source.name.clone(),
+                    image,
+                })
+            })
+            .collect())
+    }
+}
+
+#[cfg(feature = "charts")]
+impl UrlChartSource {
+    /// Returns the chart from `source` for `ident`, from the cache at `path` if it hasn't expired
+    /// or can't be downloaded again.
+    fn chart(&self, source: &ChartSource, ident: &str, path: &Path) -> Option<Vec<u8>> {
+        let modified = std::fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok();
+        if modified.is_some_and(|modified| is_fresh(modified, SystemTime::now(), self.max_age)) {
+            debug!("Using cached chart {path:?}");
+            return read_cached(path);
+        }
+        let url = expand_url(&source.url, ident);
+        match download(&self.agent, &url) {
+            Ok(image) => {
+                if let Err(e) = write_cached(path, &image) {
+                    warn!("Unable to cache chart in {path:?}: {e}");
+                }
+                Some(image)
+            }
+            Err(e) if modified.is_some() => {
+                warn!("Unable to download {url}, using expired cached chart: {e}");
+                read_cached(path)
+            }
+            Err(e) => {
+                error!("Unable to download {url}: {e}");
+                None
+            }
+        }
+    }
+}
+
+#[cfg(feature = "charts")]
+fn download(agent: &ureq::Agent, url: &str) -> Result<Vec<u8>, String> {
+    let response = agent.get(url).call().map_err(|e| e.to_string())?;
+    read_limited(response.into_reader(), MAX_CHART_BYTES)
+}
+
+/// Reads all of `reader`, or returns an error if it has more than `limit` bytes, rather than
+/// returning the start of it as if it were the whole.
+#[cfg(any(feature = "charts", feature = "update"))]
+pub(crate) fn read_limited(reader: impl Read, limit: u64) -> Result<Vec<u8>, String> {
+    let mut body = vec![];
+    reader
+        .take(limit + 1)
+        .read_to_end(&mut body)
+        .map_err(|e| e.to_string())?;
+    if body.len() as u64 > limit {
+        return Err(format!("the download is larger than {limit} bytes"));
+    }
+    Ok(body)
+}
+
+/// Caches `image` at `path`, writing it to a temporary file first so that a chart that is only
+/// partly written, such as when X-Plane quits, is never used.
+#[cfg(feature = "charts")]
+fn write_cached(path: &Path, image: &[u8]) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let temporary = path.with_extension("partial");
+    std::fs::write(&temporary, image)?;
+    std::fs::rename(&temporary, path)
+}
+
+#[cfg(feature = "charts")]
+fn read_cached(path: &Path) -> Option<Vec<u8>> {
+    match std::fs::read(path) {
+        Ok(image) => Some(image),
+        Err(e) => {
+            error!("Unable to read cached chart {path:?}: {e}");
+            None
+        }
+    }
+}
+
+/// Whether `ident` can be put into a URL and a filename as it is.
+#[cfg(feature = "charts")]
+fn is_valid_ident(ident: &str) -> bool {
+    (3..=8).contains(&ident.len()) && ident.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+#[cfg(feature = "charts")]
+fn expand_url(template: &str, ident: &str) -> String {
+    template.replace(ICAO_PLACEHOLDER, ident)
+}
+
+/// The name of the file a source's charts are cached in, made safe for any file system.
+#[cfg(feature = "charts")]
+fn cache_filename(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+/// Whether a chart cached at `modified` is still to be used at `now`. A chart that appears to be
+/// from the future, because the clock has changed, is used.
+#[cfg(feature = "charts")]
+fn is_fresh(modified: SystemTime, now: SystemTime, max_age: Duration) -> bool {
+    match now.duration_since(modified) {
+        Ok(age) => age < max_age,
+        Err(_) => true,
+    }
+}
+
+#[cfg(all(test, feature = "mock-xplm", feature = "charts"))]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use super::{cache_filename, expand_url, is_fresh, is_valid_ident, read_limited};
+
+    #[test]
+    fn expands_url_templates() {
+        assert_eq!(
+            expand_url("https://example.com/{icao}/{icao}.png", "EGLL"),
+            "https://example.com/EGLL/EGLL.png"
+        );
+    }
+
+    #[test]
+    fn only_accepts_plain_identifiers() {
+        assert!(is_valid_ident("EGLL"));
+        assert!(is_valid_ident("K1G4"));
+        assert!(!is_valid_ident("EG"));
+        assert!(!is_valid_ident("../EGLL"));
+        assert!(!is_valid_ident("EGLL?x=1"));
+    }
+
+    #[test]
+    fn makes_safe_cache_filenames() {
+        assert_eq!(cache_filename("Aerodrome chart"), "Aerodrome_chart");
+        assert_eq!(cache_filename("../VAC"), "___VAC");
+    }
+
+    #[test]
+    fn rejects_downloads_over_the_limit() {
+        assert_eq!(read_limited(&b"chart"[..], 5), Ok(b"chart".to_vec()));
+        assert!(read_limited(&b"chart!"[..], 5).is_err());
+    }
+
+    #[test]
+    fn expires_cached_charts() {
+        let now = SystemTime::now();
+        let day = Duration::from_secs(24 * 60 * 60);
+        assert!(is_fresh(now - day, now, day * 28));
+        assert!(!is_fresh(now - day * 28, now, day * 28));
+        assert!(is_fresh(now + day, now, day * 28));
+    }
+}
@@ -0,0 +1,586 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+use std::time::{Duration, Instant};
+
+use imgui_support::geometry::Rect;
+use tracing::{debug, info, warn};
+use xplm::command::{CommandHandler, OwnedCommand};
+use xplm::data::owned::OwnedData;
+use xplm::data::{ReadOnly, StringReadWrite};
+
+use hints_common::{catch_panic, log_event_history, record_event, HintsError, HintsEvent};
+
+use crate::archive::export_states;
+use crate::paging::PagingAcceleration;
+use crate::utils::get_current_aircraft_path;
+use crate::{guard, import_and_load, CurrentInternals, SystemWrapper};
+
+/// How long a repeating command must be held before it first repeats.
+const REPEAT_DELAY: Duration = Duration::from_millis(400);
+/// The time between the first and second repeats, which then shrinks by [`REPEAT_ACCELERATION`]
+/// with each repeat down to [`MIN_REPEAT_INTERVAL`].
+const FIRST_REPEAT_INTERVAL: Duration = Duration::from_millis(200);
+const MIN_REPEAT_INTERVAL: Duration = Duration::from_millis(50);
+const REPEAT_ACCELERATION: f32 = 0.8;
+/// How far the window nudge commands move or resize the window, in pixels.
+const NUDGE_PIXELS: i32 = 10;
+/// The smallest the window can be made by the resize commands, in pixels.
+const MIN_NUDGED_SIZE: i32 = 100;
+
+/// What a command does when it is triggered.
+#[derive(Debug, Clone, Copy)]
+pub enum CommandAction {
+    Event(HintsEvent),
+    ToggleWindow,
+    LoadWindow,
+    SaveWindow,
+    SaveWindowForView,
+    ForgetWindowForView,
+    ResetWindow,
+    NudgeWindow(Nudge),
+    ExportState,
+    ImportState,
+    DumpHistory,
+    Diagnose,
+    /// Shows the peek hint for as long as the command is held.
+    Peek,
+}
+
+impl CommandAction {
+    /// Whether holding the command repeats it, so that holding a button scrubs through the hints.
+    fn repeats(self) -> bool {
+        matches!(
+            self,
+            CommandAction::Event(
+                HintsEvent::NextHint
+                    | HintsEvent::PreviousHint
+                    | HintsEvent::NextTenHints
+                    | HintsEvent::PreviousTenHints
+            ) | CommandAction::NudgeWindow(_)
+        )
+    }
+}
+
+/// A small move or resize of the hints window, so that cockpit builders can place it precisely.
+#[derive(Debug, Clone, Copy)]
+pub enum Nudge {
+    /// Moves the window right and up by the given number of pixels.
+    Move(i32, i32),
+    /// Makes the window wider and taller by the given number of pixels, keeping its top left
+    /// corner where it is.
+    Resize(i32, i32),
+}
+
+impl Nudge {
+    /// The geometry of a window at `geometry` once nudged.
+    pub fn apply(self, geometry: &Rect) -> Rect {
+        match self {
+            Nudge::Move(right, up) => Rect {
+                left: geometry.left.saturating_add(right),
+                top: geometry.top.saturating_add(up),
+                right: geometry.right.saturating_add(right),
+                bottom: geometry.bottom.saturating_add(up),
+            },
+            Nudge::Resize(wider, taller) => Rect {
+                right: geometry
+                    .right
+                    .saturating_add(wider)
+                    .max(geometry.left.saturating_add(MIN_NUDGED_SIZE)),
+                bottom: geometry
+                    .bottom
+                    .saturating_sub(taller)
+                    .min(geometry.top.saturating_sub(MIN_NUDGED_SIZE)),
+                ..geometry.clone()
+            },
+        }
+    }
+}
+
+/// A command that can be bound to a key or joystick button in X-Plane.
+#[derive(Debug)]
+pub struct Command {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub action: CommandAction,
+}
+
+/// Every command the plugin provides, in the order they are created. Adding an entry here is all
+/// that is needed to add a command.
+pub const COMMANDS: &[Command] = &[
+    Command {
+        name: "flc/hints/next",
+        description: "Show next hint",
+        action: CommandAction::Event(HintsEvent::NextHint),
+    },
+    Command {
+        name: "flc/hints/previous",
+        description: "Show previous hint",
+        action: CommandAction::Event(HintsEvent::PreviousHint),
+    },
+    Command {
+        name: "flc/hints/next_10",
+        description: "Show the hint 10 after this one",
+        action: CommandAction::Event(HintsEvent::NextTenHints),
+    },
+    Command {
+        name: "flc/hints/previous_10",
+        description: "Show the hint 10 before this one",
+        action: CommandAction::Event(HintsEvent::PreviousTenHints),
+    },
+    Command {
+        name: "flc/hints/reload",
+        description: "Reload hints from disk",
+        action: CommandAction::Event(HintsEvent::Reload),
+    },
+    Command {
+        name: "flc/hints/zoom/in",
+        description: "Zoom in on the current hint",
+        action: CommandAction::Event(HintsEvent::ZoomIn),
+    },
+    Command {
+        name: "flc/hints/zoom/out",
+        description: "Zoom out of the current hint",
+        action: CommandAction::Event(HintsEvent::ZoomOut),
+    },
+    Command {
+        name: "flc/hints/zoom/reset",
+        description: "Fit the current hint to the window",
+        action: CommandAction::Event(HintsEvent::ResetZoom),
+    },
+    Command {
+        name: "flc/hints/compare",
+        description: "Toggle comparing the current hint side-by-side with another",
+        action: CommandAction::Event(HintsEvent::ToggleCompare),
+    },
+    Command {
+        name: "flc/hints/adjustments",
+        description: "Toggle brightness, contrast and gamma controls",
+        action: CommandAction::Event(HintsEvent::ToggleAdjustments),
+    },
+    Command {
+        name: "flc/hints/conversions",
+        description: "Toggle the unit conversions panel",
+        action: CommandAction::Event(HintsEvent::ToggleConversions),
+    },
+    Command {
+        name: "flc/hints/navdata",
+        description: "Toggle the airport navigation data panel",
+        action: CommandAction::Event(HintsEvent::ToggleNavPanel),
+    },
+    Command {
+        name: "flc/hints/about",
+        description: "Toggle the page about the hint pack",
+        action: CommandAction::Event(HintsEvent::ToggleAbout),
+    },
+    Command {
+        name: "flc/hints/peek",
+        description: "Show the pack's quick-reference hint while held",
+        action: CommandAction::Peek,
+    },
+    Command {
+        name: "flc/hints/overview",
+        description: "Toggle a grid of thumbnails of every hint",
+        action: CommandAction::Event(HintsEvent::ToggleOverview),
+    },
+    Command {
+        name: "flc/hints/mini",
+        description: "Toggle shrinking the window to a thumbnail of the current hint",
+        action: CommandAction::Event(HintsEvent::ToggleMiniMode),
+    },
+    Command {
+        name: "flc/hints/next_step",
+        description: "Start or move on to the next step of the current hint's guided flow",
+        action: CommandAction::Event(HintsEvent::NextFlowStep),
+    },
+    Command {
+        name: "flc/hints/variants",
+        description: "Toggle choosing the variant of the aircraft to show hints for",
+        action: CommandAction::Event(HintsEvent::ToggleVariants),
+    },
+    Command {
+        name: "flc/hints/display_mode",
+        description: "Cycle between normal, grayscale and red-light display",
+        action: CommandAction::Event(HintsEvent::NextDisplayMode),
+    },
+    Command {
+        name: "flc/hints/accessibility",
+        description: "Toggle the large cursor, magnifier and high-contrast mode",
+        action: CommandAction::Event(HintsEvent::ToggleAccessibility),
+    },
+    Command {
+        name: "flc/hints/copy",
+        description: "Copy the current hint to the clipboard",
+        action: CommandAction::Event(HintsEvent::CopyHint),
+    },
+    Command {
+        name: "flc/hints/text/copy",
+        description: "Copy the text of the current text or checklist hint to the clipboard",
+        action: CommandAction::Event(HintsEvent::CopyText),
+    },
+    Command {
+        name: "flc/hints/text/speak",
+        description: "Speak the text of the current text or checklist hint",
+        action: CommandAction::Event(HintsEvent::SpeakText),
+    },
+    Command {
+        name: "flc/hints/charts/refresh",
+        description: "Show charts for the airports in the flight plan",
+        action: CommandAction::Event(HintsEvent::RefreshCharts),
+    },
+    Command {
+        name: "flc/hints/window/toggle",
+        description: "Toggle window visibility",
+        action: CommandAction::ToggleWindow,
+    },
+    Command {
+        name: "flc/hints/window/move_left_10px",
+        description: "Move the window 10 pixels left",
+        action: CommandAction::NudgeWindow(Nudge::Move(-NUDGE_PIXELS, 0)),
+    },
+    Command {
+        name: "flc/hints/window/move_right_10px",
+        description: "Move the window 10 pixels right",
+        action: CommandAction::NudgeWindow(Nudge::Move(NUDGE_PIXELS, 0)),
+    },
+    Command {
+        name: "flc/hints/window/move_up_10px",
+        description: "Move the window 10 pixels up",
+        action: CommandAction::NudgeWindow(Nudge::Move(0, NUDGE_PIXELS)),
+    },
+    Command {
+        name: "flc/hints/window/move_down_10px",
+        description: "Move the window 10 pixels down",
+        action: CommandAction::NudgeWindow(Nudge::Move(0, -NUDGE_PIXELS)),
+    },
+    Command {
+        name: "flc/hints/window/wider_10px",
+        description: "Make the window 10 pixels wider",
+        action: CommandAction::NudgeWindow(Nudge::Resize(NUDGE_PIXELS, 0)),
+    },
+    Command {
+        name: "flc/hints/window/narrower_10px",
+        description: "Make the window 10 pixels narrower",
+        action: CommandAction::NudgeWindow(Nudge::Resize(-NUDGE_PIXELS, 0)),
+    },
+    Command {
+        name: "flc/hints/window/taller_10px",
+        description: "Make the window 10 pixels taller",
+        action: CommandAction::NudgeWindow(Nudge::Resize(0, NUDGE_PIXELS)),
+    },
+    Command {
+        name: "flc/hints/window/shorter_10px",
+        description: "Make the window 10 pixels shorter",
+        action: CommandAction::NudgeWindow(Nudge::Resize(0, -NUDGE_PIXELS)),
+    },
+    Command {
+        name: "flc/hints/window/load",
+        description: "Load window position",
+        action: CommandAction::LoadWindow,
+    },
+    Command {
+        name: "flc/hints/window/save",
+        description: "Save window position",
+        action: CommandAction::SaveWindow,
+    },
+    Command {
+        name: "flc/hints/window/save_for_view",
+        description: "Save window position for the current view",
+        action: CommandAction::SaveWindowForView,
+    },
+    Command {
+        name: "flc/hints/window/forget_for_view",
+        description: "Forget the window position saved for the current view",
+        action: CommandAction::ForgetWindowForView,
+    },
+    Command {
+        name: "flc/hints/window/reset",
+        description: "Reset window position",
+        action: CommandAction::ResetWindow,
+    },
+    Command {
+        name: "flc/hints/state/export",
+        description: "Export saved window positions for all aircraft",
+        action: CommandAction::ExportState,
+    },
+    Command {
+        name: "flc/hints/state/import",
+        description: "Import saved window positions for all aircraft",
+        action: CommandAction::ImportState,
+    },
+    Command {
+        name: "flc/hints/diagnose",
+        description: "Check the hints and write a summary to the log",
+        action: CommandAction::Diagnose,
+    },
+    Command {
+        name: "flc/hints/debug/dump_history",
+        description: "Write recent hints events and commands to the log",
+        action: CommandAction::DumpHistory,
+    },
+];
+
+/// Creates every command in [`COMMANDS`].
+pub fn create_commands(internals: &CurrentInternals) -> Vec<OwnedCommand> {
+    let acceleration = PagingAcceleration::load();
+    COMMANDS
+        .iter()
+        .map(|command| {
+            let handler = ActionCommandHandler {
+                command,
+                internals: internals.clone(),
+                repeater: None,
+                acceleration,
+                last_began: None,
+            };
+            OwnedCommand::new(command.name, command.description, handler)
+                .unwrap_or_else(|_| panic!("Unable to create command '{}'", command.name))
+        })
+        .collect()
+}
+
+/// Publishes every command in [`COMMANDS`] as the string dataref `flc/hints/commands`, so that
+/// other plugins, such as a cockpit builder's binding tool, can list them.
+pub fn publish_commands() -> OwnedData<[u8], ReadOnly> {
+    let mut dataref =
+        OwnedData::create("flc/hints/commands").expect("Unable to create commands dataref");
+    if let Err(e) = dataref.set_as_string(&command_list()) {
+        warn!("Unable to publish the commands: {e:?}");
+    }
+    dataref
+}
+
+/// Each command's name and description, separated by a tab, one command per line.
+fn command_list() -> String {
+    COMMANDS
+        .iter()
+        .map(|command| format!("{}\t{}\n", command.name, command.description))
+        .collect()
+}
+
+struct ActionCommandHandler {
+    command: &'static Command,
+    internals: CurrentInternals,
+    repeater: Option<Repeater>,
+    acceleration: PagingAcceleration,
+    /// When the command last began, to accelerate paging while an encoder is spun quickly.
+    last_began: Option<Instant>,
+}
+
+/// When a held command next repeats, repeating faster the longer it is held.
+#[derive(Debug)]
+struct Repeater {
+    next: Instant,
+    interval: Duration,
+}
+
+impl Repeater {
+    fn new(began: Instant) -> Self {
+        Repeater {
+            next: began + REPEAT_DELAY,
+            interval: FIRST_REPEAT_INTERVAL,
+        }
+    }
+
+    /// Whether the command should repeat at `now`, scheduling the following repeat if so.
+    fn due(&mut self, now: Instant) -> bool {
+        if now < self.next {
+            return false;
+        }
+        self.next = now + self.interval;
+        self.interval = self
+            .interval
+            .mul_f32(REPEAT_ACCELERATION)
+            .max(MIN_REPEAT_INTERVAL);
+        true
+    }
+}
+
+impl ActionCommandHandler {
+    fn send_event(&self, event: HintsEvent) {
+        if let Some(app) = self.internals.app() {
+            let result = catch_panic("command", || app.borrow_mut().handle_hints_event(event));
+            if let Err(message) = result {
+                app.borrow().show_error(&HintsError::Panic(message));
+            }
+        } else {
+            debug!("No hints loaded to handle {event:?}");
+        }
+    }
+
+    fn nudge_window(&self, nudge: Nudge) {
+        guard(self.command.name, || {
+            self.internals.with_wrapper(|wrapper| {
+                let geometry = nudge.apply(&wrapper.geometry());
+                wrapper.set_geometry(&geometry);
+            });
+        });
+    }
+}
+
+impl CommandHandler for ActionCommandHandler {
+    fn command_begin(&mut self) {
+        record_event("command", self.command.name);
+        let now = Instant::now();
+        let since_last = self.last_began.replace(now).map(|last| now - last);
+        if self.command.action.repeats() {
+            self.repeater = Some(Repeater::new(now));
+        }
+        match self.command.action {
+            CommandAction::Event(event) => {
+                self.send_event(self.acceleration.accelerate(event, since_last));
+            }
+            CommandAction::ToggleWindow => guard(self.command.name, || {
+                self.internals
+                    .with_wrapper(SystemWrapper::toggle_hint_window);
+            }),
+            CommandAction::LoadWindow => guard(self.command.name, || {
+                self.internals.with_wrapper(|wrapper| wrapper.load(false));
+            }),
+            CommandAction::SaveWindow => guard(self.command.name, || {
+                self.internals.with_wrapper(|wrapper| wrapper.save());
+            }),
+            CommandAction::SaveWindowForView => guard(self.command.name, || {
+                self.internals
+                    .with_wrapper(|wrapper| wrapper.save_for_view(true));
+            }),
+            CommandAction::ForgetWindowForView => guard(self.command.name, || {
+                self.internals
+                    .with_wrapper(|wrapper| wrapper.save_for_view(false));
+            }),
+            CommandAction::ResetWindow => guard(self.command.name, || {
+                self.internals.with_wrapper(SystemWrapper::reset);
+            }),
+            CommandAction::NudgeWindow(nudge) => self.nudge_window(nudge),
+            CommandAction::ExportState => guard(self.command.name, export_states),
+            CommandAction::ImportState => {
+                guard(self.command.name, || import_and_load(&self.internals));
+            }
+            CommandAction::DumpHistory => guard(self.command.name, log_event_history),
+            CommandAction::Peek => self.send_event(HintsEvent::StartPeek),
+            CommandAction::Diagnose => {
+                if self.internals.is_some() {
+                    self.send_event(HintsEvent::Diagnose);
+                } else {
+                    info!(
+                        "Hints diagnosis:\n  Hints directory: {} (not found)",
+                        get_current_aircraft_path().join("hints").display()
+                    );
+                }
+            }
+        }
+    }
+    fn command_continue(&mut self) {
+        let due = self
+            .repeater
+            .as_mut()
+            .is_some_and(|repeater| repeater.due(Instant::now()));
+        if !due {
+            return;
+        }
+        record_event("command", format_args!("{} (repeat)", self.command.name));
+        match self.command.action {
+            CommandAction::Event(event) => self.send_event(event),
+            CommandAction::NudgeWindow(nudge) => self.nudge_window(nudge),
+            _ => {}
+        }
+    }
+
+    fn command_end(&mut self) {
+        self.repeater = None;
+        if matches!(self.command.action, CommandAction::Peek) {
+            self.send_event(HintsEvent::EndPeek);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "mock-xplm"))]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use imgui_support::geometry::Rect;
+
+    use super::{
+        command_list, Nudge, Repeater, COMMANDS, MIN_NUDGED_SIZE, MIN_REPEAT_INTERVAL, REPEAT_DELAY,
+    };
+
+    /// The times after the command began at which it repeats, checking every 10 ms.
+    fn repeats(held: Duration) -> Vec<Duration> {
+        let began = Instant::now();
+        let mut repeater = Repeater::new(began);
+        (0..=u64::try_from(held.as_millis()).unwrap() / 10)
+            .map(|tick| Duration::from_millis(tick * 10))
+            .filter(|elapsed| repeater.due(began + *elapsed))
+            .collect()
+    }
+
+    #[test]
+    fn lists_every_command() {
+        let list = command_list();
+        assert_eq!(list.lines().count(), COMMANDS.len());
+        assert!(list.starts_with("flc/hints/next\tShow next hint\n"));
+    }
+
+    #[test]
+    fn does_not_repeat_before_delay() {
+        assert!(repeats(REPEAT_DELAY - Duration::from_millis(10)).is_empty());
+    }
+
+    #[test]
+    fn repeats_faster_while_held() {
+        let times = repeats(Duration::from_secs(3));
+        assert_eq!(times[0], REPEAT_DELAY);
+        assert_eq!(times[1], REPEAT_DELAY + Duration::from_millis(200));
+        let intervals: Vec<_> = times.windows(2).map(|pair| pair[1] - pair[0]).collect();
+        assert!(intervals.windows(2).all(|pair| pair[1] <= pair[0]));
+        assert_eq!(intervals.last(), Some(&MIN_REPEAT_INTERVAL));
+    }
+
+    fn window() -> Rect {
+        Rect {
+            left: 100,
+            top: 600,
+            right: 500,
+            bottom: 300,
+        }
+    }
+
+    #[test]
+    fn nudges_move_the_window() {
+        let moved = Nudge::Move(-10, 10).apply(&window());
+        assert_eq!(
+            [moved.left, moved.top, moved.right, moved.bottom],
+            [90, 610, 490, 310]
+        );
+    }
+
+    #[test]
+    fn nudges_resize_from_the_top_left() {
+        let resized = Nudge::Resize(10, 10).apply(&window());
+        assert_eq!(
+            [resized.left, resized.top, resized.right, resized.bottom],
+            [100, 600, 510, 290]
+        );
+        let shrunk = Nudge::Resize(-1000, -1000).apply(&window());
+        assert_eq!(shrunk.right - shrunk.left, MIN_NUDGED_SIZE);
+        assert_eq!(shrunk.top - shrunk.bottom, MIN_NUDGED_SIZE);
+    }
+
+    #[test]
+    fn nudges_stop_at_the_edge_of_the_coordinate_space() {
+        let far = Rect {
+            left: i32::MAX - 10,
+            top: i32::MAX,
+            right: i32::MAX,
+            bottom: i32::MAX - 10,
+        };
+        let moved = Nudge::Move(10, 10).apply(&far);
+        assert_eq!([moved.top, moved.right], [i32::MAX, i32::MAX]);
+        let resized = Nudge::Resize(10, -10).apply(&far);
+        assert_eq!(resized.right, i32::MAX);
+        assert_eq!(resized.bottom, i32::MAX - MIN_NUDGED_SIZE);
+    }
+}
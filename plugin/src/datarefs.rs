@@ -0,0 +1,243 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::rc::Rc;
+
+use imgui_support::geometry::Rect;
+use tracing::{debug, warn};
+use xplm::data::owned::OwnedData;
+use xplm::data::{
+    ArrayRead, ArrayReadWrite, DataRead, DataReadWrite, ReadOnly, ReadWrite, StringRead,
+    StringReadWrite,
+};
+use xplm_sys::{
+    xplmType_Double, xplmType_Float, xplmType_FloatArray, xplmType_Int, xplmType_IntArray,
+    XPLMDataRef, XPLMFindDataRef, XPLMGetDataRefTypes, XPLMGetDatad, XPLMGetDataf, XPLMGetDatai,
+    XPLMGetDatavf, XPLMGetDatavi,
+};
+
+use hints_common::{record_event, DatarefReader, Hints, HintsEvent};
+
+/// Datarefs that let cockpit gauges follow and change the current hint.
+///
+/// `flc/hints/page` is the current hint, numbered from 1. Writing to it shows that hint on the
+/// next flight loop. `flc/hints/page_count` is the number of hints loaded.
+/// `flc/hints/category_starts` is the page each category of hints starts on, in order, such as
+/// the charts or the pages of a PDF.
+///
+/// When the current hint is a checklist, `flc/hints/checklist/percent_complete` is the
+/// percentage of its items checked and `flc/hints/checklist/current_item` is the first item not
+/// yet checked. Otherwise they are 0 and empty.
+pub struct PageDatarefs {
+    app: Rc<RefCell<Hints>>,
+    page: OwnedData<i32, ReadWrite>,
+    page_count: OwnedData<i32, ReadOnly>,
+    category_starts: OwnedData<[i32], ReadOnly>,
+    checklist_percent_complete: OwnedData<f32, ReadOnly>,
+    checklist_current_item: OwnedData<[u8], ReadOnly>,
+    published_page: i32,
+}
+
+impl PageDatarefs {
+    pub fn new(app: Rc<RefCell<Hints>>) -> Self {
+        PageDatarefs {
+            app,
+            page: OwnedData::create("flc/hints/page").expect("Unable to create page dataref"),
+            page_count: OwnedData::create("flc/hints/page_count")
+                .expect("Unable to create page count dataref"),
+            category_starts: OwnedData::create("flc/hints/category_starts")
+                .expect("Unable to create category starts dataref"),
+            checklist_percent_complete: OwnedData::create("flc/hints/checklist/percent_complete")
+                .expect("Unable to create checklist percent complete dataref"),
+            checklist_current_item: OwnedData::create("flc/hints/checklist/current_item")
+                .expect("Unable to create checklist current item dataref"),
+            published_page: 0,
+        }
+    }
+
+    /// Handles any page written by another plugin, then publishes the current page and count.
+    pub fn update(&mut self) {
+        let requested_page = self.page.get();
+        if requested_page != self.published_page {
+            debug!(requested_page, "flc/hints/page written");
+            record_event("dataref", format_args!("flc/hints/page = {requested_page}"));
+            match usize::try_from(requested_page)
+                .ok()
+                .and_then(|page| page.checked_sub(1))
+            {
+                Some(idx) => self
+                    .app
+                    .borrow_mut()
+                    .handle_hints_event(HintsEvent::GoToHint(idx)),
+                None => warn!("Ignoring invalid page {requested_page} written to flc/hints/page"),
+            }
+        }
+
+        let app = self.app.borrow();
+        let page_count = app.hint_count();
+        let page = if page_count == 0 {
+            0
+        } else {
+            to_dataref_value(app.current_hint_index() + 1)
+        };
+        self.page.set(page);
+        self.page_count.set(to_dataref_value(page_count));
+        self.published_page = page;
+        let category_starts: Vec<i32> = app
+            .category_starts()
+            .into_iter()
+            .map(|idx| to_dataref_value(idx + 1))
+            .collect();
+        if self.category_starts.as_vec() != category_starts {
+            self.category_starts.set(&category_starts);
+        }
+
+        let progress = app.checklist_progress();
+        self.checklist_percent_complete.set(
+            progress
+                .as_ref()
+                .map_or(0.0, |progress| progress.percent_complete),
+        );
+        let current_item = progress
+            .and_then(|progress| progress.current_item)
+            .unwrap_or_default();
+        if self.checklist_current_item.get_as_string().ok().as_ref() != Some(&current_item) {
+            if let Err(e) = self.checklist_current_item.set_as_string(&current_item) {
+                warn!("Unable to publish current checklist item {current_item:?}: {e:?}");
+            }
+        }
+    }
+}
+
+/// Datarefs that let cockpit-builder tools place the hints window, such as for each view preset.
+///
+/// `flc/hints/window/x` and `flc/hints/window/y` are the window's bottom left corner, and
+/// `flc/hints/window/width` and `flc/hints/window/height` its size, in X-Plane's global desktop
+/// coordinates. Writing to any of them moves or resizes the window on the next flight loop.
+pub struct WindowDatarefs {
+    x: OwnedData<i32, ReadWrite>,
+    y: OwnedData<i32, ReadWrite>,
+    width: OwnedData<i32, ReadWrite>,
+    height: OwnedData<i32, ReadWrite>,
+    published: [i32; 4],
+}
+
+impl WindowDatarefs {
+    pub fn new() -> Self {
+        WindowDatarefs {
+            x: OwnedData::create("flc/hints/window/x").expect("Unable to create window x dataref"),
+            y: OwnedData::create("flc/hints/window/y").expect("Unable to create window y dataref"),
+            width: OwnedData::create("flc/hints/window/width")
+                .expect("Unable to create window width dataref"),
+            height: OwnedData::create("flc/hints/window/height")
+                .expect("Unable to create window height dataref"),
+            published: [0; 4],
+        }
+    }
+
+    /// Publishes the window's `geometry`, returning the geometry to give it instead if another
+    /// plugin has written to the datarefs.
+    pub fn update(&mut self, geometry: &Rect) -> Option<Rect> {
+        let [x, y, width, height] = [
+            self.x.get(),
+            self.y.get(),
+            self.width.get(),
+            self.height.get(),
+        ];
+        let requested = if [x, y, width, height] == self.published {
+            None
+        } else if width > 0 && height > 0 {
+            debug!(x, y, width, height, "flc/hints/window written");
+            record_event(
+                "dataref",
+                format_args!("flc/hints/window = {x}, {y}, {width} x {height}"),
+            );
+            Some(Rect {
+                left: x,
+                top: y.saturating_add(height),
+                right: x.saturating_add(width),
+                bottom: y,
+            })
+        } else {
+            warn!("Ignoring invalid window size {width} x {height} written to flc/hints/window");
+            None
+        };
+        let shown = requested.as_ref().unwrap_or(geometry);
+        self.published = [
+            shown.left,
+            shown.bottom,
+            shown.right.saturating_sub(shown.left),
+            shown.top.saturating_sub(shown.bottom),
+        ];
+        let [x, y, width, height] = self.published;
+        self.x.set(x);
+        self.y.set(y);
+        self.width.set(width);
+        self.height.set(height);
+        requested
+    }
+}
+
+fn to_dataref_value(value: usize) -> i32 {
+    i32::try_from(value).unwrap_or(i32::MAX)
+}
+
+/// Reads the datarefs used by templated text hints from the sim. Each dataref is looked up once,
+/// and datarefs that cannot be found are remembered so that the lookup is not repeated every
+/// frame.
+#[derive(Default)]
+pub struct SimDatarefs {
+    found: RefCell<HashMap<String, Option<XPLMDataRef>>>,
+}
+
+impl SimDatarefs {
+    fn find(&self, name: &str) -> Option<XPLMDataRef> {
+        *self
+            .found
+            .borrow_mut()
+            .entry(name.to_string())
+            .or_insert_with(|| {
+                let dataref = CString::new(name)
+                    .ok()
+                    .map(|c_name| unsafe { XPLMFindDataRef(c_name.as_ptr()) })
+                    .filter(|dataref| !dataref.is_null());
+                if dataref.is_none() {
+                    warn!("Unable to find dataref {name} used in a text hint");
+                }
+                dataref
+            })
+    }
+}
+
+impl DatarefReader for SimDatarefs {
+    fn read(&self, name: &str, index: Option<usize>) -> Option<f64> {
+        let dataref = self.find(name)?;
+        let types = unsafe { XPLMGetDataRefTypes(dataref) };
+        #[allow(clippy::cast_possible_wrap)]
+        let has_type = |data_type: u32| types & data_type as i32 != 0;
+        match index {
+            None if has_type(xplmType_Double) => Some(unsafe { XPLMGetDatad(dataref) }),
+            None if has_type(xplmType_Float) => Some(f64::from(unsafe { XPLMGetDataf(dataref) })),
+            None if has_type(xplmType_Int) => Some(f64::from(unsafe { XPLMGetDatai(dataref) })),
+            Some(index) if has_type(xplmType_FloatArray) => {
+                let mut value = 0.0;
+                let offset = i32::try_from(index).ok()?;
+                let read = unsafe { XPLMGetDatavf(dataref, &mut value, offset, 1) };
+                (read == 1).then_some(f64::from(value))
+            }
+            Some(index) if has_type(xplmType_IntArray) => {
+                let mut value = 0;
+                let offset = i32::try_from(index).ok()?;
+                let read = unsafe { XPLMGetDatavi(dataref, &mut value, offset, 1) };
+                (read == 1).then_some(f64::from(value))
+            }
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,242 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+use std::collections::BTreeMap;
+use std::ffi::{c_char, c_void, CString};
+
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+use xplm_sys::{
+    xplm_ControlFlag, xplm_DownFlag, xplm_OptionAltFlag, xplm_ShiftFlag, XPLMCommandOnce,
+    XPLMCommandRef, XPLMFindCommand, XPLMHotKeyID, XPLMKeyFlags, XPLMRegisterHotKey,
+    XPLMUnregisterHotKey, XPLM_VK_DOWN, XPLM_VK_END, XPLM_VK_F1, XPLM_VK_HOME, XPLM_VK_LEFT,
+    XPLM_VK_NEXT, XPLM_VK_PRIOR, XPLM_VK_RIGHT, XPLM_VK_SPACE, XPLM_VK_TAB, XPLM_VK_UP,
+};
+
+use crate::settings::{load_settings, save_settings};
+use hints_common::TomlDocs;
+
+const HOT_KEYS_FILENAME: &str = "hot-keys.toml";
+const HOT_KEYS_DOCS: TomlDocs = TomlDocs {
+    header: "Keys that trigger FLC Hints commands whichever window has keyboard focus.",
+    fields: HotKeySettings::FIELD_DOCS,
+};
+
+/// Keys that can be named other than letters, digits and function keys.
+const NAMED_KEYS: [(&str, u32); 10] = [
+    ("left", XPLM_VK_LEFT),
+    ("right", XPLM_VK_RIGHT),
+    ("up", XPLM_VK_UP),
+    ("down", XPLM_VK_DOWN),
+    ("pageup", XPLM_VK_PRIOR),
+    ("pagedown", XPLM_VK_NEXT),
+    ("home", XPLM_VK_HOME),
+    ("end", XPLM_VK_END),
+    ("space", XPLM_VK_SPACE),
+    ("tab", XPLM_VK_TAB),
+];
+
+/// X-Plane hot keys that trigger the plugin's commands even when the hints window doesn't have
+/// keyboard focus, which is usual while flying. They are off by default, as they take the keys
+/// from X-Plane and other plugins, and are set in `hot-keys.toml` in
+/// `Output/preferences/hints`.
+pub struct HotKeys {
+    settings: HotKeySettings,
+    registered: Vec<XPLMHotKeyID>,
+}
+
+hints_common::documented_struct! {
+    #[derive(Debug, Serialize, Deserialize)]
+    #[serde(default)]
+    struct HotKeySettings {
+        /// Whether the keys are registered, as set by Plugins > FLC Hints > Global hot keys
+        enabled: bool,
+        /// The key for each command, by command name, such as "ctrl+shift+pagedown". Keys are
+        /// a-z, 0-9, f1-f12, left, right, up, down, pageup, pagedown, home, end, space or tab,
+        /// after any of shift, ctrl and alt
+        keys: BTreeMap<String, String>,
+    }
+}
+
+impl Default for HotKeySettings {
+    fn default() -> Self {
+        HotKeySettings {
+            enabled: false,
+            keys: BTreeMap::from([
+                (
+                    "flc/hints/next".to_string(),
+                    "ctrl+shift+pagedown".to_string(),
+                ),
+                (
+                    "flc/hints/previous".to_string(),
+                    "ctrl+shift+pageup".to_string(),
+                ),
+            ]),
+        }
+    }
+}
+
+impl HotKeys {
+    /// Loads the hot key settings, registering the keys if they are enabled. The plugin's commands
+    /// must already have been created.
+    pub fn load() -> Self {
+        let mut hot_keys = HotKeys {
+            settings: load_settings(HOT_KEYS_FILENAME),
+            registered: vec![],
+        };
+        if hot_keys.settings.enabled {
+            hot_keys.register();
+        }
+        hot_keys
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.settings.enabled
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if enabled == self.settings.enabled {
+            return;
+        }
+        self.settings.enabled = enabled;
+        self.save();
+        if enabled {
+            self.register();
+        } else {
+            self.unregister();
+        }
+    }
+
+    fn register(&mut self) {
+        for (command, combination) in &self.settings.keys {
+            let (key, flags) = match parse_combination(combination) {
+                Some(key) => key,
+                None => {
+                    warn!("Ignoring invalid hot key {combination:?} for {command}");
+                    continue;
+                }
+            };
+            let command_ref = match find_command(command) {
+                Some(command_ref) => command_ref,
+                None => {
+                    warn!("Ignoring hot key {combination:?} for unknown command {command}");
+                    continue;
+                }
+            };
+            let description = CString::new(format!("FLC Hints: {command}")).unwrap_or_default();
+            #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+            let id = unsafe {
+                XPLMRegisterHotKey(
+                    key as c_char,
+                    flags | xplm_DownFlag as XPLMKeyFlags,
+                    description.as_ptr(),
+                    Some(hot_key_pressed),
+                    command_ref,
+                )
+            };
+            self.registered.push(id);
+        }
+        info!(count = self.registered.len(), "Registered hot keys");
+    }
+
+    fn unregister(&mut self) {
+        for id in self.registered.drain(..) {
+            unsafe { XPLMUnregisterHotKey(id) };
+        }
+        info!("Unregistered hot keys");
+    }
+
+    fn save(&self) {
+        save_settings(HOT_KEYS_FILENAME, &self.settings, &HOT_KEYS_DOCS);
+    }
+}
+
+impl Drop for HotKeys {
+    fn drop(&mut self) {
+        if !self.registered.is_empty() {
+            self.unregister();
+        }
+    }
+}
+
+unsafe extern "C" fn hot_key_pressed(command_ref: *mut c_void) {
+    XPLMCommandOnce(command_ref);
+}
+
+fn find_command(name: &str) -> Option<XPLMCommandRef> {
+    let name = CString::new(name).ok()?;
+    let command_ref = unsafe { XPLMFindCommand(name.as_ptr()) };
+    (!command_ref.is_null()).then_some(command_ref)
+}
+
+/// Parses a key combination such as `ctrl+shift+pagedown` into its virtual key and modifiers.
+fn parse_combination(combination: &str) -> Option<(u32, XPLMKeyFlags)> {
+    let mut key = None;
+    let mut flags = 0;
+    for part in combination.split('+') {
+        match part.trim().to_ascii_lowercase().as_str() {
+            "shift" => flags |= xplm_ShiftFlag as XPLMKeyFlags,
+            "ctrl" | "control" => flags |= xplm_ControlFlag as XPLMKeyFlags,
+            "alt" | "option" => flags |= xplm_OptionAltFlag as XPLMKeyFlags,
+            name if key.is_none() => key = Some(virtual_key(name)?),
+            _ => return None,
+        }
+    }
+    Some((key?, flags))
+}
+
+fn virtual_key(name: &str) -> Option<u32> {
+    if let Some(number) = name.strip_prefix('f').and_then(|n| n.parse::<u32>().ok()) {
+        return Some(number)
+            .filter(|number| (1..=12).contains(number))
+            .map(|number| XPLM_VK_F1 + number - 1);
+    }
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        // letters and digits have the same virtual key codes as their uppercase ASCII
+        return c
+            .is_ascii_alphanumeric()
+            .then(|| u32::from(c.to_ascii_uppercase()));
+    }
+    NAMED_KEYS
+        .iter()
+        .find(|(key_name, _)| *key_name == name)
+        .map(|(_, key)| *key)
+}
+
+#[cfg(all(test, feature = "mock-xplm"))]
+mod tests {
+    use xplm_sys::{
+        xplm_ControlFlag, xplm_ShiftFlag, XPLMKeyFlags, XPLM_VK_F1, XPLM_VK_NEXT, XPLM_VK_SPACE,
+    };
+
+    use super::parse_combination;
+
+    #[test]
+    fn parses_key_combinations() {
+        let ctrl_shift = (xplm_ControlFlag | xplm_ShiftFlag) as XPLMKeyFlags;
+        assert_eq!(
+            parse_combination("ctrl+shift+pagedown"),
+            Some((XPLM_VK_NEXT, ctrl_shift))
+        );
+        assert_eq!(
+            parse_combination(" Shift + Ctrl + N "),
+            Some((u32::from(b'N'), ctrl_shift))
+        );
+        assert_eq!(parse_combination("f"), Some((u32::from(b'F'), 0)));
+        assert_eq!(parse_combination("f1"), Some((XPLM_VK_F1, 0)));
+        assert_eq!(parse_combination("space"), Some((XPLM_VK_SPACE, 0)));
+    }
+
+    #[test]
+    fn rejects_invalid_combinations() {
+        assert_eq!(parse_combination("ctrl+shift"), None);
+        assert_eq!(parse_combination("a+b"), None);
+        assert_eq!(parse_combination("f13"), None);
+        assert_eq!(parse_combination("ctrl+escape"), None);
+        assert_eq!(parse_combination(""), None);
+    }
+}
@@ -8,13 +8,37 @@
 #![warn(clippy::pedantic)]
 #![allow(clippy::missing_panics_doc)]
 
+mod add_ons;
+mod archive;
+mod charts;
+mod commands;
+mod datarefs;
+mod hot_keys;
+mod log_level;
+#[cfg(all(test, feature = "mock-xplm"))]
+mod mock_xplm;
+mod navdata;
+mod paging;
+mod pop_out;
+mod profile;
+mod safe_mode;
+mod settings;
+mod sound;
+mod speech;
+mod startup;
+mod sync;
+mod update;
 mod utils;
+mod views;
 
 use std::cell::RefCell;
+use std::collections::BTreeMap;
 use std::ffi::c_void;
-use std::path::PathBuf;
-use std::rc::Rc;
+use std::path::{Path, PathBuf};
+use std::rc::{Rc, Weak};
+use std::sync::mpsc::{Receiver, TryRecvError};
 use std::sync::OnceLock;
+use std::time::{Duration, Instant};
 
 use imgui_support::geometry::Rect;
 use imgui_support_xplane::ui::{PositioningMode, Ref};
@@ -22,42 +46,359 @@ use imgui_support_xplane::System;
 use serde::{Deserialize, Serialize};
 use tracing::{debug, error, info, trace, warn};
 use tracing_subscriber::layer::SubscriberExt;
-use xplm::command::{CommandHandler, OwnedCommand};
+use xplm::command::OwnedCommand;
+use xplm::data::owned::OwnedData;
+use xplm::data::ReadOnly;
+use xplm::flight_loop::{FlightLoop, LoopState};
 use xplm::menu::{ActionItem, CheckHandler, CheckItem, Menu, MenuClickHandler};
 use xplm::plugin::Plugin;
 use xplm_sys::{XPLM_MSG_LIVERY_LOADED, XPLM_MSG_PLANE_UNLOADED};
 
+use crate::add_ons::SimAddOns;
+use crate::archive::{export_states, import_states};
+use crate::charts::load_chart_source;
+use crate::commands::{create_commands, publish_commands};
+use crate::datarefs::{PageDatarefs, SimDatarefs, WindowDatarefs};
+use crate::hot_keys::HotKeys;
+use crate::log_level::{
+    current_log_preset, reloadable_filter, set_log_preset, LogLevelDataref, LogPreset,
+};
+use crate::navdata::SimNavData;
+use crate::pop_out::{PopOutPlacement, POP_OUT_DELAY_FRAMES};
+use crate::profile::{
+    current_profile, display_name, get_profile_directory, init_profile, list_profiles,
+    select_profile,
+};
+use crate::safe_mode::{init_safe_mode, is_safe_mode, SAFE_MODE_DISABLED};
+use crate::settings::{read_toml, write_toml};
+use crate::sound::{page_turn_volume, set_page_turn_volume, PageTurnSound, PageTurnVolume};
+use crate::speech::SimSpeaker;
+use crate::startup::{
+    load_startup_visibility, save_startup_visibility, SimPaused, StartupDelay, StartupVisibility,
+};
+use crate::sync::StateSync;
+use crate::update::UpdateCheck;
 use crate::utils::{
     get_current_aircraft_filename, get_current_aircraft_icao, get_current_aircraft_path,
-    get_prefs_path, XplmWrite,
+    get_language, LoadedAircraft, XplmWrite,
 };
+use crate::views::ViewTracker;
 use hints_common::logging::{env_filter, layer};
 use hints_common::{
-    get_offset_from_edge, ConfigError, Hints, HintsEvent, FROM_EDGE_MIN, FROM_EDGE_PROPORTION,
+    catch_panic, get_offset_from_edge, install_panic_hook, is_writable, Hints, HintsError,
+    HintsEvent, HintsOptions, MainThreadTasks, TomlDocs, FROM_EDGE_MIN, FROM_EDGE_PROPORTION,
     HEIGHT, LOGGING_ENV_VAR, TITLE, WIDTH,
 };
 
 static LOGGING: OnceLock<()> = OnceLock::new();
 
+const AIRCRAFT_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+const MENU_STATE_CHECK_INTERVAL: Duration = Duration::from_millis(250);
+const LIBRARY_DIRNAME: &str = "library";
+/// The size of the window in mini mode, in pixels.
+const MINI_WIDTH: i32 = 200;
+const MINI_HEIGHT: i32 = 150;
+
 struct HintPlugin {
-    internals: Option<Internals>,
-    aircraft_loaded: bool,
+    session: Rc<RefCell<Session>>,
+    tasks: Rc<MainThreadTasks>,
+    task_loop: Option<FlightLoop>,
+    aircraft_loop: Option<FlightLoop>,
+    internals_loop: Option<FlightLoop>,
+    controls: Option<Controls>,
+}
+
+/// The aircraft hints are being shown for, and the internals created for it.
+#[derive(Default)]
+struct Session {
+    aircraft: Option<LoadedAircraft>,
+    internals: CurrentInternals,
+    sync: Option<StateSync>,
+    /// The state file of the aircraft the internals were created for, to sync when they go.
+    state_path: Option<PathBuf>,
+    /// Whether a newer state file was fetched from the sync server, once it has been checked.
+    pull: Option<Receiver<bool>>,
+    /// Whether internals are to be created on the next flight loop.
+    internals_pending: bool,
+    /// Whether to show the wizard that creates hints from the aircraft's documentation once the
+    /// internals have been created.
+    import_pending: bool,
+    /// Why the internals could not be created, shown in the menu until they are created again.
+    failure: Option<String>,
+}
+
+impl Session {
+    /// Some aircraft send several livery loaded messages while loading, so internals are only
+    /// created again if the aircraft has changed since they were last created.
+    fn aircraft_loaded(&mut self) {
+        let current = LoadedAircraft::current();
+        if self.internals.is_some() && self.aircraft.as_ref() == Some(&current) {
+            debug!("Ignoring repeated livery loaded message for {current:?}");
+            return;
+        }
+        self.aircraft = Some(current);
+        self.create_internals();
+    }
+
+    fn aircraft_unloaded(&mut self) {
+        self.aircraft = None;
+        self.clear_internals();
+    }
+
+    fn enable(&mut self) {
+        self.sync = if is_safe_mode() {
+            None
+        } else {
+            StateSync::load()
+        };
+        if self.aircraft.is_some() {
+            self.create_internals();
+        }
+    }
+
+    fn disable(&mut self) {
+        self.clear_internals();
+        self.sync = None;
+    }
+
+    /// Some ways of changing aircraft do not reliably send the plane unloaded and livery loaded
+    /// messages, so also check whether the aircraft has changed from the one hints were loaded
+    /// for.
+    fn check_aircraft(&mut self) {
+        if let Some(aircraft) = &self.aircraft {
+            let current = LoadedAircraft::current();
+            if current != *aircraft {
+                info!("Aircraft changed from {aircraft:?} to {current:?} without a message");
+                self.aircraft = Some(current);
+                self.create_internals();
+            }
+        }
+    }
+
+    /// Recreates the internals, if there are any, so that they use the selected profile.
+    fn profile_changed(&mut self) {
+        if self.aircraft.is_some() {
+            self.create_internals();
+        }
+    }
+
+    /// Drops any existing internals now, so that their window and datarefs are destroyed, and
+    /// creates new ones on the next flight loop. X-Plane may still be loading the aircraft when
+    /// this is called, and creating the window then can fail to set up its graphics.
+    fn create_internals(&mut self) {
+        self.clear_internals();
+        self.internals_pending = true;
+    }
+
+    fn create_pending_internals(&mut self) {
+        if !self.internals_pending {
+            return;
+        }
+        self.internals_pending = false;
+        self.state_path = get_state_path();
+        self.pull = match (&self.sync, &self.state_path) {
+            (Some(sync), Some(state_path)) => sync.pull(state_path),
+            _ => None,
+        };
+        // anything created before a failure is dropped with the error, rather than being left
+        // half set up
+        match catch_panic("create internals", Internals::new) {
+            Ok(Ok(internals)) => {
+                self.internals.set(internals);
+                let sync_status = match (&self.sync, is_safe_mode()) {
+                    (Some(_), _) => "enabled",
+                    (None, true) => "disabled in safe mode",
+                    (None, false) => "not configured",
+                };
+                self.internals.with_app(|app| {
+                    app.set_service_status("Sync server", sync_status);
+                    if is_safe_mode() {
+                        app.set_service_status("Safe mode", SAFE_MODE_DISABLED);
+                    }
+                });
+                if std::mem::take(&mut self.import_pending) {
+                    self.start_import();
+                }
+            }
+            Ok(Err(e)) => {
+                error!(code = e.code(), "Unable to create hints: {e}");
+                self.failure = Some(e.to_string());
+            }
+            Err(message) => self.failure = Some(message),
+        }
+    }
+
+    /// Gives the window the state fetched from the sync server, if it was newer than the one the
+    /// window was created with.
+    fn apply_pulled_state(&mut self) {
+        let pulled = match self.pull.as_ref().map(Receiver::try_recv) {
+            Some(Ok(pulled)) => pulled,
+            Some(Err(TryRecvError::Empty)) | None => return,
+            Some(Err(TryRecvError::Disconnected)) => false,
+        };
+        self.pull = None;
+        if pulled {
+            self.internals
+                .with_wrapper(SystemWrapper::load_pulled_state);
+        }
+    }
+
+    /// Tries again to create the internals after they failed.
+    fn retry(&mut self) {
+        if self.failure.is_some() && self.aircraft.is_some() {
+            info!("Retrying creating hints");
+            self.create_internals();
+        }
+    }
+
+    /// Shows the wizard that creates hints from the aircraft's documentation, first creating the
+    /// hints directory and internals if the aircraft has no hints yet.
+    fn import_documentation(&mut self) {
+        if self.internals.is_some() {
+            self.start_import();
+            return;
+        }
+        let path = get_current_aircraft_path().join("hints");
+        if let Err(e) = std::fs::create_dir_all(&path) {
+            let library = get_library_directory();
+            warn!("Unable to create {path:?}, so creating hints in {library:?}: {e}");
+            if let Err(e) = std::fs::create_dir_all(&library) {
+                error!("Unable to create {library:?}: {e}");
+                return;
+            }
+        }
+        if self.aircraft.is_none() {
+            self.aircraft = Some(LoadedAircraft::current());
+        }
+        self.create_internals();
+        self.import_pending = true;
+    }
+
+    fn start_import(&self) {
+        let aircraft_path = get_current_aircraft_path();
+        self.internals
+            .with_app(|app| app.start_import(&aircraft_path));
+        self.internals
+            .with_wrapper(|wrapper| wrapper.set_hint_window_visible(true));
+    }
+
+    fn clear_internals(&mut self) {
+        self.internals_pending = false;
+        self.import_pending = false;
+        self.failure = None;
+        self.pull = None;
+        self.internals.clear();
+        if let (Some(sync), Some(state_path)) = (&self.sync, self.state_path.take()) {
+            sync.push(&state_path);
+        }
+    }
+}
+
+/// The internals for the current aircraft, if any. This is shared with the commands and menu
+/// items, which act on whichever internals are current rather than being recreated with them.
+#[derive(Clone, Default)]
+struct CurrentInternals(Rc<RefCell<Option<Internals>>>);
+
+impl CurrentInternals {
+    fn is_some(&self) -> bool {
+        self.0.borrow().is_some()
+    }
+
+    fn set(&self, internals: Option<Internals>) {
+        *self.0.borrow_mut() = internals;
+    }
+
+    fn clear(&self) {
+        let internals = self.0.borrow_mut().take();
+        drop(internals);
+    }
+
+    fn app(&self) -> Option<Rc<RefCell<Hints>>> {
+        self.0
+            .borrow()
+            .as_ref()
+            .map(|internals| Rc::clone(&internals.app))
+    }
+
+    fn with_app<F: FnOnce(&mut Hints)>(&self, f: F) {
+        match self.app() {
+            Some(app) => f(&mut app.borrow_mut()),
+            None => debug!("No hints loaded for the current aircraft"),
+        }
+    }
+
+    fn startup_visibility(&self) -> Option<StartupVisibility> {
+        self.0
+            .borrow()
+            .as_ref()
+            .map(|internals| internals.wrapper.borrow().startup_visibility)
+    }
+
+    /// Whether the hints window is shown, or `None` if there are no hints for the aircraft.
+    fn window_visible(&self) -> Option<bool> {
+        self.0
+            .borrow()
+            .as_ref()
+            .map(|internals| internals.wrapper.borrow().is_hint_window_visible())
+    }
+
+    fn with_wrapper<F: FnOnce(&mut SystemWrapper)>(&self, f: F) {
+        let wrapper = self
+            .0
+            .borrow()
+            .as_ref()
+            .map(|internals| Rc::clone(&internals.wrapper));
+        match wrapper {
+            Some(wrapper) => f(&mut wrapper.borrow_mut()),
+            None => debug!("No hints window for the current aircraft"),
+        }
+    }
 }
 
+/// The hints app, window and flight loop for the current aircraft.
 struct Internals {
-    _menu: Menu,
-    _next_command: OwnedCommand,
-    _previous_command: OwnedCommand,
-    _reload_command: OwnedCommand,
-    _toggle_window_command: OwnedCommand,
-    _load_command: OwnedCommand,
-    _save_command: OwnedCommand,
-    _reset_command: OwnedCommand,
+    app: Rc<RefCell<Hints>>,
+    wrapper: Rc<RefCell<SystemWrapper>>,
+    _flight_loop: FlightLoop,
+}
+
+/// Commands and menu items. These are created once when the plugin is enabled, so that joystick
+/// and keyboard bindings to the commands keep working when the aircraft changes.
+struct Controls {
+    _menu: Rc<Menu>,
+    _commands: Vec<OwnedCommand>,
+    _commands_dataref: OwnedData<[u8], ReadOnly>,
+    _menu_state_loop: FlightLoop,
+    _update_check: Option<UpdateCheck>,
+    _hot_keys: Rc<RefCell<HotKeys>>,
+}
+
+/// A wait before the window is first shown for an aircraft.
+struct PendingStartup {
+    loaded: Instant,
+    delay: StartupDelay,
+    paused: SimPaused,
+    /// Whether the window was visible when it was hidden for the wait.
+    visible: bool,
+}
+
+impl PendingStartup {
+    fn is_over(&self) -> bool {
+        self.delay.is_over(self.loaded.elapsed(), self.paused.get())
+    }
 }
 
 struct SystemWrapper {
     system: System,
     default_geometry: Rect,
+    startup_visibility: StartupVisibility,
+    /// The wait before showing the window after the aircraft was loaded, until it is over.
+    pending_startup: Option<PendingStartup>,
+    /// The window's geometry before it was shrunk for mini mode, while it is in mini mode.
+    full_geometry: Option<Rect>,
+    views: ViewTracker,
+    /// Where to place the window once it has been popped out, and the frames left until then.
+    pending_pop_out: Option<(PopOutPlacement, u32)>,
 }
 
 impl SystemWrapper {
@@ -66,46 +407,169 @@ impl SystemWrapper {
         let mut wrapper = Self {
             system,
             default_geometry,
+            startup_visibility: load_startup_visibility(&get_current_aircraft_id()),
+            pending_startup: None,
+            full_geometry: None,
+            views: ViewTracker::new(),
+            pending_pop_out: None,
         };
-        wrapper.load(true);
+        let delay = StartupDelay::load();
+        if delay.is_set() {
+            info!(?delay, "Waiting to show the hints window");
+            wrapper.pending_startup = Some(PendingStartup {
+                loaded: Instant::now(),
+                delay,
+                paused: SimPaused::find(),
+                visible: wrapper.is_hint_window_visible(),
+            });
+            wrapper.set_hint_window_visible(false);
+        } else {
+            wrapper.restore_startup_state();
+        }
         wrapper
     }
 
-    #[must_use]
-    pub fn toggle_hint_window(&mut self) -> bool {
-        self.system.window_mut().toggle_visible()
+    /// Gives the window its saved geometry and visibility, as when the aircraft is loaded.
+    fn restore_startup_state(&mut self) {
+        self.load(true);
+        if let Some(visible) = self.startup_visibility.visible() {
+            self.set_hint_window_visible(visible);
+        }
+        let placement = PopOutPlacement::load();
+        if placement.is_set() && matches!(self.state().mode, Mode::PopOut) {
+            self.pending_pop_out = Some((placement, POP_OUT_DELAY_FRAMES));
+        }
+    }
+
+    /// Places the popped out window on the monitor set for it, a few frames after the aircraft
+    /// was loaded.
+    fn place_pop_out(&mut self) {
+        let (placement, frames) = match self.pending_pop_out {
+            Some(pending) => pending,
+            None => return,
+        };
+        if frames > 0 {
+            self.pending_pop_out = Some((placement, frames - 1));
+            return;
+        }
+        self.pending_pop_out = None;
+        match placement.place(&self.state().position) {
+            Some(geometry) => {
+                info!(?placement, ?geometry, "Placing the popped out hints window");
+                self.set_geometry(&geometry);
+            }
+            None => warn!(
+                ?placement,
+                "Unable to find the monitor for the hints window"
+            ),
+        }
+    }
+
+    /// Restores the window once the startup delay is over, if it is waiting for one.
+    fn show_when_ready(&mut self) {
+        if let Some(pending) = &self.pending_startup {
+            if pending.is_over() {
+                let visible = pending.visible;
+                info!("Showing the hints window after the startup delay");
+                self.pending_startup = None;
+                self.set_hint_window_visible(visible);
+                self.restore_startup_state();
+            }
+        }
+    }
+
+    fn set_startup_visibility(&mut self, visibility: StartupVisibility) {
+        self.startup_visibility = visibility;
+        save_startup_visibility(&get_current_aircraft_id(), visibility);
+    }
+
+    pub fn toggle_hint_window(&mut self) {
+        self.system.window_mut().toggle_visible();
     }
 
     pub fn set_hint_window_visible(&mut self, visible: bool) {
         self.system.window_mut().set_visible(visible);
     }
 
+    fn is_hint_window_visible(&self) -> bool {
+        self.system.window().is_visible()
+    }
+
+    fn geometry(&self) -> Rect {
+        self.system.window().geometry()
+    }
+
+    /// Moves and resizes the window, as the window commands and datarefs do.
+    fn set_geometry(&mut self, geometry: &Rect) {
+        debug!(?geometry, "Placing hints window");
+        self.system.window_mut().set_geometry(geometry);
+        // shrunk again from the new geometry if in mini mode
+        self.full_geometry = None;
+    }
+
+    /// Shrinks the window to a thumbnail in its top right corner when the app enters mini mode,
+    /// and restores it when the app leaves it.
+    fn sync_mini_mode(&mut self, mini: bool) {
+        let window = self.system.window_mut();
+        match (mini, self.full_geometry.take()) {
+            (true, None) => {
+                let full = window.geometry();
+                let thumbnail = Rect {
+                    left: full.right - MINI_WIDTH,
+                    bottom: full.top - MINI_HEIGHT,
+                    ..full
+                };
+                debug!(?full, ?thumbnail, "Shrinking hints window for mini mode");
+                window.set_geometry(&thumbnail);
+                self.full_geometry = Some(full);
+            }
+            (false, Some(full)) => {
+                debug!(?full, "Restoring hints window from mini mode");
+                window.set_geometry(&full);
+            }
+            (_, full) => self.full_geometry = full,
+        }
+    }
+
+    /// The window's state to save, at its full size rather than as a thumbnail in mini mode.
+    fn state(&self) -> State {
+        let mut state = State::from(self.system.window());
+        if let Some(full) = &self.full_geometry {
+            state.position = full.clone();
+        }
+        state
+    }
+
+    fn apply_state(&mut self, state: &State) {
+        let window = self.system.window_mut();
+        window.set_positioning_mode(PositioningMode::from(&state.mode));
+        window.set_geometry(&state.position);
+        window.set_visible(state.visible);
+        // shrunk again from the loaded geometry if in mini mode
+        self.full_geometry = None;
+    }
+
     fn save(&self) {
         if let Some(filename) = get_state_path() {
-            let state = State::from(self.system.window());
-            let toml = toml::to_string_pretty(&state).unwrap();
-            match std::fs::write(&filename, toml) {
-                Ok(()) => info!("Saved hints window state to {filename:?}"),
-                Err(e) => error!("Unable to save hints window state: {e}"),
-            }
+            write_toml(&filename, &self.state(), &STATE_DOCS);
+        }
+    }
+
+    /// Loads the state fetched from the sync server after the window was created, unless the
+    /// window is still waiting to be shown, when the state is loaded anyway.
+    fn load_pulled_state(&mut self) {
+        if self.pending_startup.is_none() {
+            info!("Loading hints window state fetched from sync server");
+            self.load(true);
         }
     }
 
     fn load(&mut self, quietly: bool) {
         if let Some(filename) = get_state_path() {
             if filename.is_file() {
-                match std::fs::read_to_string(&filename) {
-                    Ok(toml) => match toml::from_str::<State>(&toml) {
-                        Ok(state) => {
-                            let window = self.system.window_mut();
-                            window.set_positioning_mode(PositioningMode::from(&state.mode));
-                            window.set_geometry(&state.position);
-                            window.set_visible(state.visible);
-                            info!("Loaded hints window state from {filename:?}");
-                        }
-                        Err(e) => error!("Unable to parse hints window state: {e}"),
-                    },
-                    Err(e) => error!("Unable to read from {filename:?}: {e}"),
+                if let Some(state) = read_toml::<State>(&filename) {
+                    self.apply_state(&state);
+                    info!("Loaded hints window state from {filename:?}");
                 }
             } else if !quietly {
                 warn!("Unable to find any saved window state to load at {filename:?}");
@@ -113,102 +577,184 @@ impl SystemWrapper {
         }
     }
 
+    /// Gives the window the layout saved for the view, when the sim changes to a view that has
+    /// one.
+    fn follow_view(&mut self) {
+        let view = match self.views.changed() {
+            Some(view) => view,
+            None => return,
+        };
+        if self.pending_startup.is_some() {
+            return;
+        }
+        if let Some(state) = read_view_layouts().remove(&view) {
+            info!(view, "Loading the hints window layout saved for the view");
+            self.apply_state(&state);
+        }
+    }
+
+    /// Saves the window's layout for the view the sim is showing, or forgets the one saved if
+    /// `save` is `false`.
+    fn save_for_view(&self, save: bool) {
+        let (view, filename) = match (self.views.current(), get_view_layouts_path()) {
+            (Some(view), Some(filename)) => (view, filename),
+            _ => {
+                warn!("Unable to tell which view the sim is showing");
+                return;
+            }
+        };
+        let mut layouts = read_view_layouts();
+        if save {
+            layouts.insert(view.to_string(), self.state());
+        } else if layouts.remove(view).is_none() {
+            info!(view, "No hints window layout is saved for the view");
+            return;
+        }
+        if write_toml(&filename, &layouts, &VIEW_LAYOUTS_DOCS) {
+            if save {
+                info!(view, "Saved the hints window layout for the view");
+            } else {
+                info!(view, "Forgot the hints window layout for the view");
+            }
+        }
+    }
+
     fn reset(&mut self) {
         let window = self.system.window_mut();
         window.set_positioning_mode(PositioningMode::Free);
         window.set_visible(true);
         window.set_geometry(&self.default_geometry);
+        self.full_geometry = None;
     }
 }
 
 impl Internals {
-    fn new() -> Option<Self> {
+    /// Creates the hints for the current aircraft, or `None` if it has no hints.
+    fn new() -> Result<Option<Self>, HintsError> {
         let path = find_path();
         if path.is_none() {
             error!("Unable to find hints directory - plugin will do nothing");
-            return None;
+            return Ok(None);
+        }
+        let path = path.unwrap();
+        let read_only = !is_writable(&path);
+        let library = find_library(&path, read_only);
+        // settings that can't be saved with the hints are saved in the library
+        let settings_dir =
+            get_settings_directory().or_else(|| library.clone().filter(|_| read_only));
+        let options = HintsOptions {
+            settings_dir,
+            library,
+            read_only,
+            language: get_language().map(String::from),
+            // rules in the hints config, and the datarefs they read, are left out in safe mode
+            add_ons: if is_safe_mode() {
+                None
+            } else {
+                Some(Box::new(SimAddOns))
+            },
+            safe_mode: is_safe_mode(),
+        };
+        let mut app = Hints::with_options(path, options)?;
+        app.set_nav_data(Box::<SimNavData>::default());
+        app.set_speaker(Box::new(SimSpeaker));
+        if !is_safe_mode() {
+            app.set_dataref_reader(Box::<SimDatarefs>::default());
+            if let Some(chart_source) = load_chart_source() {
+                app.set_chart_provider(chart_source);
+            }
         }
-        let app = Rc::new(RefCell::new(
-            Hints::new(path.unwrap()).expect("Unable to create FLC Hints app"),
-        ));
+        let app = Rc::new(RefCell::new(app));
         let wrapper = Rc::new(RefCell::new(SystemWrapper::new(init_xplane(Rc::clone(
             &app,
         )))));
 
-        let (menu, toggle) = create_menu(&wrapper, &app);
-
-        let toggle_command_handler = ToggleWindowCommandHandler {
-            wrapper: Rc::clone(&wrapper),
-            toggle: Rc::clone(&toggle),
-        };
-
-        let save_command_handler = SaveCommandHandler {
-            wrapper: Rc::clone(&wrapper),
-        };
+        let mut page_datarefs = PageDatarefs::new(Rc::clone(&app));
+        let mut page_turn_sound = PageTurnSound::new(Rc::clone(&app));
+        let mut window_datarefs = WindowDatarefs::new();
+        let loop_app = Rc::clone(&app);
+        let loop_wrapper = Rc::clone(&wrapper);
+        let mut flight_loop = FlightLoop::new(move |_: &mut LoopState| {
+            guard("update", || loop_app.borrow_mut().update());
+            guard("startup delay", || {
+                loop_wrapper.borrow_mut().show_when_ready()
+            });
+            guard("view layouts", || loop_wrapper.borrow_mut().follow_view());
+            guard("pop-out placement", || {
+                loop_wrapper.borrow_mut().place_pop_out()
+            });
+            guard("mini mode", || {
+                let mini = loop_app.borrow().is_mini_mode();
+                loop_wrapper.borrow_mut().sync_mini_mode(mini);
+            });
+            guard("page datarefs", || page_datarefs.update());
+            guard("page-turn sound", || page_turn_sound.update());
+            guard("window datarefs", || {
+                let mut wrapper = loop_wrapper.borrow_mut();
+                if let Some(geometry) = window_datarefs.update(&wrapper.geometry()) {
+                    wrapper.set_geometry(&geometry);
+                }
+            });
+        });
+        flight_loop.schedule_immediate();
+
+        Ok(Some(Internals {
+            app,
+            wrapper,
+            _flight_loop: flight_loop,
+        }))
+    }
+}
 
-        let load_command_handler = LoadCommandHandler {
-            wrapper: Rc::clone(&wrapper),
-        };
+impl Controls {
+    fn new(internals: &CurrentInternals, session: &Rc<RefCell<Session>>) -> Self {
+        let commands = create_commands(internals);
+        let hot_keys = Rc::new(RefCell::new(HotKeys::load()));
 
-        let reset_command_handler = ResetCommandHandler {
-            wrapper: Rc::clone(&wrapper),
+        let (menu, items) = create_menu(internals, session, &hot_keys);
+        let menu = Rc::new(menu);
+        let menu_state_loop = create_menu_state_loop(internals.clone(), Rc::clone(session), items);
+        let update_check = if is_safe_mode() {
+            None
+        } else {
+            UpdateCheck::start(&menu)
         };
 
-        Some(Internals {
+        Controls {
             _menu: menu,
-            _next_command: create_event_sending_command(
-                "flc/hints/next",
-                "Show next hint",
-                HintsEvent::NextHint,
-                Rc::clone(&app),
-            ),
-            _previous_command: create_event_sending_command(
-                "flc/hints/previous",
-                "Show previous hint",
-                HintsEvent::PreviousHint,
-                Rc::clone(&app),
-            ),
-            _reload_command: create_event_sending_command(
-                "flc/hints/reload",
-                "Reload hints from disk",
-                HintsEvent::Reload,
-                app,
-            ),
-            _toggle_window_command: create_owned_command(
-                "flc/hints/window/toggle",
-                "Toggle window visibility",
-                toggle_command_handler,
-            ),
-            _load_command: create_owned_command(
-                "flc/hints/window/load",
-                "Load window position",
-                load_command_handler,
-            ),
-            _save_command: create_owned_command(
-                "flc/hints/window/save",
-                "Save window position",
-                save_command_handler,
-            ),
-            _reset_command: create_owned_command(
-                "flc/hints/window/reset",
-                "Reset window position",
-                reset_command_handler,
-            ),
-        })
+            _commands: commands,
+            _commands_dataref: publish_commands(),
+            _menu_state_loop: menu_state_loop,
+            _update_check: update_check,
+            _hot_keys: hot_keys,
+        }
     }
 }
 
 fn create_menu(
-    wrapper: &Rc<RefCell<SystemWrapper>>,
-    app: &Rc<RefCell<Hints>>,
-) -> (Menu, Rc<CheckItem>) {
+    internals: &CurrentInternals,
+    session: &Rc<RefCell<Session>>,
+    hot_keys: &Rc<RefCell<HotKeys>>,
+) -> (Menu, MenuItems) {
     let menu = Menu::new("FLC Hints").expect("Unable to create hints menu");
+    // shows how many hints were found, so that a menu that does nothing is explained, or why
+    // they could not be shown
+    let status = Rc::new(
+        ActionItem::new(
+            "(no hints found)",
+            StatusMenuClickHandler {
+                session: Rc::clone(session),
+            },
+        )
+        .expect("Unable to create status menu item"),
+    );
+    menu.add_child::<Rc<ActionItem>, ActionItem>(Rc::clone(&status));
     let toggle = Rc::new(
         CheckItem::new(
             "Show hints",
             false,
             ToggleWindowCheckHandler {
-                wrapper: Rc::clone(wrapper),
+                internals: internals.clone(),
             },
         )
         .expect("Unable to create show hints window menu item"),
@@ -221,7 +767,7 @@ fn create_menu(
         ActionItem::new(
             "Load",
             LoadMenuClickHandler {
-                wrapper: Rc::clone(wrapper),
+                internals: internals.clone(),
             },
         )
         .expect("Unable to create load menu item"),
@@ -231,62 +777,225 @@ fn create_menu(
         ActionItem::new(
             "Save",
             SaveMenuClickHandler {
-                wrapper: Rc::clone(wrapper),
+                internals: internals.clone(),
             },
         )
         .expect("Unable to create save menu item"),
     );
 
+    window_menu.add_child(
+        ActionItem::new(
+            "Save for this view",
+            SaveForViewMenuClickHandler {
+                internals: internals.clone(),
+            },
+        )
+        .expect("Unable to create save for view menu item"),
+    );
+
     window_menu.add_child(
         ActionItem::new(
             "Reset",
             ResetMenuClickHandler {
-                wrapper: Rc::clone(wrapper),
+                internals: internals.clone(),
             },
         )
         .expect("Unable to create reset menu item"),
     );
+
+    window_menu.add_child(
+        ActionItem::new("Export all aircraft", ExportMenuClickHandler)
+            .expect("Unable to create export menu item"),
+    );
+
+    window_menu.add_child(
+        ActionItem::new(
+            "Import all aircraft",
+            ImportMenuClickHandler {
+                internals: internals.clone(),
+            },
+        )
+        .expect("Unable to create import menu item"),
+    );
     menu.add_child(window_menu);
 
+    let startup_menu = Menu::new("On aircraft load").expect("Unable to create startup menu");
+    let startup = StartupVisibility::ALL
+        .into_iter()
+        .map(|visibility| {
+            let item = Rc::new(
+                CheckItem::new(
+                    visibility.label(),
+                    false,
+                    StartupCheckHandler {
+                        internals: internals.clone(),
+                        visibility,
+                    },
+                )
+                .expect("Unable to create startup menu item"),
+            );
+            startup_menu.add_child::<Rc<CheckItem>, CheckItem>(Rc::clone(&item));
+            (visibility, item)
+        })
+        .collect();
+    menu.add_child(startup_menu);
+
+    menu.add_child(create_profile_menu(session));
+
+    let logging_menu = Menu::new("Logging").expect("Unable to create logging menu");
+    let logging = LogPreset::ALL
+        .into_iter()
+        .map(|preset| {
+            let item = Rc::new(
+                CheckItem::new(preset.label(), false, LogPresetCheckHandler { preset })
+                    .expect("Unable to create logging menu item"),
+            );
+            logging_menu.add_child::<Rc<CheckItem>, CheckItem>(Rc::clone(&item));
+            (preset, item)
+        })
+        .collect();
+    menu.add_child(logging_menu);
+
+    let sound_menu = Menu::new("Page-turn sound").expect("Unable to create sound menu");
+    let sound = PageTurnVolume::ALL
+        .into_iter()
+        .map(|volume| {
+            let item = Rc::new(
+                CheckItem::new(volume.label(), false, PageTurnVolumeCheckHandler { volume })
+                    .expect("Unable to create sound menu item"),
+            );
+            sound_menu.add_child::<Rc<CheckItem>, CheckItem>(Rc::clone(&item));
+            (volume, item)
+        })
+        .collect();
+    menu.add_child(sound_menu);
+
+    let enabled = hot_keys.borrow().enabled();
+    menu.add_child(
+        CheckItem::new(
+            "Global hot keys",
+            enabled,
+            HotKeysCheckHandler {
+                hot_keys: Rc::clone(hot_keys),
+            },
+        )
+        .expect("Unable to create hot keys menu item"),
+    );
+
+    menu.add_child(
+        ActionItem::new(
+            "Create hints from aircraft documentation",
+            ImportDocsMenuClickHandler {
+                session: Rc::clone(session),
+            },
+        )
+        .expect("Unable to create import documentation menu item"),
+    );
+
+    menu.add_child(
+        ActionItem::new(
+            "Choose aircraft variant",
+            VariantsMenuClickHandler {
+                internals: internals.clone(),
+            },
+        )
+        .expect("Unable to create variants menu item"),
+    );
+
     menu.add_child(
         ActionItem::new(
             "Reload hints from disk",
             ReloadMenuClickHandler {
-                app: Rc::clone(app),
+                internals: internals.clone(),
             },
         )
         .expect("Unable to create reload menu item"),
     );
 
-    // TODO: add scale by 1.25 / 0.8
-
     menu.add_to_plugins_menu();
-    (menu, toggle)
+    let items = MenuItems {
+        status,
+        toggle,
+        startup,
+        logging,
+        sound,
+    };
+    (menu, items)
+}
+
+/// The menu items that show the state of the hints for the current aircraft.
+struct MenuItems {
+    status: Rc<ActionItem>,
+    toggle: Rc<CheckItem>,
+    startup: Vec<(StartupVisibility, Rc<CheckItem>)>,
+    logging: Vec<(LogPreset, Rc<CheckItem>)>,
+    sound: Vec<(PageTurnVolume, Rc<CheckItem>)>,
+}
+
+/// Creates a menu to choose between the default profile and the profiles that exist when the
+/// plugin is enabled.
+fn create_profile_menu(session: &Rc<RefCell<Session>>) -> Menu {
+    let profile_menu = Menu::new("Profile").expect("Unable to create profile menu");
+    let current = current_profile();
+    let items: ProfileItems = Rc::new(RefCell::new(vec![]));
+    let profiles = std::iter::once(None).chain(list_profiles().into_iter().map(Some));
+    for profile in profiles {
+        let item = Rc::new(
+            CheckItem::new(
+                display_name(profile.as_deref()),
+                profile == current,
+                ProfileCheckHandler {
+                    profile: profile.clone(),
+                    session: Rc::clone(session),
+                    items: Rc::clone(&items),
+                },
+            )
+            .expect("Unable to create profile menu item"),
+        );
+        profile_menu.add_child::<Rc<CheckItem>, CheckItem>(Rc::clone(&item));
+        items.borrow_mut().push((profile, Rc::downgrade(&item)));
+    }
+    profile_menu
 }
 
 impl Plugin for HintPlugin {
-    type Error = ConfigError;
+    type Error = HintsError;
 
     fn start() -> Result<Self, Self::Error> {
         init_logging(LOGGING_ENV_VAR, false);
+        install_panic_hook();
         trace!("start()");
+        init_profile();
+        init_safe_mode();
+        let tasks = MainThreadTasks::install().map_err(|e| HintsError::Config(e.to_string()))?;
         Ok(HintPlugin {
-            internals: None,
-            aircraft_loaded: false,
+            session: Rc::new(RefCell::new(Session::default())),
+            tasks: Rc::new(tasks),
+            task_loop: None,
+            aircraft_loop: None,
+            internals_loop: None,
+            controls: None,
         })
     }
 
     fn enable(&mut self) -> Result<(), Self::Error> {
         trace!("enable()");
-        if self.aircraft_loaded {
-            self.internals = Internals::new();
-        }
+        self.task_loop = Some(create_task_loop(Rc::clone(&self.tasks)));
+        self.aircraft_loop = Some(create_aircraft_loop(Rc::clone(&self.session)));
+        self.internals_loop = Some(create_internals_loop(Rc::clone(&self.session)));
+        let internals = self.session.borrow().internals.clone();
+        self.controls = Some(Controls::new(&internals, &self.session));
+        self.session.borrow_mut().enable();
         Ok(())
     }
 
     fn disable(&mut self) {
         trace!("disable()");
-        self.internals.take();
+        self.session.borrow_mut().disable();
+        self.controls.take();
+        self.internals_loop.take();
+        self.aircraft_loop.take();
+        self.task_loop.take();
     }
 
     fn info(&self) -> xplm::plugin::PluginInfo {
@@ -299,17 +1008,21 @@ impl Plugin for HintPlugin {
 
     fn receive_message(&mut self, _from: i32, message: i32, _param: *mut c_void) {
         trace!("Received message {message}");
+        guard("message", || self.handle_message(message));
+    }
+}
+
+impl HintPlugin {
+    fn handle_message(&mut self, message: i32) {
         #[allow(clippy::cast_sign_loss)]
         match message as u32 {
             XPLM_MSG_LIVERY_LOADED => {
                 debug!("Livery loaded");
-                self.aircraft_loaded = true;
-                self.internals = Internals::new();
+                self.session.borrow_mut().aircraft_loaded();
             }
             XPLM_MSG_PLANE_UNLOADED => {
                 debug!("Plane unloaded");
-                self.aircraft_loaded = false;
-                self.internals.take();
+                self.session.borrow_mut().aircraft_unloaded();
             }
             _ => {}
         }
@@ -318,141 +1031,364 @@ impl Plugin for HintPlugin {
 
 xplm::xplane_plugin!(HintPlugin);
 
-fn create_event_sending_command(
-    name: &str,
-    description: &str,
-    event: HintsEvent,
-    app: Rc<RefCell<Hints>>,
-) -> OwnedCommand {
-    create_owned_command(name, description, EventSendingCommandHandler { app, event })
+/// Runs a callback from X-Plane, logging any panic rather than letting it unwind into the sim.
+fn guard<F: FnOnce()>(context: &str, f: F) {
+    if catch_panic(context, f).is_err() {
+        warn!("Recovered from error in {context}");
+    }
 }
 
-fn create_owned_command<T: CommandHandler>(
-    name: &str,
-    description: &str,
-    handler: T,
-) -> OwnedCommand {
-    OwnedCommand::new(name, description, handler).expect("Unable to create command '{name}'")
+/// Creates a flight loop that runs tasks scheduled on the main thread by background threads.
+fn create_task_loop(tasks: Rc<MainThreadTasks>) -> FlightLoop {
+    let mut flight_loop = FlightLoop::new(move |_: &mut LoopState| {
+        guard("main thread tasks", || tasks.run_pending());
+    });
+    flight_loop.schedule_immediate();
+    flight_loop
 }
 
-struct EventSendingCommandHandler {
-    app: Rc<RefCell<Hints>>,
-    event: HintsEvent,
+/// Creates a flight loop that keeps the menu in step with the hints for the current aircraft: the
+/// "Show hints" item with the window however it was shown or hidden (by the close button, a
+/// command, loading a saved position and so on), the status item with the number of hints loaded
+/// or why they failed, the startup items with the aircraft's startup visibility, the logging
+/// items and dataref with the current logging preset and the sound items with the page-turn
+/// volume.
+fn create_menu_state_loop(
+    internals: CurrentInternals,
+    session: Rc<RefCell<Session>>,
+    items: MenuItems,
+) -> FlightLoop {
+    let mut log_level_dataref = LogLevelDataref::default();
+    let mut flight_loop = FlightLoop::new(move |state: &mut LoopState| {
+        guard("menu state check", || {
+            let visible = internals.window_visible().unwrap_or(false);
+            if items.toggle.checked() != visible {
+                items.toggle.set_checked(visible);
+            }
+            let hint_count = internals.app().map_or(0, |app| app.borrow().hint_count());
+            let failure = session.borrow().failure.clone();
+            let status_text = match (failure, hint_count) {
+                (Some(failure), _) => format!("Hints disabled: {} (retry)", summarise(&failure)),
+                (None, 0) => "(no hints found)".to_string(),
+                (None, 1) => "(1 hint loaded)".to_string(),
+                (None, count) => format!("({count} hints loaded)"),
+            };
+            let status_text = if is_safe_mode() {
+                format!("{status_text} - safe mode")
+            } else {
+                status_text
+            };
+            if items.status.name() != status_text {
+                items.status.set_name(&status_text);
+            }
+            let startup_visibility = internals.startup_visibility();
+            for (visibility, item) in &items.startup {
+                let checked = startup_visibility == Some(*visibility);
+                if item.checked() != checked {
+                    item.set_checked(checked);
+                }
+            }
+            log_level_dataref.update();
+            let log_preset = current_log_preset();
+            for (preset, item) in &items.logging {
+                let checked = log_preset == Some(*preset);
+                if item.checked() != checked {
+                    item.set_checked(checked);
+                }
+            }
+            let volume = page_turn_volume();
+            for (preset, item) in &items.sound {
+                let checked = (preset.volume() - volume).abs() < f32::EPSILON;
+                if item.checked() != checked {
+                    item.set_checked(checked);
+                }
+            }
+        });
+        state.call_after(MENU_STATE_CHECK_INTERVAL);
+    });
+    flight_loop.schedule_after(MENU_STATE_CHECK_INTERVAL);
+    flight_loop
+}
+
+/// Creates a flight loop that creates the internals requested since the previous frame.
+fn create_internals_loop(session: Rc<RefCell<Session>>) -> FlightLoop {
+    let mut flight_loop = FlightLoop::new(move |_: &mut LoopState| {
+        guard("create internals", || {
+            let mut session = session.borrow_mut();
+            session.create_pending_internals();
+            session.apply_pulled_state();
+        });
+    });
+    flight_loop.schedule_immediate();
+    flight_loop
+}
+
+/// Creates a flight loop that periodically checks whether the aircraft has changed.
+fn create_aircraft_loop(session: Rc<RefCell<Session>>) -> FlightLoop {
+    let mut flight_loop = FlightLoop::new(move |state: &mut LoopState| {
+        guard("aircraft check", || session.borrow_mut().check_aircraft());
+        state.call_after(AIRCRAFT_CHECK_INTERVAL);
+    });
+    flight_loop.schedule_after(AIRCRAFT_CHECK_INTERVAL);
+    flight_loop
 }
 
-impl CommandHandler for EventSendingCommandHandler {
-    fn command_begin(&mut self) {
-        self.app.borrow_mut().handle_hints_event(self.event);
+/// The first line of `reason`, shortened to fit in a menu item.
+fn summarise(reason: &str) -> String {
+    const MAX_CHARS: usize = 60;
+    let line = reason.lines().next().unwrap_or_default();
+    if line.chars().count() > MAX_CHARS {
+        let short: String = line.chars().take(MAX_CHARS).collect();
+        format!("{short}...")
+    } else {
+        line.to_string()
     }
-    fn command_continue(&mut self) {}
-    fn command_end(&mut self) {}
 }
 
-struct ToggleWindowCommandHandler {
-    wrapper: Rc<RefCell<SystemWrapper>>,
-    toggle: Rc<CheckItem>,
+struct StatusMenuClickHandler {
+    session: Rc<RefCell<Session>>,
 }
 
-impl CommandHandler for ToggleWindowCommandHandler {
-    fn command_begin(&mut self) {
-        let new_visibility = self.wrapper.borrow_mut().toggle_hint_window();
-        self.toggle.set_checked(new_visibility);
+impl MenuClickHandler for StatusMenuClickHandler {
+    fn item_clicked(&mut self, _item: &ActionItem) {
+        guard("status menu", || self.session.borrow_mut().retry());
     }
-    fn command_continue(&mut self) {}
-    fn command_end(&mut self) {}
 }
 
 struct ToggleWindowCheckHandler {
-    wrapper: Rc<RefCell<SystemWrapper>>,
+    internals: CurrentInternals,
 }
 
 impl CheckHandler for ToggleWindowCheckHandler {
     fn item_checked(&mut self, _: &CheckItem, checked: bool) {
-        self.wrapper.borrow_mut().set_hint_window_visible(checked);
+        guard("show hints menu", || {
+            self.internals
+                .with_wrapper(|wrapper| wrapper.set_hint_window_visible(checked));
+        });
+    }
+}
+
+struct StartupCheckHandler {
+    internals: CurrentInternals,
+    visibility: StartupVisibility,
+}
+
+impl CheckHandler for StartupCheckHandler {
+    fn item_checked(&mut self, _item: &CheckItem, _checked: bool) {
+        guard("startup menu", || {
+            self.internals
+                .with_wrapper(|wrapper| wrapper.set_startup_visibility(self.visibility));
+        });
+    }
+}
+
+struct LogPresetCheckHandler {
+    preset: LogPreset,
+}
+
+impl CheckHandler for LogPresetCheckHandler {
+    fn item_checked(&mut self, _item: &CheckItem, _checked: bool) {
+        guard("logging menu", || set_log_preset(self.preset));
+    }
+}
+
+struct HotKeysCheckHandler {
+    hot_keys: Rc<RefCell<HotKeys>>,
+}
+
+impl CheckHandler for HotKeysCheckHandler {
+    fn item_checked(&mut self, _item: &CheckItem, checked: bool) {
+        guard("hot keys menu", || {
+            self.hot_keys.borrow_mut().set_enabled(checked)
+        });
+    }
+}
+
+struct PageTurnVolumeCheckHandler {
+    volume: PageTurnVolume,
+}
+
+impl CheckHandler for PageTurnVolumeCheckHandler {
+    fn item_checked(&mut self, _item: &CheckItem, _checked: bool) {
+        guard("sound menu", || set_page_turn_volume(self.volume.volume()));
     }
 }
 
 struct ReloadMenuClickHandler {
-    app: Rc<RefCell<Hints>>,
+    internals: CurrentInternals,
 }
 
 impl MenuClickHandler for ReloadMenuClickHandler {
     fn item_clicked(&mut self, _item: &ActionItem) {
-        self.app.borrow_mut().reload();
+        guard("reload menu", || self.internals.with_app(Hints::reload));
     }
 }
 
-struct LoadCommandHandler {
-    wrapper: Rc<RefCell<SystemWrapper>>,
+struct VariantsMenuClickHandler {
+    internals: CurrentInternals,
 }
 
-impl CommandHandler for LoadCommandHandler {
-    fn command_begin(&mut self) {
-        self.wrapper.borrow_mut().load(false);
+impl MenuClickHandler for VariantsMenuClickHandler {
+    fn item_clicked(&mut self, _item: &ActionItem) {
+        guard("variants menu", || {
+            self.internals
+                .with_app(|app| app.handle_hints_event(HintsEvent::ToggleVariants));
+        });
     }
 }
 
-struct LoadMenuClickHandler {
-    wrapper: Rc<RefCell<SystemWrapper>>,
+struct ImportDocsMenuClickHandler {
+    session: Rc<RefCell<Session>>,
 }
 
-impl MenuClickHandler for LoadMenuClickHandler {
+impl MenuClickHandler for ImportDocsMenuClickHandler {
     fn item_clicked(&mut self, _item: &ActionItem) {
-        self.wrapper.borrow_mut().load(false);
+        guard("import documentation menu", || {
+            self.session.borrow_mut().import_documentation();
+        });
     }
 }
 
-struct SaveCommandHandler {
-    wrapper: Rc<RefCell<SystemWrapper>>,
+struct LoadMenuClickHandler {
+    internals: CurrentInternals,
 }
 
-impl CommandHandler for SaveCommandHandler {
-    fn command_begin(&mut self) {
-        self.wrapper.borrow().save();
+impl MenuClickHandler for LoadMenuClickHandler {
+    fn item_clicked(&mut self, _item: &ActionItem) {
+        guard("load menu", || {
+            self.internals.with_wrapper(|wrapper| wrapper.load(false));
+        });
     }
 }
 
 struct SaveMenuClickHandler {
-    wrapper: Rc<RefCell<SystemWrapper>>,
+    internals: CurrentInternals,
 }
 
 impl MenuClickHandler for SaveMenuClickHandler {
     fn item_clicked(&mut self, _item: &ActionItem) {
-        self.wrapper.borrow().save();
+        guard("save menu", || {
+            self.internals.with_wrapper(|wrapper| wrapper.save());
+        });
     }
 }
 
-struct ResetCommandHandler {
-    wrapper: Rc<RefCell<SystemWrapper>>,
+struct SaveForViewMenuClickHandler {
+    internals: CurrentInternals,
 }
 
-impl CommandHandler for ResetCommandHandler {
-    fn command_begin(&mut self) {
-        self.wrapper.borrow_mut().reset();
+impl MenuClickHandler for SaveForViewMenuClickHandler {
+    fn item_clicked(&mut self, _item: &ActionItem) {
+        guard("save for view menu", || {
+            self.internals
+                .with_wrapper(|wrapper| wrapper.save_for_view(true));
+        });
     }
 }
 
 struct ResetMenuClickHandler {
-    wrapper: Rc<RefCell<SystemWrapper>>,
+    internals: CurrentInternals,
 }
 
 impl MenuClickHandler for ResetMenuClickHandler {
     fn item_clicked(&mut self, _item: &ActionItem) {
-        self.wrapper.borrow_mut().reset();
+        guard("reset menu", || {
+            self.internals.with_wrapper(SystemWrapper::reset);
+        });
+    }
+}
+
+/// The items of the profile menu, which is their owner.
+type ProfileItems = Rc<RefCell<Vec<(Option<String>, Weak<CheckItem>)>>>;
+
+struct ProfileCheckHandler {
+    profile: Option<String>,
+    session: Rc<RefCell<Session>>,
+    items: ProfileItems,
+}
+
+impl CheckHandler for ProfileCheckHandler {
+    fn item_checked(&mut self, item: &CheckItem, _checked: bool) {
+        guard("profile menu", || {
+            if current_profile() == self.profile {
+                // the selected profile stays checked
+                item.set_checked(true);
+                return;
+            }
+            // sync and close the current profile's state before switching
+            self.session.borrow_mut().clear_internals();
+            select_profile(self.profile.clone());
+            for (profile, item) in self.items.borrow().iter() {
+                if let Some(item) = item.upgrade() {
+                    item.set_checked(*profile == self.profile);
+                }
+            }
+            self.session.borrow_mut().profile_changed();
+        });
+    }
+}
+
+struct ExportMenuClickHandler;
+
+impl MenuClickHandler for ExportMenuClickHandler {
+    fn item_clicked(&mut self, _item: &ActionItem) {
+        guard("export menu", export_states);
+    }
+}
+
+struct ImportMenuClickHandler {
+    internals: CurrentInternals,
+}
+
+impl MenuClickHandler for ImportMenuClickHandler {
+    fn item_clicked(&mut self, _item: &ActionItem) {
+        guard("import menu", || import_and_load(&self.internals));
+    }
+}
+
+/// Imports the saved state of all aircraft, then applies the current aircraft's.
+fn import_and_load(internals: &CurrentInternals) {
+    if import_states() {
+        internals.with_wrapper(|wrapper| wrapper.load(false));
     }
 }
 
+/// The hints directory of the current aircraft or, if it has none, its library of hints, which
+/// has hints created for aircraft whose directory is read-only.
 fn find_path() -> Option<PathBuf> {
     let aircraft_path = get_current_aircraft_path().join("hints");
     info!("Looking for hints in {aircraft_path:?}");
+    let library = get_library_directory();
     if aircraft_path.is_dir() {
         Some(aircraft_path)
+    } else if library.is_dir() {
+        info!("Using hints from the library in {library:?}");
+        Some(library)
     } else {
         warn!("No hints found in {aircraft_path:?}");
         None
     }
 }
 
+/// The library of hints for the current aircraft to show along with those in `path`, if it has
+/// one, or if `path` is read-only, in which case it is created.
+fn find_library(path: &Path, read_only: bool) -> Option<PathBuf> {
+    let library = get_library_directory();
+    if library == path || (!read_only && !library.is_dir()) {
+        return None;
+    }
+    if read_only {
+        info!("{path:?} is read-only, so saving hints and settings in {library:?}");
+    }
+    match std::fs::create_dir_all(&library) {
+        Ok(()) => Some(library),
+        Err(e) => {
+            error!("Could not create hints library directory: {e:?}");
+            None
+        }
+    }
+}
+
 fn init_xplane(app: Rc<RefCell<Hints>>) -> System {
     let bounds = imgui_support_xplane::get_screen_bounds();
     let horiz_offset = get_offset_from_edge(bounds.width(), FROM_EDGE_PROPORTION, FROM_EDGE_MIN);
@@ -494,11 +1430,29 @@ impl From<&Mode> for PositioningMode {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct State {
-    mode: Mode,
-    position: Rect,
-    visible: bool,
+const STATE_DOCS: TomlDocs = TomlDocs {
+    header: "The saved position of the hints window for this aircraft.",
+    fields: State::FIELD_DOCS,
+};
+
+const VIEW_LAYOUTS_DOCS: TomlDocs = TomlDocs {
+    header:
+        "Positions of the hints window for this aircraft, loaded when the sim changes to each view.
+Views are quick looks, such as quick_look_3, or other views by X-Plane's view type, such as
+view_type_1026 for the 3-D cockpit.",
+    fields: &[],
+};
+
+hints_common::documented_struct! {
+    #[derive(Debug, Serialize, Deserialize)]
+    struct State {
+        /// How the window is shown: Free (inside X-Plane), PopOut (a separate window) or VR
+        mode: Mode,
+        /// The window's edges in pixels, measured from the bottom left of the screen
+        position: Rect,
+        /// Whether the window is shown: true or false
+        visible: bool,
+    }
 }
 
 impl From<&Ref> for State {
@@ -523,7 +1477,7 @@ fn get_current_aircraft_id() -> String {
 }
 
 fn get_save_directory() -> Option<PathBuf> {
-    let path = get_prefs_path().join("hints");
+    let path = get_profile_directory(current_profile().as_deref());
     match std::fs::create_dir_all(&path) {
         Ok(()) => Some(path),
         Err(e) => {
@@ -533,20 +1487,54 @@ fn get_save_directory() -> Option<PathBuf> {
     }
 }
 
+/// Where the user's settings for the current aircraft's hints are saved. This is only set for a
+/// named profile; the default profile keeps them in the hints directory.
+fn get_settings_directory() -> Option<PathBuf> {
+    current_profile()?;
+    let path = get_save_directory()?.join(get_current_aircraft_id());
+    match std::fs::create_dir_all(&path) {
+        Ok(()) => Some(path),
+        Err(e) => {
+            error!("Could not create hints settings directory: {e:?}");
+            None
+        }
+    }
+}
+
+/// Where hints and settings are kept for the current aircraft when its own directory can't be
+/// written to. This is shared by all profiles, as the hints in the aircraft directory are.
+fn get_library_directory() -> PathBuf {
+    get_profile_directory(None)
+        .join(LIBRARY_DIRNAME)
+        .join(get_current_aircraft_id())
+}
+
 fn get_state_path() -> Option<PathBuf> {
     get_save_directory()
         .map(|save_dir| save_dir.join(format!("{}.toml", get_current_aircraft_id())))
 }
 
+fn get_view_layouts_path() -> Option<PathBuf> {
+    get_save_directory()
+        .map(|save_dir| save_dir.join(format!("{}-views.toml", get_current_aircraft_id())))
+}
+
+/// The window layouts saved for the current aircraft, keyed by view.
+fn read_view_layouts() -> BTreeMap<String, State> {
+    get_view_layouts_path()
+        .and_then(|filename| read_toml(&filename))
+        .unwrap_or_default()
+}
+
 fn init_logging(var: &str, with_thread_names: bool) {
     LOGGING.get_or_init(|| configure_logging(var, with_thread_names));
 }
 
 fn configure_logging(env_var: &str, with_thread_names: bool) {
     let stdout_layer = layer(with_thread_names, None);
-    let xp_layer = layer(with_thread_names, Some(false)).with_writer(|| XplmWrite);
+    let xp_layer = layer(with_thread_names, Some(false)).with_writer(XplmWrite::default);
 
-    let filter = env_filter(Some(env_var));
+    let filter = reloadable_filter(env_filter(Some(env_var)));
     let subscriber = tracing_subscriber::registry()
         .with(filter)
         .with(stdout_layer)
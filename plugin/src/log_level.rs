@@ -0,0 +1,207 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+use std::sync::{Mutex, OnceLock, PoisonError};
+
+use tracing::level_filters::LevelFilter;
+use tracing::{error, info, warn};
+use tracing_subscriber::{reload, EnvFilter, Registry};
+use xplm::data::owned::OwnedData;
+use xplm::data::{ReadWrite, StringRead, StringReadWrite};
+
+static FILTER: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+static SELECTED: Mutex<Option<LogPreset>> = Mutex::new(None);
+
+/// The modules that find and load hint files.
+const LOADER_MODULES: &[&str] = &[
+    "hints_common::app",
+    "hints_common::config",
+    "hints_common::concurrent",
+    "hints_common::hints",
+    "hints_plugin",
+];
+/// The modules that handle key presses, scrolling, commands and datarefs.
+const INPUT_MODULES: &[&str] = &[
+    "hints_common::app",
+    "hints_common::history",
+    "hints_plugin::commands",
+    "hints_plugin::datarefs",
+];
+/// The modules that talk to servers, and the HTTP client they use.
+const NETWORK_MODULES: &[&str] = &[
+    "hints_plugin::charts",
+    "hints_plugin::sync",
+    "hints_plugin::update",
+    "ureq",
+];
+
+/// The logging that can be switched to while X-Plane is running, so that a problem can be
+/// captured without restarting. As well as a level for everything, there are presets that log
+/// one area in detail, so that support can ask for "Loader debug" rather than a filter directive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogPreset {
+    Error,
+    Warn,
+    Info,
+    LoaderDebug,
+    InputDebug,
+    NetworkTrace,
+    Debug,
+    Trace,
+}
+
+impl LogPreset {
+    pub const ALL: [LogPreset; 8] = [
+        Self::Error,
+        Self::Warn,
+        Self::Info,
+        Self::LoaderDebug,
+        Self::InputDebug,
+        Self::NetworkTrace,
+        Self::Debug,
+        Self::Trace,
+    ];
+
+    /// The name used in the `flc/hints/log_level` dataref.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warn => "warn",
+            Self::Info => "info",
+            Self::LoaderDebug => "loader_debug",
+            Self::InputDebug => "input_debug",
+            Self::NetworkTrace => "network_trace",
+            Self::Debug => "debug",
+            Self::Trace => "trace",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Error => "Errors only",
+            Self::Warn => "Quiet",
+            Self::Info => "Normal",
+            Self::LoaderDebug => "Loader debug",
+            Self::InputDebug => "Input debug",
+            Self::NetworkTrace => "Network trace",
+            Self::Debug => "Debug everything",
+            Self::Trace => "Trace everything",
+        }
+    }
+
+    /// The `EnvFilter` directives for the preset.
+    fn directives(self) -> String {
+        let detailed = |modules: &[&str], level: &str| {
+            std::iter::once(String::from("info"))
+                .chain(modules.iter().map(|module| format!("{module}={level}")))
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        match self {
+            Self::LoaderDebug => detailed(LOADER_MODULES, "debug"),
+            Self::InputDebug => detailed(INPUT_MODULES, "debug"),
+            Self::NetworkTrace => detailed(NETWORK_MODULES, "trace"),
+            level => level.name().to_string(),
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|preset| preset.name().eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// The preset that logs everything at `filter`, if there is one.
+    fn from_filter(filter: LevelFilter) -> Option<Self> {
+        let name = filter.to_string().to_ascii_lowercase();
+        Self::ALL
+            .into_iter()
+            .filter(|preset| preset.directives() == preset.name())
+            .find(|preset| preset.name() == name)
+    }
+}
+
+/// Wraps `filter` so that it can be replaced by [`set_log_preset`].
+pub fn reloadable_filter(filter: EnvFilter) -> reload::Layer<EnvFilter, Registry> {
+    let (layer, handle) = reload::Layer::new(filter);
+    if FILTER.set(handle).is_err() {
+        warn!("Log level can already be changed");
+    }
+    layer
+}
+
+/// The preset last selected or, before one has been, the level for everything set by the
+/// environment.
+pub fn current_log_preset() -> Option<LogPreset> {
+    let selected = *SELECTED.lock().unwrap_or_else(PoisonError::into_inner);
+    selected.or_else(|| {
+        FILTER
+            .get()?
+            .with_current(|filter| filter.max_level_hint())
+            .ok()
+            .flatten()
+            .and_then(LogPreset::from_filter)
+    })
+}
+
+/// Replaces the filter, including any directives from the environment, with the preset's.
+pub fn set_log_preset(preset: LogPreset) {
+    let handle = match FILTER.get() {
+        Some(handle) => handle,
+        None => return,
+    };
+    let directives = preset.directives();
+    match handle.reload(EnvFilter::new(&directives)) {
+        Ok(()) => {
+            *SELECTED.lock().unwrap_or_else(PoisonError::into_inner) = Some(preset);
+            info!(directives, "Logging set to {}", preset.label());
+        }
+        Err(e) => error!("Unable to change logging: {e}"),
+    }
+}
+
+/// `flc/hints/log_level` publishes the name of the current preset and changes it when another
+/// plugin writes the name of a preset to it, such as `debug` or `loader_debug`.
+pub struct LogLevelDataref {
+    level: OwnedData<[u8], ReadWrite>,
+    published: String,
+}
+
+impl Default for LogLevelDataref {
+    fn default() -> Self {
+        LogLevelDataref {
+            level: OwnedData::create("flc/hints/log_level")
+                .expect("Unable to create log level dataref"),
+            published: String::new(),
+        }
+    }
+}
+
+impl LogLevelDataref {
+    pub fn update(&mut self) {
+        if let Ok(requested) = self.level.get_as_string() {
+            if requested != self.published {
+                match LogPreset::from_name(&requested) {
+                    Some(preset) => set_log_preset(preset),
+                    None => {
+                        warn!(
+                            "Ignoring invalid level {requested:?} written to flc/hints/log_level"
+                        );
+                    }
+                }
+                // republishes the level if the one written was invalid
+                self.published = requested;
+            }
+        }
+        let current = current_log_preset().map_or("", LogPreset::name);
+        if self.published != current {
+            match self.level.set_as_string(current) {
+                Ok(()) => self.published = current.to_string(),
+                Err(e) => warn!("Unable to publish log preset {current:?}: {e:?}"),
+            }
+        }
+    }
+}
@@ -0,0 +1,160 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+//! Stand-ins for the X-Plane SDK functions used by [`crate::utils`], so that they can be tested
+//! without a running simulator. The functions are exported under the SDK's own names, and so
+//! replace the real ones when the `mock-xplm` feature is enabled.
+//!
+//! X-Plane only calls plugins on its main thread, so each test thread has its own simulated
+//! X-Plane, set up with [`set_prefs_path`], [`load_aircraft`] and so on.
+
+#![allow(non_snake_case, clippy::cast_possible_wrap, clippy::missing_safety_doc)]
+
+use std::cell::RefCell;
+use std::ffi::{c_char, c_int, c_void, CStr, CString};
+use std::ptr;
+
+use xplm_sys::{xplmType_Data, XPLMDataRef, XPLMDataTypeID, XPLMLanguageCode};
+
+const ICAO_DATAREF: &str = "sim/aircraft/view/acf_ICAO";
+/// Its address stands for the ICAO dataref, which is the only one the mock knows.
+static ICAO_DATAREF_ID: u8 = 0;
+
+#[derive(Default)]
+struct Sim {
+    prefs_path: String,
+    aircraft_path: String,
+    icao: String,
+    language: XPLMLanguageCode,
+    log: String,
+}
+
+thread_local! {
+    static SIM: RefCell<Sim> = RefCell::new(Sim::default());
+}
+
+/// Sets the full path of X-Plane's preferences file, as returned by `XPLMGetPrefsPath`.
+pub fn set_prefs_path(path: &str) {
+    SIM.with(|sim| sim.borrow_mut().prefs_path = path.to_string());
+}
+
+/// Loads the aircraft at `acf_path`, the full path of its `.acf` file, with the given ICAO code.
+pub fn load_aircraft(acf_path: &str, icao: &str) {
+    SIM.with(|sim| {
+        let mut sim = sim.borrow_mut();
+        sim.aircraft_path = acf_path.to_string();
+        sim.icao = icao.to_string();
+    });
+}
+
+/// Sets the language X-Plane is running in, as returned by `XPLMGetLanguage`.
+pub fn set_language(language: XPLMLanguageCode) {
+    SIM.with(|sim| sim.borrow_mut().language = language);
+}
+
+/// Returns and clears everything written to `Log.txt`.
+pub fn take_log() -> String {
+    SIM.with(|sim| std::mem::take(&mut sim.borrow_mut().log))
+}
+
+unsafe fn write_string(value: &str, out: *mut c_char) {
+    if out.is_null() {
+        return;
+    }
+    let value = CString::new(value).expect("Mock strings contain no NULs");
+    let bytes = value.as_bytes_with_nul();
+    ptr::copy_nonoverlapping(bytes.as_ptr().cast(), out, bytes.len());
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn XPLMGetPrefsPath(outPrefsPath: *mut c_char) {
+    SIM.with(|sim| write_string(&sim.borrow().prefs_path, outPrefsPath));
+}
+
+/// Splits the full path in place at its last separator, leaving the directory and returning the
+/// file name, as X-Plane does.
+#[no_mangle]
+pub unsafe extern "C" fn XPLMExtractFileAndPath(inFullPath: *mut c_char) -> *mut c_char {
+    let full_path = CStr::from_ptr(inFullPath).to_bytes();
+    match full_path.iter().rposition(|&b| b == b'/') {
+        Some(separator) => {
+            *inFullPath.add(separator) = 0;
+            inFullPath.add(separator + 1)
+        }
+        None => inFullPath.add(full_path.len()),
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn XPLMGetNthAircraftModel(
+    inIndex: c_int,
+    outFileName: *mut c_char,
+    outPath: *mut c_char,
+) {
+    assert_eq!(inIndex, 0, "Only the user's aircraft is simulated");
+    SIM.with(|sim| {
+        let sim = sim.borrow();
+        let filename = sim.aircraft_path.rsplit('/').next().unwrap_or_default();
+        write_string(filename, outFileName);
+        write_string(&sim.aircraft_path, outPath);
+    });
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn XPLMGetLanguage() -> XPLMLanguageCode {
+    SIM.with(|sim| sim.borrow().language)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn XPLMDebugString(inString: *const c_char) {
+    let message = CStr::from_ptr(inString).to_string_lossy();
+    SIM.with(|sim| sim.borrow_mut().log.push_str(&message));
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn XPLMFindDataRef(inDataRefName: *const c_char) -> XPLMDataRef {
+    if CStr::from_ptr(inDataRefName).to_bytes() == ICAO_DATAREF.as_bytes() {
+        ptr::addr_of!(ICAO_DATAREF_ID).cast_mut().cast()
+    } else {
+        ptr::null_mut()
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn XPLMGetDataRefTypes(_inDataRef: XPLMDataRef) -> XPLMDataTypeID {
+    xplmType_Data as XPLMDataTypeID
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn XPLMCanWriteDataRef(_inDataRef: XPLMDataRef) -> c_int {
+    0
+}
+
+/// Reads the ICAO code, returning its length if `outValue` is null.
+#[no_mangle]
+pub unsafe extern "C" fn XPLMGetDatab(
+    _inDataRef: XPLMDataRef,
+    outValue: *mut c_void,
+    inOffset: c_int,
+    inMaxBytes: c_int,
+) -> c_int {
+    SIM.with(|sim| {
+        let sim = sim.borrow();
+        let bytes = sim.icao.as_bytes();
+        let offset = usize::try_from(inOffset)
+            .unwrap_or_default()
+            .min(bytes.len());
+        let bytes = &bytes[offset..];
+        if outValue.is_null() {
+            return c_int::try_from(bytes.len()).unwrap_or(c_int::MAX);
+        }
+        let count = bytes
+            .len()
+            .min(usize::try_from(inMaxBytes).unwrap_or_default());
+        ptr::copy_nonoverlapping(bytes.as_ptr(), outValue.cast(), count);
+        c_int::try_from(count).unwrap_or(c_int::MAX)
+    })
+}
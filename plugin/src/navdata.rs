@@ -0,0 +1,305 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::{c_char, CStr, CString};
+use std::ptr;
+
+use tracing::debug;
+use xplm::data::borrowed::DataRef;
+use xplm::data::DataRead;
+use xplm_sys::{
+    xplm_Nav_Airport, xplm_Nav_DME, xplm_Nav_ILS, xplm_Nav_Localizer, xplm_Nav_NDB, xplm_Nav_VOR,
+    XPLMCountFMSEntries, XPLMFindNavAid, XPLMGetFMSEntryInfo, XPLMGetFirstNavAid,
+    XPLMGetNavAidInfo, XPLMGetNextNavAid, XPLMNavRef, XPLMNavType, XPLM_NAV_NOT_FOUND,
+};
+
+use hints_common::{Airport, NavData, Navaid};
+
+/// How far from an airport navaids are considered to belong to it.
+const NAVAID_RANGE_NM: f32 = 10.0;
+const METRES_PER_FOOT: f32 = 0.3048;
+const NM_PER_DEGREE: f32 = 60.0;
+
+/// Looks up airports and their navaids using the X-Plane navigation API.
+#[derive(Default)]
+pub struct SimNavData {
+    /// The radio navaids, indexed the first time an airport is looked up.
+    index: RefCell<Option<NavaidIndex>>,
+}
+
+#[derive(Clone)]
+struct NavaidInfo {
+    nav_type: XPLMNavType,
+    latitude: f32,
+    longitude: f32,
+    height: f32,
+    frequency: i32,
+    heading: f32,
+    id: String,
+    name: String,
+}
+
+impl NavData for SimNavData {
+    fn find_airport(&self, ident: &str) -> Option<Airport> {
+        let id = CString::new(ident).ok()?;
+        let nav_ref = unsafe {
+            XPLMFindNavAid(
+                ptr::null(),
+                id.as_ptr(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                xplm_Nav_Airport as XPLMNavType,
+            )
+        };
+        // the identifier is matched as a fragment, so check that the match is exact
+        get_info(nav_ref)
+            .filter(|info| info.id.eq_ignore_ascii_case(ident))
+            .map(|info| self.airport(info))
+    }
+
+    fn nearest_airport(&self) -> Option<Airport> {
+        #[allow(clippy::cast_possible_truncation)]
+        let (mut latitude, mut longitude) = (
+            read_position("sim/flightmodel/position/latitude")? as f32,
+            read_position("sim/flightmodel/position/longitude")? as f32,
+        );
+        let nav_ref = unsafe {
+            XPLMFindNavAid(
+                ptr::null(),
+                ptr::null(),
+                &mut latitude,
+                &mut longitude,
+                ptr::null_mut(),
+                xplm_Nav_Airport as XPLMNavType,
+            )
+        };
+        get_info(nav_ref).map(|info| self.airport(info))
+    }
+
+    /// The airports entered in the FMS, usually the origin and destination.
+    fn flight_plan_airports(&self) -> Vec<String> {
+        let count = unsafe { XPLMCountFMSEntries() };
+        let mut airports: Vec<String> = vec![];
+        for idx in 0..count {
+            let mut nav_type: XPLMNavType = 0;
+            let mut id = [0 as c_char; 32];
+            let ident = unsafe {
+                XPLMGetFMSEntryInfo(
+                    idx,
+                    &mut nav_type,
+                    id.as_mut_ptr(),
+                    ptr::null_mut(),
+                    ptr::null_mut(),
+                    ptr::null_mut(),
+                    ptr::null_mut(),
+                );
+                CStr::from_ptr(id.as_ptr()).to_string_lossy().into_owned()
+            };
+            if nav_type == xplm_Nav_Airport as XPLMNavType
+                && !ident.is_empty()
+                && !airports.contains(&ident)
+            {
+                airports.push(ident);
+            }
+        }
+        airports
+    }
+}
+
+fn read_position(name: &str) -> Option<f64> {
+    DataRef::<f64>::find(name).ok().map(|dataref| dataref.get())
+}
+
+impl SimNavData {
+    fn airport(&self, info: NavaidInfo) -> Airport {
+        let mut index = self.index.borrow_mut();
+        let mut navaids = index.get_or_insert_with(NavaidIndex::build).near(&info);
+        navaids.sort_by(|a, b| a.nav_type.cmp(&b.nav_type).then(a.id.cmp(&b.id)));
+        Airport {
+            ident: info.id,
+            name: info.name,
+            elevation_ft: info.height / METRES_PER_FOOT,
+            navaids: navaids.into_iter().map(navaid).collect(),
+        }
+    }
+}
+
+/// The radio navaids in the sim's database by the whole degrees of latitude and longitude they are
+/// in, so that the navaids near an airport can be found without walking the whole database,
+/// which takes long enough to make the sim stutter.
+struct NavaidIndex(HashMap<(i32, i32), Vec<NavaidInfo>>);
+
+impl NavaidIndex {
+    fn build() -> Self {
+        let navaid_types =
+            (xplm_Nav_NDB | xplm_Nav_VOR | xplm_Nav_ILS | xplm_Nav_Localizer | xplm_Nav_DME)
+                as XPLMNavType;
+        let mut cells: HashMap<(i32, i32), Vec<NavaidInfo>> = HashMap::new();
+        let mut nav_ref = unsafe { XPLMGetFirstNavAid() };
+        while nav_ref != XPLM_NAV_NOT_FOUND {
+            if let Some(navaid) = get_info(nav_ref) {
+                if navaid.nav_type & navaid_types != 0 {
+                    cells
+                        .entry(cell(navaid.latitude, navaid.longitude))
+                        .or_default()
+                        .push(navaid);
+                }
+            }
+            nav_ref = unsafe { XPLMGetNextNavAid(nav_ref) };
+        }
+        debug!(cells = cells.len(), "Indexed radio navaids");
+        NavaidIndex(cells)
+    }
+
+    /// The navaids close enough to `airport` to belong to it.
+    fn near(&self, airport: &NavaidInfo) -> Vec<NavaidInfo> {
+        cells_near(airport.latitude, airport.longitude)
+            .iter()
+            .filter_map(|cell| self.0.get(cell))
+            .flatten()
+            .filter(|navaid| distance_nm(airport, navaid) <= NAVAID_RANGE_NM)
+            .cloned()
+            .collect()
+    }
+}
+
+/// The whole degrees of latitude and longitude containing a position.
+#[allow(clippy::cast_possible_truncation)]
+fn cell(latitude: f32, longitude: f32) -> (i32, i32) {
+    (latitude.floor() as i32, longitude.floor() as i32)
+}
+
+/// The cells that navaids within range of a position can be in. A degree of latitude is always
+/// wider than the range, but degrees of longitude narrow towards the poles.
+#[allow(clippy::cast_possible_truncation)]
+fn cells_near(latitude: f32, longitude: f32) -> Vec<(i32, i32)> {
+    let (latitude_cell, longitude_cell) = cell(latitude, longitude);
+    let degree_nm = NM_PER_DEGREE * latitude.to_radians().cos();
+    let longitude_span = if degree_nm > NAVAID_RANGE_NM / 180.0 {
+        ((NAVAID_RANGE_NM / degree_nm).ceil() as i32).min(180)
+    } else {
+        180
+    };
+    let mut cells = vec![];
+    for latitude_offset in -1..=1 {
+        for longitude_offset in -longitude_span..=longitude_span {
+            let longitude = (longitude_cell + longitude_offset + 180).rem_euclid(360) - 180;
+            let cell = (latitude_cell + latitude_offset, longitude);
+            if !cells.contains(&cell) {
+                cells.push(cell);
+            }
+        }
+    }
+    cells
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn navaid(info: NavaidInfo) -> Navaid {
+    let (kind, frequency, course) = match info.nav_type as u32 {
+        t if t == xplm_Nav_NDB => ("NDB", format!("{} kHz", info.frequency), None),
+        t if t == xplm_Nav_ILS => (
+            "ILS",
+            format!("{:.2} MHz", info.frequency as f32 / 100.0),
+            Some(info.heading),
+        ),
+        t if t == xplm_Nav_Localizer => (
+            "LOC",
+            format!("{:.2} MHz", info.frequency as f32 / 100.0),
+            Some(info.heading),
+        ),
+        t if t == xplm_Nav_DME => (
+            "DME",
+            format!("{:.2} MHz", info.frequency as f32 / 100.0),
+            None,
+        ),
+        _ => (
+            "VOR",
+            format!("{:.2} MHz", info.frequency as f32 / 100.0),
+            None,
+        ),
+    };
+    Navaid {
+        kind,
+        ident: info.id,
+        name: info.name,
+        frequency,
+        course,
+    }
+}
+
+fn distance_nm(from: &NavaidInfo, to: &NavaidInfo) -> f32 {
+    let latitude = (to.latitude - from.latitude) * NM_PER_DEGREE;
+    let longitude =
+        (to.longitude - from.longitude) * NM_PER_DEGREE * from.latitude.to_radians().cos();
+    latitude.hypot(longitude)
+}
+
+fn get_info(nav_ref: XPLMNavRef) -> Option<NavaidInfo> {
+    if nav_ref == XPLM_NAV_NOT_FOUND {
+        return None;
+    }
+    let mut info = NavaidInfo {
+        nav_type: 0,
+        latitude: 0.0,
+        longitude: 0.0,
+        height: 0.0,
+        frequency: 0,
+        heading: 0.0,
+        id: String::new(),
+        name: String::new(),
+    };
+    let mut id = [0 as c_char; 32];
+    let mut name = [0 as c_char; 256];
+    unsafe {
+        XPLMGetNavAidInfo(
+            nav_ref,
+            &mut info.nav_type,
+            &mut info.latitude,
+            &mut info.longitude,
+            &mut info.height,
+            &mut info.frequency,
+            &mut info.heading,
+            id.as_mut_ptr(),
+            name.as_mut_ptr(),
+            ptr::null_mut(),
+        );
+        info.id = CStr::from_ptr(id.as_ptr()).to_string_lossy().into_owned();
+        info.name = CStr::from_ptr(name.as_ptr()).to_string_lossy().into_owned();
+    }
+    Some(info)
+}
+
+#[cfg(all(test, feature = "mock-xplm"))]
+mod tests {
+    use super::cells_near;
+
+    #[test]
+    fn looks_in_neighbouring_cells() {
+        let cells = cells_near(51.47, -0.45);
+        assert_eq!(cells.len(), 9);
+        assert!(cells.contains(&(51, -1)));
+        assert!(cells.contains(&(52, 0)));
+        assert!(cells.contains(&(50, -2)));
+    }
+
+    #[test]
+    fn looks_wider_towards_the_poles() {
+        let cells = cells_near(85.2, 15.5);
+        assert!(cells.contains(&(85, 13)));
+        assert!(cells.contains(&(85, 17)));
+        assert_eq!(cells.len(), 15);
+    }
+
+    #[test]
+    fn wraps_around_the_antimeridian() {
+        let cells = cells_near(-17.75, 179.5);
+        assert!(cells.contains(&(-18, -180)));
+        assert!(cells.contains(&(-18, 178)));
+    }
+}
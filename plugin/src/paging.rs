@@ -0,0 +1,80 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::settings::load_settings;
+use hints_common::HintsEvent;
+
+const PAGING_FILENAME: &str = "paging.toml";
+
+/// Paging acceleration for rotary encoders, set in `paging.toml` in `Output/preferences/hints`.
+/// While the next and previous commands are triggered within `accelerate_within_ms` of each
+/// other, such as by spinning an encoder quickly, each detent turns 10 hints rather than one.
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct PagingAcceleration {
+    accelerate_within_ms: u64,
+}
+
+impl PagingAcceleration {
+    pub fn load() -> Self {
+        load_settings(PAGING_FILENAME)
+    }
+
+    /// The event to send for `event`, `since_last` after the previous detent, turning 10 hints
+    /// rather than one if the detents are coming quickly enough.
+    pub fn accelerate(self, event: HintsEvent, since_last: Option<Duration>) -> HintsEvent {
+        let fast = since_last.is_some_and(|since_last| {
+            since_last.as_millis() < u128::from(self.accelerate_within_ms)
+        });
+        match event {
+            HintsEvent::NextHint if fast => HintsEvent::NextTenHints,
+            HintsEvent::PreviousHint if fast => HintsEvent::PreviousTenHints,
+            event => event,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "mock-xplm"))]
+mod tests {
+    use std::time::Duration;
+
+    use hints_common::HintsEvent;
+
+    use super::PagingAcceleration;
+
+    fn is_accelerated(acceleration: PagingAcceleration, since_last: Option<Duration>) -> bool {
+        matches!(
+            acceleration.accelerate(HintsEvent::NextHint, since_last),
+            HintsEvent::NextTenHints
+        )
+    }
+
+    #[test]
+    fn accelerates_fast_detents() {
+        let acceleration = PagingAcceleration {
+            accelerate_within_ms: 100,
+        };
+        assert!(is_accelerated(
+            acceleration,
+            Some(Duration::from_millis(60))
+        ));
+        assert!(!is_accelerated(
+            acceleration,
+            Some(Duration::from_millis(150))
+        ));
+        assert!(!is_accelerated(acceleration, None));
+    }
+
+    #[test]
+    fn does_not_accelerate_by_default() {
+        let acceleration = PagingAcceleration::default();
+        assert!(!is_accelerated(acceleration, Some(Duration::ZERO)));
+    }
+}
@@ -0,0 +1,138 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+use std::ffi::{c_int, c_void};
+use std::ptr::addr_of_mut;
+
+use imgui_support::geometry::Rect;
+use serde::Deserialize;
+use xplm_sys::XPLMGetAllMonitorBoundsOS;
+
+use crate::settings::load_settings;
+
+const POP_OUT_FILENAME: &str = "pop-out.toml";
+/// How many frames to wait after the aircraft is loaded before placing a popped out window, as
+/// X-Plane only creates its OS window once it has been drawn.
+pub const POP_OUT_DELAY_FRAMES: u32 = 3;
+
+/// Where to put the hints window when it is popped out as the aircraft loads, set in
+/// `pop-out.toml` in `Output/preferences/hints`, so that checklists on another
+/// monitor come back there even if the monitors have moved. The window goes in the top left
+/// corner of monitor `monitor`, counting from 0 as X-Plane does, at its saved size unless `width`
+/// and `height` are set.
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct PopOutPlacement {
+    monitor: Option<i32>,
+    width: Option<i32>,
+    height: Option<i32>,
+}
+
+impl PopOutPlacement {
+    pub fn load() -> Self {
+        load_settings(POP_OUT_FILENAME)
+    }
+
+    /// Whether popped out windows are placed at all.
+    pub fn is_set(self) -> bool {
+        self.monitor.is_some()
+    }
+
+    /// The geometry to give a popped out window at `saved`, or `None` if the monitor isn't
+    /// connected.
+    pub fn place(self, saved: &Rect) -> Option<Rect> {
+        let bounds = monitor_bounds(self.monitor?)?;
+        Some(self.fit(&bounds, saved))
+    }
+
+    /// The geometry in the top left corner of a monitor with `bounds`, no bigger than it.
+    fn fit(self, bounds: &Rect, saved: &Rect) -> Rect {
+        let width = self.width.unwrap_or(saved.right - saved.left);
+        let height = self.height.unwrap_or(saved.top - saved.bottom);
+        Rect {
+            left: bounds.left,
+            top: bounds.top,
+            right: (bounds.left + width).min(bounds.right),
+            bottom: (bounds.top - height).max(bounds.bottom),
+        }
+    }
+}
+
+/// The bounds of monitor `index` in OS coordinates, if it is connected.
+fn monitor_bounds(index: i32) -> Option<Rect> {
+    let mut monitors: Vec<(i32, Rect)> = vec![];
+    unsafe {
+        XPLMGetAllMonitorBoundsOS(Some(receive_monitor_bounds), addr_of_mut!(monitors).cast());
+    }
+    monitors
+        .into_iter()
+        .find(|(monitor, _)| *monitor == index)
+        .map(|(_, bounds)| bounds)
+}
+
+unsafe extern "C" fn receive_monitor_bounds(
+    index: c_int,
+    left: c_int,
+    top: c_int,
+    right: c_int,
+    bottom: c_int,
+    refcon: *mut c_void,
+) {
+    let monitors = &mut *refcon.cast::<Vec<(i32, Rect)>>();
+    monitors.push((
+        index,
+        Rect {
+            left,
+            top,
+            right,
+            bottom,
+        },
+    ));
+}
+
+#[cfg(all(test, feature = "mock-xplm"))]
+mod tests {
+    use imgui_support::geometry::Rect;
+
+    use super::PopOutPlacement;
+
+    fn rect(left: i32, top: i32, right: i32, bottom: i32) -> Rect {
+        Rect {
+            left,
+            top,
+            right,
+            bottom,
+        }
+    }
+
+    #[test]
+    fn places_in_the_top_left_of_the_monitor() {
+        let placement = PopOutPlacement {
+            monitor: Some(1),
+            width: None,
+            height: Some(600),
+        };
+        let placed = placement.fit(&rect(1920, 1080, 3840, 0), &rect(10, 500, 410, 100));
+        assert_eq!(
+            [placed.left, placed.top, placed.right, placed.bottom],
+            [1920, 1080, 2320, 480]
+        );
+    }
+
+    #[test]
+    fn fits_on_the_monitor() {
+        let placement = PopOutPlacement {
+            monitor: Some(1),
+            width: Some(5000),
+            height: Some(5000),
+        };
+        let placed = placement.fit(&rect(1920, 1080, 3840, 0), &rect(10, 500, 410, 100));
+        assert_eq!(
+            [placed.left, placed.top, placed.right, placed.bottom],
+            [1920, 1080, 3840, 0]
+        );
+    }
+}
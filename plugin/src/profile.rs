@@ -0,0 +1,98 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+use std::path::PathBuf;
+use std::sync::{Mutex, PoisonError};
+
+use tracing::{error, info};
+
+use crate::utils::get_prefs_path;
+
+const PROFILE_ENV_VAR: &str = "HINTS_PROFILE";
+const PROFILE_FILENAME: &str = "hints-profile.txt";
+const PROFILES_DIRNAME: &str = "profiles";
+
+/// The selected user profile, or `None` for the default profile. Each profile has its own saved
+/// state and settings, so that several people can share one computer.
+static PROFILE: Mutex<Option<String>> = Mutex::new(None);
+
+/// Selects the profile named by the `HINTS_PROFILE` environment variable or, if that is not set,
+/// the profile last selected from the menu.
+pub fn init_profile() {
+    let profile = std::env::var(PROFILE_ENV_VAR)
+        .ok()
+        .or_else(|| std::fs::read_to_string(get_profile_path()).ok())
+        .map(|profile| profile.trim().to_string())
+        .filter(|profile| is_valid(profile));
+    info!("Using hints profile {}", display_name(profile.as_deref()));
+    *PROFILE.lock().unwrap_or_else(PoisonError::into_inner) = profile;
+}
+
+pub fn current_profile() -> Option<String> {
+    PROFILE
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+}
+
+/// Selects `profile` and remembers it for next time.
+pub fn select_profile(profile: Option<String>) {
+    info!(
+        "Selected hints profile {}",
+        display_name(profile.as_deref())
+    );
+    let path = get_profile_path();
+    let result = match &profile {
+        Some(profile) => std::fs::write(&path, profile),
+        None if path.is_file() => std::fs::remove_file(&path),
+        None => Ok(()),
+    };
+    if let Err(e) = result {
+        error!("Unable to save selected profile to {path:?}: {e}");
+    }
+    *PROFILE.lock().unwrap_or_else(PoisonError::into_inner) = profile;
+}
+
+/// The profiles that have been created, as directories within `Output/preferences/hints/profiles`.
+pub fn list_profiles() -> Vec<String> {
+    let mut profiles: Vec<String> = std::fs::read_dir(get_profiles_directory())
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .filter(|entry| entry.path().is_dir())
+                .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                .filter(|profile| is_valid(profile))
+                .collect()
+        })
+        .unwrap_or_default();
+    profiles.sort();
+    profiles
+}
+
+/// The directory in which the saved state of `profile` is kept.
+pub fn get_profile_directory(profile: Option<&str>) -> PathBuf {
+    let path = get_prefs_path().join("hints");
+    match profile {
+        Some(profile) => path.join(PROFILES_DIRNAME).join(profile),
+        None => path,
+    }
+}
+
+pub fn display_name(profile: Option<&str>) -> &str {
+    profile.unwrap_or("Default")
+}
+
+fn get_profiles_directory() -> PathBuf {
+    get_prefs_path().join("hints").join(PROFILES_DIRNAME)
+}
+
+fn get_profile_path() -> PathBuf {
+    get_prefs_path().join(PROFILE_FILENAME)
+}
+
+fn is_valid(profile: &str) -> bool {
+    !profile.is_empty() && !profile.starts_with('.') && !profile.contains(['/', '\\'])
+}
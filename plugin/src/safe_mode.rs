@@ -0,0 +1,69 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+use tracing::warn;
+
+use crate::settings::load_settings;
+
+const SAFE_MODE_ENV_VAR: &str = "HINTS_SAFE_MODE";
+const SAFE_MODE_FILENAME: &str = "safe-mode.toml";
+/// What safe mode turns off, for the diagnosis and the log.
+pub const SAFE_MODE_DISABLED: &str =
+    "network, file watcher, rules, PDFs and image look-ahead disabled";
+
+static SAFE_MODE: OnceLock<bool> = OnceLock::new();
+
+/// Whether to start in safe mode, set with `enabled = true` in `safe-mode.toml` in
+/// `Output/preferences/hints`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct SafeModeConfig {
+    enabled: bool,
+}
+
+/// Decides whether the plugin runs in safe mode, in which the subsystems that most often cause
+/// problems are turned off so that users can tell whether one of them is to blame. Safe mode is
+/// on if the `HINTS_SAFE_MODE` environment variable is set to `1`, `true`, `yes` or `on`, or if
+/// it is enabled in `safe-mode.toml`. The environment variable takes precedence.
+pub fn init_safe_mode() {
+    let enabled = match std::env::var(SAFE_MODE_ENV_VAR) {
+        Ok(value) => is_enabled(&value),
+        Err(_) => load_settings::<SafeModeConfig>(SAFE_MODE_FILENAME).enabled,
+    };
+    if enabled {
+        warn!("Starting hints in safe mode: {SAFE_MODE_DISABLED}");
+    }
+    let _ = SAFE_MODE.set(enabled);
+}
+
+pub fn is_safe_mode() -> bool {
+    SAFE_MODE.get().copied().unwrap_or(false)
+}
+
+fn is_enabled(value: &str) -> bool {
+    matches!(
+        value.trim().to_lowercase().as_str(),
+        "1" | "true" | "yes" | "on"
+    )
+}
+
+#[cfg(all(test, feature = "mock-xplm"))]
+mod tests {
+    use super::is_enabled;
+
+    #[test]
+    fn reads_environment_variable() {
+        assert!(is_enabled("1"));
+        assert!(is_enabled(" TRUE\n"));
+        assert!(is_enabled("yes"));
+        assert!(!is_enabled("0"));
+        assert!(!is_enabled("false"));
+        assert!(!is_enabled(""));
+    }
+}
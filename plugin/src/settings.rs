@@ -0,0 +1,73 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+//! Reading and writing the plugin's own settings files, which are kept in
+//! `Output/preferences/hints` along with the saved window state.
+
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tracing::{error, info};
+
+use crate::profile::get_profile_directory;
+use hints_common::{read_toml_file, write_toml_file, TomlDocs};
+
+/// Where the settings file `filename` is kept. Settings apply to every profile.
+pub fn settings_path(filename: &str) -> PathBuf {
+    get_profile_directory(None).join(filename)
+}
+
+/// The settings in `filename`, or `None` if the user hasn't created the file or it can't be read.
+pub fn read_settings<T: DeserializeOwned>(filename: &str) -> Option<T> {
+    read_toml(&settings_path(filename))
+}
+
+/// The settings in `filename`, or the defaults if there aren't any.
+pub fn load_settings<T: DeserializeOwned + Default>(filename: &str) -> T {
+    read_settings(filename).unwrap_or_default()
+}
+
+/// Saves `value` to the settings file `filename`, with the comments in `docs`.
+pub fn save_settings<T: Serialize>(filename: &str, value: &T, docs: &TomlDocs) {
+    let path = settings_path(filename);
+    if let Some(dir) = path.parent() {
+        if let Err(e) = std::fs::create_dir_all(dir) {
+            error!("Unable to create {dir:?}: {e}");
+            return;
+        }
+    }
+    write_toml(&path, value, docs);
+}
+
+/// The TOML in `path`, or `None` if there is no such file. Errors reading or parsing the file are
+/// logged.
+pub fn read_toml<T: DeserializeOwned>(path: &Path) -> Option<T> {
+    if !path.is_file() {
+        return None;
+    }
+    match read_toml_file(path) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            error!(code = e.code(), "Unable to read from {path:?}: {e}");
+            None
+        }
+    }
+}
+
+/// Writes `value` to `path`, logging whether it was saved.
+pub fn write_toml<T: Serialize>(path: &Path, value: &T, docs: &TomlDocs) -> bool {
+    match write_toml_file(path, value, docs) {
+        Ok(()) => {
+            info!("Saved {path:?}");
+            true
+        }
+        Err(e) => {
+            error!(code = e.code(), "Unable to save {path:?}: {e}");
+            false
+        }
+    }
+}
@@ -0,0 +1,177 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+use std::cell::RefCell;
+use std::ffi::c_void;
+use std::ptr;
+use std::rc::Rc;
+use std::sync::{Mutex, OnceLock, PoisonError};
+
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+use xplm_sys::{
+    xplm_AudioUI, XPLMAudioBus, XPLMPlayPCMOnBus, XPLMSetAudioVolume, FMOD_SOUND_FORMAT_PCM16,
+};
+
+use crate::settings::{load_settings, save_settings};
+use hints_common::{Hints, TomlDocs};
+
+const SOUND_FILENAME: &str = "sound.toml";
+const SOUND_DOCS: TomlDocs = TomlDocs {
+    header: "Sound effects played by FLC Hints.",
+    fields: SoundSettings::FIELD_DOCS,
+};
+
+const SAMPLE_RATE: i32 = 22_050;
+/// The length of the page-turn sound, about 90 ms.
+const PAGE_TURN_SAMPLES: usize = 2_000;
+
+/// The volume of the page-turn sound, loaded from `sound.toml` when first needed.
+static VOLUME: Mutex<Option<f32>> = Mutex::new(None);
+/// The page-turn sound as 16-bit mono PCM, created when first played and kept for X-Plane to play
+/// from for as long as the plugin is loaded.
+static PAGE_TURN_PCM: OnceLock<Vec<i16>> = OnceLock::new();
+
+hints_common::documented_struct! {
+    #[derive(Debug, Default, Serialize, Deserialize)]
+    #[serde(default)]
+    struct SoundSettings {
+        /// Volume of the sound played when the hint changes, from 0.0 (off, the default) to 1.0
+        page_turn_volume: f32,
+    }
+}
+
+/// The volumes that can be chosen from the menu. Others can be set in `sound.toml`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PageTurnVolume {
+    Off,
+    Quiet,
+    Medium,
+    Loud,
+}
+
+impl PageTurnVolume {
+    pub const ALL: [PageTurnVolume; 4] = [Self::Off, Self::Quiet, Self::Medium, Self::Loud];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Off => "Off",
+            Self::Quiet => "Quiet",
+            Self::Medium => "Medium",
+            Self::Loud => "Loud",
+        }
+    }
+
+    pub fn volume(self) -> f32 {
+        match self {
+            Self::Off => 0.0,
+            Self::Quiet => 0.25,
+            Self::Medium => 0.5,
+            Self::Loud => 1.0,
+        }
+    }
+}
+
+/// Plays a subtle page-turn sound on X-Plane's UI audio bus whenever a different hint is shown,
+/// so that paging with hardware buttons, for example in VR, can be heard. It is off by default.
+pub struct PageTurnSound {
+    app: Rc<RefCell<Hints>>,
+    shown: Option<(usize, usize)>,
+}
+
+impl PageTurnSound {
+    pub fn new(app: Rc<RefCell<Hints>>) -> Self {
+        PageTurnSound { app, shown: None }
+    }
+
+    /// Plays the sound if the current hint has changed since the last update, other than by the
+    /// hints being loaded or reloaded.
+    pub fn update(&mut self) {
+        let app = self.app.borrow();
+        let shown = (app.hint_count(), app.current_hint_index());
+        let previous = self.shown.replace(shown);
+        if matches!(previous, Some((count, idx)) if count == shown.0 && idx != shown.1) {
+            let volume = page_turn_volume();
+            if volume > 0.0 {
+                play_page_turn(volume);
+            }
+        }
+    }
+}
+
+pub fn page_turn_volume() -> f32 {
+    *VOLUME
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get_or_insert_with(|| load_settings::<SoundSettings>(SOUND_FILENAME).page_turn_volume)
+}
+
+pub fn set_page_turn_volume(volume: f32) {
+    VOLUME
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .replace(volume);
+    let settings = SoundSettings {
+        page_turn_volume: volume,
+    };
+    save_settings(SOUND_FILENAME, &settings, &SOUND_DOCS);
+}
+
+fn play_page_turn(volume: f32) {
+    let pcm = PAGE_TURN_PCM.get_or_init(page_turn_pcm);
+    debug!(volume, "Playing page-turn sound");
+    let size = u32::try_from(std::mem::size_of_val(pcm.as_slice())).unwrap();
+    unsafe {
+        let channel = XPLMPlayPCMOnBus(
+            pcm.as_ptr().cast::<c_void>().cast_mut(),
+            size,
+            FMOD_SOUND_FORMAT_PCM16,
+            SAMPLE_RATE,
+            1,
+            0,
+            xplm_AudioUI as XPLMAudioBus,
+            None,
+            ptr::null_mut(),
+        );
+        if !channel.is_null() {
+            XPLMSetAudioVolume(channel, volume.clamp(0.0, 1.0));
+        }
+    }
+}
+
+/// A short burst of softened noise that rises quickly and dies away, like a page being turned.
+fn page_turn_pcm() -> Vec<i16> {
+    const ATTACK: f32 = 0.05;
+    const AMPLITUDE: f32 = 0.3;
+    const SMOOTHING: f32 = 0.7;
+    // a fixed xorshift generator, so that the sound is the same every time
+    let mut state: u32 = 0x9E37_79B9;
+    let mut filtered = 0.0;
+    (0..PAGE_TURN_SAMPLES)
+        .map(|sample| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            #[allow(clippy::cast_precision_loss)]
+            let noise = state as f32 / u32::MAX as f32 * 2.0 - 1.0;
+            filtered = filtered * SMOOTHING + noise * (1.0 - SMOOTHING);
+            #[allow(clippy::cast_precision_loss)]
+            let progress = sample as f32 / PAGE_TURN_SAMPLES as f32;
+            let envelope = if progress < ATTACK {
+                progress / ATTACK
+            } else {
+                (1.0 - (progress - ATTACK) / (1.0 - ATTACK)).powi(3)
+            };
+            to_sample(filtered * envelope * AMPLITUDE)
+        })
+        .collect()
+}
+
+/// Converts `value`, from -1.0 to 1.0, to a 16-bit sample.
+#[allow(clippy::cast_possible_truncation)]
+fn to_sample(value: f32) -> i16 {
+    (value.clamp(-1.0, 1.0) * f32::from(i16::MAX)) as i16
+}
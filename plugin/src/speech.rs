@@ -0,0 +1,21 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+use std::ffi::CString;
+
+use xplm_sys::XPLMSpeakString;
+
+use hints_common::Speaker;
+
+/// Speaks text with X-Plane's text-to-speech, which also shows it briefly on screen.
+pub struct SimSpeaker;
+
+impl Speaker for SimSpeaker {
+    fn speak(&self, text: &str) {
+        let text = CString::new(text.replace('\0', " ")).unwrap_or_default();
+        unsafe { XPLMSpeakString(text.as_ptr()) };
+    }
+}
@@ -0,0 +1,184 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tracing::info;
+use xplm::data::borrowed::DataRef;
+use xplm::data::DataRead;
+
+use crate::get_save_directory;
+use crate::settings::{read_toml, write_toml};
+use hints_common::TomlDocs;
+
+const STARTUP_FILENAME: &str = "startup.toml";
+const STARTUP_DOCS: TomlDocs = TomlDocs {
+    header: "How the hints window is opened when an aircraft is loaded.",
+    fields: StartupSettings::FIELD_DOCS,
+};
+
+/// Whether the hints window is opened when an aircraft is loaded.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StartupVisibility {
+    /// As it was when the window position was last saved.
+    #[default]
+    Saved,
+    Shown,
+    Hidden,
+}
+
+impl StartupVisibility {
+    pub const ALL: [StartupVisibility; 3] = [Self::Saved, Self::Shown, Self::Hidden];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Saved => "As saved with the window position",
+            Self::Shown => "Always show hints",
+            Self::Hidden => "Never show hints",
+        }
+    }
+
+    /// The visibility to give the window, or `None` to keep the saved visibility.
+    pub fn visible(self) -> Option<bool> {
+        match self {
+            Self::Saved => None,
+            Self::Shown => Some(true),
+            Self::Hidden => Some(false),
+        }
+    }
+}
+
+/// How long to wait after an aircraft is loaded before showing the hints window, set in
+/// `startup.toml`. Windows shown while X-Plane is still loading can be given the wrong geometry by
+/// some window managers.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct StartupDelay {
+    delay_seconds: f32,
+    until_unpaused: bool,
+}
+
+impl StartupDelay {
+    pub fn load() -> Self {
+        let settings = read_settings().unwrap_or_default();
+        StartupDelay {
+            delay_seconds: settings.delay_seconds,
+            until_unpaused: settings.until_unpaused,
+        }
+    }
+
+    /// Whether showing the window waits at all.
+    pub fn is_set(self) -> bool {
+        self.delay_seconds > 0.0 || self.until_unpaused
+    }
+
+    /// Whether the window can be shown, `elapsed` after the aircraft was loaded, while the sim is
+    /// `paused` or not.
+    pub fn is_over(self, elapsed: Duration, paused: bool) -> bool {
+        elapsed.as_secs_f32() >= self.delay_seconds && !(self.until_unpaused && paused)
+    }
+}
+
+/// Whether the sim is paused, with the dataref looked up once rather than on every frame of the
+/// wait.
+pub struct SimPaused(Option<DataRef<i32>>);
+
+impl SimPaused {
+    pub fn find() -> Self {
+        SimPaused(DataRef::find("sim/time/paused").ok())
+    }
+
+    pub fn get(&self) -> bool {
+        self.0.as_ref().is_some_and(|paused| paused.get() != 0)
+    }
+}
+
+hints_common::documented_struct! {
+    #[derive(Debug, Default, Serialize, Deserialize)]
+    #[serde(default)]
+    struct StartupSettings {
+        /// Seconds to wait after an aircraft is loaded before showing the hints window
+        delay_seconds: f32,
+        /// Whether to also wait until the sim is unpaused: true or false
+        until_unpaused: bool,
+        /// Whether the hints window is opened when each aircraft is loaded, keyed by aircraft:
+        /// "shown" (always open the window) or "hidden" (never open it). Aircraft without an entry
+        /// open the window as it was when its position was saved.
+        aircraft: BTreeMap<String, StartupVisibility>,
+    }
+}
+
+pub fn load_startup_visibility(aircraft_id: &str) -> StartupVisibility {
+    read_settings()
+        .and_then(|mut settings| settings.aircraft.remove(aircraft_id))
+        .unwrap_or_default()
+}
+
+pub fn save_startup_visibility(aircraft_id: &str, visibility: StartupVisibility) {
+    let filename = match get_startup_path() {
+        Some(filename) => filename,
+        None => return,
+    };
+    let mut settings = read_settings().unwrap_or_default();
+    if visibility == StartupVisibility::Saved {
+        settings.aircraft.remove(aircraft_id);
+    } else {
+        settings
+            .aircraft
+            .insert(aircraft_id.to_string(), visibility);
+    }
+    if write_toml(&filename, &settings, &STARTUP_DOCS) {
+        info!("Saved startup visibility {visibility:?} for {aircraft_id}");
+    }
+}
+
+fn read_settings() -> Option<StartupSettings> {
+    read_toml(&get_startup_path()?)
+}
+
+fn get_startup_path() -> Option<PathBuf> {
+    get_save_directory().map(|save_dir| save_dir.join(STARTUP_FILENAME))
+}
+
+#[cfg(all(test, feature = "mock-xplm"))]
+mod tests {
+    use std::time::Duration;
+
+    use super::StartupDelay;
+
+    #[test]
+    fn waits_for_the_delay() {
+        let delay = StartupDelay {
+            delay_seconds: 10.0,
+            until_unpaused: false,
+        };
+        assert!(delay.is_set());
+        assert!(!delay.is_over(Duration::from_secs(9), false));
+        assert!(delay.is_over(Duration::from_secs(10), false));
+        assert!(delay.is_over(Duration::from_secs(10), true));
+    }
+
+    #[test]
+    fn waits_until_unpaused() {
+        let delay = StartupDelay {
+            delay_seconds: 5.0,
+            until_unpaused: true,
+        };
+        assert!(!delay.is_over(Duration::from_secs(4), false));
+        assert!(!delay.is_over(Duration::from_secs(60), true));
+        assert!(delay.is_over(Duration::from_secs(60), false));
+    }
+
+    #[test]
+    fn shows_straight_away_without_a_delay() {
+        let delay = StartupDelay::default();
+        assert!(!delay.is_set());
+        assert!(delay.is_over(Duration::ZERO, true));
+    }
+}
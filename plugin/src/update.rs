@@ -0,0 +1,278 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+#[cfg(feature = "update")]
+use std::cell::RefCell;
+#[cfg(feature = "update")]
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+#[cfg(feature = "update")]
+use std::sync::mpsc::{channel, Receiver, TryRecvError};
+#[cfg(feature = "update")]
+use std::time::Duration;
+
+#[cfg(feature = "update")]
+use serde::Deserialize;
+#[cfg(feature = "update")]
+use tracing::{error, info};
+#[cfg(feature = "update")]
+use xplm::flight_loop::{FlightLoop, LoopState};
+use xplm::menu::Menu;
+#[cfg(feature = "update")]
+use xplm::menu::{ActionItem, MenuClickHandler};
+
+#[cfg(feature = "update")]
+use crate::charts::read_limited;
+#[cfg(feature = "update")]
+use crate::guard;
+#[cfg(feature = "update")]
+use crate::settings::{read_settings, settings_path};
+#[cfg(feature = "update")]
+use hints_common::parse_version;
+
+#[cfg(feature = "update")]
+const UPDATE_CONFIG_FILENAME: &str = "update.toml";
+#[cfg(feature = "update")]
+const STAGING_DIRNAME: &str = "update";
+#[cfg(feature = "update")]
+const TIMEOUT: Duration = Duration::from_secs(10);
+/// Downloads are much bigger than the manifest, so are given longer.
+#[cfg(feature = "update")]
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(300);
+/// Larger downloads are abandoned, as they are unlikely to be a build of the plugin.
+#[cfg(feature = "update")]
+const MAX_DOWNLOAD_BYTES: u64 = 256 * 1024 * 1024;
+#[cfg(feature = "update")]
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Checks in the background whether a newer build of the plugin has been released and, if so,
+/// adds an item to the menu that downloads it into a staging folder for the user to install.
+///
+/// Checking is only built with the `update` feature, and only enabled when `update.toml` in
+/// `Output/preferences/hints` gives the HTTPS URL of a manifest, for example
+/// `manifest = "https://example.com/hints/latest.toml"`. The manifest gives the latest `version`
+/// and the `url` of its download.
+pub struct UpdateCheck {
+    #[cfg(feature = "update")]
+    _flight_loop: FlightLoop,
+}
+
+#[cfg(feature = "update")]
+#[derive(Deserialize)]
+struct UpdateConfig {
+    manifest: String,
+}
+
+#[cfg(feature = "update")]
+#[derive(Debug, Clone, Deserialize)]
+struct Manifest {
+    version: String,
+    url: String,
+}
+
+/// How far the check has got, shared by the flight loop that polls the background threads and the
+/// menu item that starts the download.
+#[cfg(feature = "update")]
+enum Status {
+    Checking(Receiver<Option<Manifest>>),
+    Available(Manifest),
+    Downloading(Manifest, Receiver<Result<PathBuf, String>>),
+    Finished,
+}
+
+#[cfg(not(feature = "update"))]
+impl UpdateCheck {
+    pub fn start(_menu: &Rc<Menu>) -> Option<Self> {
+        None
+    }
+}
+
+#[cfg(feature = "update")]
+impl UpdateCheck {
+    pub fn start(menu: &Rc<Menu>) -> Option<Self> {
+        let config: UpdateConfig = read_settings(UPDATE_CONFIG_FILENAME)?;
+        if !config.manifest.starts_with("https://") {
+            error!(
+                "Not checking for updates, as {UPDATE_CONFIG_FILENAME} does not give an HTTPS URL"
+            );
+            return None;
+        }
+        let status = Rc::new(RefCell::new(Status::Checking(spawn(
+            "hints-update-check",
+            move || check(&config.manifest),
+        )?)));
+        let menu = Rc::clone(menu);
+        let mut item: Option<Rc<ActionItem>> = None;
+        let mut flight_loop = FlightLoop::new(move |state: &mut LoopState| {
+            guard("update check", || poll(&status, &menu, &mut item));
+            state.call_after(POLL_INTERVAL);
+        });
+        flight_loop.schedule_after(POLL_INTERVAL);
+        Some(UpdateCheck {
+            _flight_loop: flight_loop,
+        })
+    }
+}
+
+/// Moves the check on once a background thread has finished, adding or renaming the menu item.
+#[cfg(feature = "update")]
+fn poll(status: &Rc<RefCell<Status>>, menu: &Menu, item: &mut Option<Rc<ActionItem>>) {
+    let next = match &*status.borrow() {
+        Status::Checking(rx) => match rx.try_recv() {
+            Ok(Some(manifest)) => {
+                info!("Version {} of the plugin is available", manifest.version);
+                let new_item = Rc::new(
+                    ActionItem::new(
+                        &format!("Update available: {}", manifest.version),
+                        DownloadMenuClickHandler {
+                            status: Rc::clone(status),
+                        },
+                    )
+                    .expect("Unable to create update menu item"),
+                );
+                menu.add_child::<Rc<ActionItem>, ActionItem>(Rc::clone(&new_item));
+                *item = Some(new_item);
+                Status::Available(manifest)
+            }
+            Ok(None) | Err(TryRecvError::Disconnected) => Status::Finished,
+            Err(TryRecvError::Empty) => return,
+        },
+        Status::Downloading(manifest, rx) => {
+            let name = match rx.try_recv() {
+                Ok(Ok(path)) => {
+                    info!("Downloaded version {} to {path:?}", manifest.version);
+                    format!(
+                        "Update {} downloaded to hints/{STAGING_DIRNAME}",
+                        manifest.version
+                    )
+                }
+                Ok(Err(e)) => {
+                    error!("Unable to download version {}: {e}", manifest.version);
+                    String::from("Update download failed")
+                }
+                Err(TryRecvError::Disconnected) => String::from("Update download failed"),
+                Err(TryRecvError::Empty) => return,
+            };
+            if let Some(item) = item {
+                item.set_name(&name);
+            }
+            Status::Finished
+        }
+        Status::Available(_) | Status::Finished => return,
+    };
+    status.replace(next);
+}
+
+#[cfg(feature = "update")]
+struct DownloadMenuClickHandler {
+    status: Rc<RefCell<Status>>,
+}
+
+#[cfg(feature = "update")]
+impl MenuClickHandler for DownloadMenuClickHandler {
+    fn item_clicked(&mut self, item: &ActionItem) {
+        let manifest = match &*self.status.borrow() {
+            Status::Available(manifest) => manifest.clone(),
+            _ => return,
+        };
+        let url = manifest.url.clone();
+        // found here, as X-Plane can only be called from the main thread
+        let dir = settings_path(STAGING_DIRNAME);
+        if let Some(rx) = spawn("hints-update-download", move || download(&url, &dir)) {
+            item.set_name(&format!("Downloading update {}...", manifest.version));
+            self.status.replace(Status::Downloading(manifest, rx));
+        }
+    }
+}
+
+/// Runs `f` on a new thread, returning a receiver for its result.
+#[cfg(feature = "update")]
+fn spawn<T, F>(name: &str, f: F) -> Option<Receiver<T>>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let (tx, rx) = channel();
+    let spawned = std::thread::Builder::new()
+        .name(name.to_string())
+        .spawn(move || {
+            // the receiver has gone if the plugin was disabled meanwhile
+            let _ = tx.send(f());
+        });
+    match spawned {
+        Ok(_) => Some(rx),
+        Err(e) => {
+            error!("Unable to start {name} thread: {e}");
+            None
+        }
+    }
+}
+
+/// Fetches the manifest, returning it if it describes a newer version than this one.
+#[cfg(feature = "update")]
+fn check(manifest_url: &str) -> Option<Manifest> {
+    let result = ureq::AgentBuilder::new()
+        .timeout(TIMEOUT)
+        .build()
+        .get(manifest_url)
+        .call()
+        .map_err(|e| e.to_string())
+        .and_then(|response| response.into_string().map_err(|e| e.to_string()))
+        .and_then(|toml| toml::from_str::<Manifest>(&toml).map_err(|e| e.to_string()));
+    let manifest = match result {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            error!("Unable to check for updates: {e}");
+            return None;
+        }
+    };
+    let current = env!("CARGO_PKG_VERSION");
+    match (parse_version(&manifest.version), parse_version(current)) {
+        (Some(latest), Some(current)) if latest > current => {}
+        (Some(_), Some(_)) => {
+            info!("Version {current} of the plugin is the latest");
+            return None;
+        }
+        _ => {
+            error!(
+                "Ignoring update manifest with invalid version {:?}",
+                manifest.version
+            );
+            return None;
+        }
+    }
+    if !manifest.url.starts_with("https://") {
+        error!("Ignoring update manifest whose download is not an HTTPS URL");
+        return None;
+    }
+    Some(manifest)
+}
+
+/// Downloads `url` into the staging directory `dir`, named after the last part of its path, which
+/// must be a plain file name on every platform so that the download stays in `dir`.
+#[cfg(feature = "update")]
+fn download(url: &str, dir: &Path) -> Result<PathBuf, String> {
+    let name = url
+        .split(['?', '#'])
+        .next()
+        .and_then(|path| path.rsplit('/').next())
+        .filter(|name| {
+            !name.contains(['\\', ':'])
+                && Path::new(name).file_name() == Some(std::ffi::OsStr::new(name))
+        })
+        .ok_or_else(|| format!("{url} does not name a file"))?;
+    let response = ureq::AgentBuilder::new()
+        .timeout(DOWNLOAD_TIMEOUT)
+        .build()
+        .get(url)
+        .call()
+        .map_err(|e| e.to_string())?;
+    let body = read_limited(response.into_reader(), MAX_DOWNLOAD_BYTES)?;
+    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let path = dir.join(name);
+    std::fs::write(&path, body).map_err(|e| e.to_string())?;
+    Ok(path)
+}
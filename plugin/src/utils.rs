@@ -11,7 +11,10 @@ use std::path::PathBuf;
 use xplm::data::borrowed::DataRef;
 use xplm::data::StringRead;
 use xplm_sys::{
-    XPLMDebugString, XPLMExtractFileAndPath, XPLMGetNthAircraftModel, XPLMGetPrefsPath,
+    xplm_Language_Chinese, xplm_Language_English, xplm_Language_French, xplm_Language_German,
+    xplm_Language_Greek, xplm_Language_Italian, xplm_Language_Japanese, xplm_Language_Korean,
+    xplm_Language_Russian, xplm_Language_Spanish, XPLMDebugString, XPLMExtractFileAndPath,
+    XPLMGetLanguage, XPLMGetNthAircraftModel, XPLMGetPrefsPath, XPLMLanguageCode,
 };
 
 #[must_use]
@@ -54,6 +57,48 @@ pub fn get_current_aircraft_icao() -> Option<String> {
     }
 }
 
+/// The two-letter code of the language X-Plane is running in, such as `"de"`, or `None` if
+/// X-Plane doesn't know it.
+#[must_use]
+pub fn get_language() -> Option<&'static str> {
+    let languages = [
+        (xplm_Language_English, "en"),
+        (xplm_Language_French, "fr"),
+        (xplm_Language_German, "de"),
+        (xplm_Language_Italian, "it"),
+        (xplm_Language_Spanish, "es"),
+        (xplm_Language_Korean, "ko"),
+        (xplm_Language_Russian, "ru"),
+        (xplm_Language_Greek, "el"),
+        (xplm_Language_Japanese, "ja"),
+        (xplm_Language_Chinese, "zh"),
+    ];
+    let language = unsafe { XPLMGetLanguage() };
+    languages
+        .iter()
+        .find(|(code, _)| *code as XPLMLanguageCode == language)
+        .map(|(_, name)| *name)
+}
+
+/// Identifies the user's aircraft, so that a change of aircraft can be detected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadedAircraft {
+    path: PathBuf,
+    filename: PathBuf,
+    icao: Option<String>,
+}
+
+impl LoadedAircraft {
+    #[must_use]
+    pub fn current() -> Self {
+        LoadedAircraft {
+            path: get_current_aircraft_path(),
+            filename: get_current_aircraft_filename(),
+            icao: get_current_aircraft_icao(),
+        }
+    }
+}
+
 // from xplm
 fn read_to_buffer<F: Fn(*mut c_char)>(read_callback: F) -> String {
     let mut buffer = [0 as c_char; 512];
@@ -62,22 +107,125 @@ fn read_to_buffer<F: Fn(*mut c_char)>(read_callback: F) -> String {
     cstr.to_string_lossy().into_owned()
 }
 
-pub struct XplmWrite;
+const LOG_PREFIX: &[u8] = b"[FLC Hints] ";
+
+/// Writes to X-Plane's `Log.txt` a line at a time, prefixing each line with `[FLC Hints]` so that
+/// messages that span several lines can be told apart from other plugins' output. Any text after
+/// the last newline is written when the writer is flushed or dropped.
+#[derive(Default)]
+pub struct XplmWrite {
+    buffer: Vec<u8>,
+}
+
+impl XplmWrite {
+    fn write_line(line: &[u8]) {
+        let mut message = Vec::with_capacity(LOG_PREFIX.len() + line.len() + 1);
+        message.extend_from_slice(LOG_PREFIX);
+        // X-Plane stops at the first NUL, so replace any rather than losing the rest of the line
+        message.extend(line.iter().map(|&b| if b == 0 { b'?' } else { b }));
+        message.push(b'\n');
+        let c_str = CString::new(message).expect("NULs have been replaced");
+        unsafe {
+            XPLMDebugString(c_str.as_ptr());
+        }
+    }
+}
 
 impl Write for XplmWrite {
     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
-        match CString::new(buf) {
-            Ok(c_str) => unsafe {
-                XPLMDebugString(c_str.as_ptr());
-            },
-            Err(_) => unsafe {
-                XPLMDebugString("Invalid message\n\0".as_ptr().cast());
-            },
+        self.buffer.extend_from_slice(buf);
+        while let Some(end) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            Self::write_line(&line[..end]);
         }
         Ok(buf.len())
     }
 
     fn flush(&mut self) -> std::io::Result<()> {
+        if !self.buffer.is_empty() {
+            Self::write_line(&std::mem::take(&mut self.buffer));
+        }
         Ok(())
     }
 }
+
+impl Drop for XplmWrite {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+#[cfg(all(test, feature = "mock-xplm"))]
+mod tests {
+    use std::io::Write;
+    use std::path::PathBuf;
+
+    use crate::mock_xplm::{load_aircraft, set_language, set_prefs_path, take_log};
+
+    use super::{
+        get_current_aircraft_icao, get_language, get_prefs_path, LoadedAircraft, XplmWrite,
+    };
+
+    const C172: &str = "/X-Plane 12/Aircraft/Laminar Research/Cessna 172 SP/Cessna_172SP.acf";
+
+    #[test]
+    fn prefs_path_is_directory_of_prefs_file() {
+        set_prefs_path("/X-Plane 12/Output/preferences/X-Plane.prf");
+        assert_eq!(
+            get_prefs_path(),
+            PathBuf::from("/X-Plane 12/Output/preferences")
+        );
+    }
+
+    #[test]
+    fn icao_is_none_when_empty() {
+        load_aircraft(C172, "C172");
+        assert_eq!(get_current_aircraft_icao().as_deref(), Some("C172"));
+        load_aircraft(C172, "");
+        assert_eq!(get_current_aircraft_icao(), None);
+    }
+
+    #[test]
+    fn language_is_a_two_letter_code() {
+        set_language(xplm_sys::xplm_Language_German as xplm_sys::XPLMLanguageCode);
+        assert_eq!(get_language(), Some("de"));
+        set_language(xplm_sys::xplm_Language_Unknown as xplm_sys::XPLMLanguageCode);
+        assert_eq!(get_language(), None);
+    }
+
+    #[test]
+    fn reloading_same_aircraft_is_not_a_change() {
+        load_aircraft(C172, "C172");
+        let loaded = LoadedAircraft::current();
+        load_aircraft(C172, "C172");
+        assert_eq!(LoadedAircraft::current(), loaded);
+    }
+
+    #[test]
+    fn changing_aircraft_is_detected() {
+        load_aircraft(C172, "C172");
+        let loaded = LoadedAircraft::current();
+        load_aircraft(
+            "/X-Plane 12/Aircraft/Laminar Research/Cessna 172 SP/Cessna_172SP_G1000.acf",
+            "C172",
+        );
+        assert_ne!(LoadedAircraft::current(), loaded);
+        load_aircraft(
+            "/X-Plane 12/Aircraft/Laminar Research/Baron B58/Baron_58.acf",
+            "BE58",
+        );
+        assert_ne!(LoadedAircraft::current(), loaded);
+    }
+
+    #[test]
+    fn log_is_written_a_line_at_a_time() {
+        take_log();
+        {
+            let mut writer = XplmWrite::default();
+            writer.write_all(b"first\nsec").unwrap();
+            writer.write_all(b"ond\0line\nunfinished").unwrap();
+            assert_eq!(take_log(), "[FLC Hints] first\n[FLC Hints] second?line\n");
+        }
+        assert_eq!(take_log(), "[FLC Hints] unfinished\n");
+    }
+}
@@ -0,0 +1,153 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+use std::cell::Cell;
+use std::ffi::{c_int, c_void, CString};
+use std::rc::Rc;
+
+use tracing::debug;
+use xplm::data::borrowed::DataRef;
+use xplm::data::DataRead;
+use xplm_sys::{
+    xplm_CommandBegin, XPLMCommandPhase, XPLMCommandRef, XPLMFindCommand,
+    XPLMRegisterCommandHandler, XPLMUnregisterCommandHandler,
+};
+
+/// The number of quick looks X-Plane has, each with a `sim/view/quick_look_N` command.
+const QUICK_LOOKS: usize = 20;
+
+/// Follows which view the sim is showing, so that the hints window can be given the layout saved
+/// for it. X-Plane doesn't publish which quick look is shown, so the quick look commands are
+/// watched, and the view type dataref tells when the view has changed to one that isn't a quick
+/// look.
+pub struct ViewTracker {
+    view_type: Option<DataRef<i32>>,
+    last_view_type: Option<i32>,
+    /// The quick look chosen since the last check, if any.
+    chosen: Rc<Cell<Option<usize>>>,
+    /// The quick look last chosen, until the view type changes.
+    quick_look: Option<usize>,
+    handlers: Vec<(XPLMCommandRef, Box<QuickLookHandler>)>,
+    current: Option<String>,
+}
+
+struct QuickLookHandler {
+    number: usize,
+    chosen: Rc<Cell<Option<usize>>>,
+}
+
+impl ViewTracker {
+    pub fn new() -> Self {
+        let chosen = Rc::new(Cell::new(None));
+        let handlers = (0..QUICK_LOOKS)
+            .filter_map(|number| {
+                let name = CString::new(format!("sim/view/quick_look_{number}")).ok()?;
+                let command_ref = unsafe { XPLMFindCommand(name.as_ptr()) };
+                if command_ref.is_null() {
+                    return None;
+                }
+                let mut handler = Box::new(QuickLookHandler {
+                    number,
+                    chosen: Rc::clone(&chosen),
+                });
+                let refcon: *mut QuickLookHandler = &mut *handler;
+                unsafe {
+                    XPLMRegisterCommandHandler(
+                        command_ref,
+                        Some(quick_look_chosen),
+                        1,
+                        refcon.cast(),
+                    );
+                }
+                Some((command_ref, handler))
+            })
+            .collect();
+        ViewTracker {
+            view_type: DataRef::find("sim/graphics/view/view_type").ok(),
+            last_view_type: None,
+            chosen,
+            quick_look: None,
+            handlers,
+            current: None,
+        }
+    }
+
+    /// The view now shown, if it has changed since the last call.
+    pub fn changed(&mut self) -> Option<String> {
+        let view_type = self.view_type.as_ref().map(DataRead::get);
+        if view_type != self.last_view_type {
+            self.last_view_type = view_type;
+            self.quick_look = None;
+        }
+        // a quick look chosen from an outside view changes the view type too
+        if let Some(chosen) = self.chosen.take() {
+            self.quick_look = Some(chosen);
+        }
+        let view = view_name(view_type?, self.quick_look);
+        if self.current.as_ref() == Some(&view) {
+            return None;
+        }
+        debug!(view, "View changed");
+        self.current = Some(view.clone());
+        Some(view)
+    }
+
+    /// The view now shown, such as `quick_look_3`, or `None` before it is known.
+    pub fn current(&self) -> Option<&str> {
+        self.current.as_deref()
+    }
+}
+
+impl Drop for ViewTracker {
+    fn drop(&mut self) {
+        for (command_ref, handler) in &mut self.handlers {
+            let refcon: *mut QuickLookHandler = &mut **handler;
+            unsafe {
+                XPLMUnregisterCommandHandler(
+                    *command_ref,
+                    Some(quick_look_chosen),
+                    1,
+                    refcon.cast(),
+                );
+            }
+        }
+    }
+}
+
+unsafe extern "C" fn quick_look_chosen(
+    _command_ref: XPLMCommandRef,
+    phase: XPLMCommandPhase,
+    refcon: *mut c_void,
+) -> c_int {
+    #[allow(clippy::cast_possible_wrap)]
+    let begin = xplm_CommandBegin as XPLMCommandPhase;
+    if phase == begin {
+        let handler = &*refcon.cast::<QuickLookHandler>();
+        handler.chosen.set(Some(handler.number));
+    }
+    // let X-Plane go on to show the quick look
+    1
+}
+
+/// The name that window layouts are saved under for a view: the quick look, if one was chosen,
+/// or otherwise the view type, such as `view_type_1026` for the 3-D cockpit.
+fn view_name(view_type: i32, quick_look: Option<usize>) -> String {
+    match quick_look {
+        Some(number) => format!("quick_look_{number}"),
+        None => format!("view_type_{view_type}"),
+    }
+}
+
+#[cfg(all(test, feature = "mock-xplm"))]
+mod tests {
+    use super::view_name;
+
+    #[test]
+    fn names_views() {
+        assert_eq!(view_name(1026, Some(3)), "quick_look_3");
+        assert_eq!(view_name(1026, None), "view_type_1026");
+    }
+}
@@ -0,0 +1,193 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+//! Control of the viewer over stdin and stdout, so that other cockpit software, such as an
+//! instructor station, can run it as a child process. Each line on stdin is a JSON command such
+//! as `{"command": "go_to", "page": 3}`, and each line on stdout is a JSON event such as
+//! `{"event": "page", "page": 3, "count": 12, "name": "before-start.png"}`. Pages count from 1.
+//!
+//! The viewer exits when stdin is closed.
+
+use std::cell::{Cell, RefCell};
+use std::io::BufRead;
+use std::sync::mpsc::{channel, Receiver, TryRecvError};
+
+use imgui::Ui;
+use imgui_support::events::Event;
+use imgui_support::App;
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+
+use hints_common::{Hints, HintsEvent};
+
+use crate::window::close_window;
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+enum Command {
+    Next,
+    Previous,
+    #[serde(rename = "next_10")]
+    NextTen,
+    #[serde(rename = "previous_10")]
+    PreviousTen,
+    GoTo {
+        page: usize,
+    },
+    Reload,
+    ZoomIn,
+    ZoomOut,
+    ResetZoom,
+    ToggleCompare,
+    NextDisplayMode,
+    ToggleAccessibility,
+    NextStep,
+    /// Asks for a `page` event describing the current page, even if it hasn't changed.
+    Status,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum ControlEvent {
+    /// The current page or the number of pages has changed, or the status was asked for.
+    Page {
+        page: usize,
+        count: usize,
+        name: Option<String>,
+    },
+    /// A line on stdin was not a valid command.
+    Error { message: String },
+}
+
+impl Command {
+    fn hints_event(&self) -> Option<HintsEvent> {
+        match self {
+            Command::Next => Some(HintsEvent::NextHint),
+            Command::Previous => Some(HintsEvent::PreviousHint),
+            Command::NextTen => Some(HintsEvent::NextTenHints),
+            Command::PreviousTen => Some(HintsEvent::PreviousTenHints),
+            Command::GoTo { page } => page.checked_sub(1).map(HintsEvent::GoToHint),
+            Command::Reload => Some(HintsEvent::Reload),
+            Command::ZoomIn => Some(HintsEvent::ZoomIn),
+            Command::ZoomOut => Some(HintsEvent::ZoomOut),
+            Command::ResetZoom => Some(HintsEvent::ResetZoom),
+            Command::ToggleCompare => Some(HintsEvent::ToggleCompare),
+            Command::NextDisplayMode => Some(HintsEvent::NextDisplayMode),
+            Command::ToggleAccessibility => Some(HintsEvent::ToggleAccessibility),
+            Command::NextStep => Some(HintsEvent::NextFlowStep),
+            Command::Status => None,
+        }
+    }
+}
+
+/// The hints app, driven by commands read from stdin as well as by the window's input.
+pub struct ControlledHints {
+    hints: RefCell<Hints>,
+    commands: Receiver<Command>,
+    /// The last page event written, so that one is only written when something has changed.
+    last_page: RefCell<Option<ControlEvent>>,
+    /// Whether stdin has closed and the window has been asked to close.
+    closing: Cell<bool>,
+}
+
+impl ControlledHints {
+    /// Starts reading commands from stdin on a background thread.
+    pub fn new(hints: Hints) -> Self {
+        let (tx, rx) = channel();
+        std::thread::Builder::new()
+            .name(String::from("control"))
+            .spawn(move || {
+                for line in std::io::stdin().lock().lines() {
+                    let line = match line {
+                        Ok(line) => line,
+                        Err(e) => {
+                            error!("Unable to read command from stdin: {e}");
+                            break;
+                        }
+                    };
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    match serde_json::from_str::<Command>(&line) {
+                        Ok(command) => {
+                            if tx.send(command).is_err() {
+                                break;
+                            }
+                        }
+                        Err(e) => write_event(&ControlEvent::Error {
+                            message: format!("Invalid command {line:?}: {e}"),
+                        }),
+                    }
+                }
+            })
+            .expect("Unable to start control thread");
+        ControlledHints {
+            hints: RefCell::new(hints),
+            commands: rx,
+            last_page: RefCell::new(None),
+            closing: Cell::new(false),
+        }
+    }
+
+    /// Handles the commands received since the last frame.
+    fn handle_commands(&self) {
+        loop {
+            match self.commands.try_recv() {
+                Ok(command) => {
+                    if matches!(command, Command::Status) {
+                        self.last_page.take();
+                    }
+                    if let Some(event) = command.hints_event() {
+                        self.hints.borrow_mut().handle_hints_event(event);
+                    }
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    if !self.closing.replace(true) {
+                        info!("Stdin closed, exiting");
+                        close_window();
+                    }
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Writes a page event if the page has changed since the last one.
+    fn report_page(&self) {
+        let hints = self.hints.borrow();
+        let page = ControlEvent::Page {
+            page: hints.current_hint_index() + 1,
+            count: hints.hint_count(),
+            name: hints.current_hint_name(),
+        };
+        if self.last_page.borrow().as_ref() != Some(&page) {
+            write_event(&page);
+            self.last_page.replace(Some(page));
+        }
+    }
+}
+
+impl App for ControlledHints {
+    fn draw_ui(&self, ui: &Ui) {
+        self.handle_commands();
+        self.hints.borrow_mut().update();
+        self.hints.borrow().draw_ui(ui);
+        self.report_page();
+    }
+
+    fn handle_event(&mut self, event: Event) -> bool {
+        self.hints.get_mut().handle_event(event)
+    }
+}
+
+fn write_event(event: &ControlEvent) {
+    match serde_json::to_string(event) {
+        // stdout is line buffered, so each event is flushed as it is written
+        Ok(json) => println!("{json}"),
+        Err(e) => error!("Unable to write event: {e}"),
+    }
+}
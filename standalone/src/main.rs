@@ -8,9 +8,15 @@
 #![warn(clippy::pedantic)]
 #![allow(clippy::missing_panics_doc)]
 
+use std::cell::RefCell;
+use std::io::Write;
 use std::path::PathBuf;
+use std::process::ExitCode;
 
-use glfw::fail_on_errors;
+use imgui::Ui;
+use imgui_support::events::Event;
+use imgui_support::App;
+use tracing::{error, warn};
 use tracing_subscriber::layer::SubscriberExt;
 
 use hints_common::logging::{env_filter, layer};
@@ -19,16 +25,80 @@ use hints_common::{
     TITLE, WIDTH,
 };
 
-fn main() {
-    let stdout_layer = layer(false, None);
+use crate::control::ControlledHints;
+use crate::window::{set_window_icon, WindowOptions};
+
+mod control;
+mod window;
+
+const VIEWER_USAGE: &str = "Usage: hints-standalone <dir> [--control stdio] [--gl 3.3,2.1] \
+                            [--msaa SAMPLES] [--vsync on|off|adaptive] [--x11]";
+const RENDER_USAGE: &str = "Usage: hints-standalone render <dir> --page N --size WxH -o out.png";
+const DEFAULT_RENDER_SIZE: [u32; 2] = [WIDTH, HEIGHT];
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("render") {
+        init_logging(false);
+        return render(&args[1..]);
+    }
+    let args = match parse_viewer_args(&args) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{e}\n{VIEWER_USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    // before any threads are started, as it changes the environment
+    args.window.select_platform();
+    init_logging(args.controlled);
+
+    let hints = Hints::new(args.dir).expect("Unable to create Hints app");
+    if args.controlled {
+        run_window(ControlledHints::new(hints), &args.window);
+    } else {
+        run_window(UpdatedHints(RefCell::new(hints)), &args.window);
+    }
+    ExitCode::SUCCESS
+}
+
+/// The hints app, updated before each frame is drawn, as the plugin does from its flight loop.
+struct UpdatedHints(RefCell<Hints>);
+
+impl App for UpdatedHints {
+    fn draw_ui(&self, ui: &Ui) {
+        self.0.borrow_mut().update();
+        self.0.borrow().draw_ui(ui);
+    }
+
+    fn handle_event(&mut self, event: Event) -> bool {
+        self.0.get_mut().handle_event(event)
+    }
+}
+
+/// Logs to stdout, or to stderr when stdout carries the events of `--control stdio`.
+fn init_logging(to_stderr: bool) {
+    let log_layer = layer(false, None).with_writer(move || -> Box<dyn Write> {
+        if to_stderr {
+            Box::new(std::io::stderr())
+        } else {
+            Box::new(std::io::stdout())
+        }
+    });
     let filter = env_filter(Some(LOGGING_ENV_VAR));
-    let subscriber = tracing_subscriber::registry()
-        .with(filter)
-        .with(stdout_layer);
+    let subscriber = tracing_subscriber::registry().with(filter).with(log_layer);
     tracing::subscriber::set_global_default(subscriber).expect("Could not set global default");
+}
 
-    let mut glfw = glfw::init(fail_on_errors!()).expect("GLFW failed to init");
-    glfw.window_hint(glfw::WindowHint::ContextVersion(2, 1));
+fn run_window<A: App + 'static>(app: A, options: &WindowOptions) {
+    // errors are logged rather than failing, as creating a context for an unsupported OpenGL
+    // version is expected while finding one that works, and some platforms can't set an icon
+    let mut glfw = glfw::init(|_, description| warn!("GLFW error: {description}"))
+        .expect("GLFW failed to init");
+    if let Err(e) = options.apply_hints(&mut glfw) {
+        error!("{e}");
+        return;
+    }
 
     let bounds = imgui_support_standalone::get_screen_bounds(&mut glfw);
     let horiz_offset = get_offset_from_edge(bounds.width(), FROM_EDGE_PROPORTION, FROM_EDGE_MIN);
@@ -36,24 +106,134 @@ fn main() {
     #[allow(clippy::cast_possible_wrap, clippy::cast_sign_loss)]
     {
         let mut system = imgui_support_standalone::init(
-            glfw,
+            glfw.clone(),
             TITLE,
             bounds.width() - horiz_offset - WIDTH,
             vert_offset + FROM_EDGE_MIN as i32 as u32,
             WIDTH,
             HEIGHT,
-            Hints::new(get_path()).expect("Unable to create Hints app"),
+            app,
         );
+        // the window's context is current once it has been created
+        options.apply_vsync(&mut glfw);
+        set_window_icon();
         system.main_loop();
     }
 }
 
-fn get_path() -> PathBuf {
-    let args: Vec<String> = std::env::args().collect();
-    assert_eq!(
-        args.len(),
-        2,
-        "Expected exactly one argument: path to config file"
-    );
-    PathBuf::from(&args[1])
+/// The arguments of the viewer.
+struct ViewerArgs {
+    dir: PathBuf,
+    /// Whether the viewer is controlled over stdin and stdout, with `--control stdio`.
+    controlled: bool,
+    window: WindowOptions,
+}
+
+fn parse_viewer_args(args: &[String]) -> Result<ViewerArgs, String> {
+    let mut dir = None;
+    let mut controlled = false;
+    let mut window = WindowOptions::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if !arg.starts_with('-') {
+            if dir.is_some() {
+                return Err(format!("Unexpected argument {arg}"));
+            }
+            dir = Some(PathBuf::from(arg));
+            continue;
+        }
+        if window.set_flag(arg) {
+            continue;
+        }
+        let value = args
+            .next()
+            .ok_or_else(|| format!("Expected a value after {arg}"))?;
+        match arg.as_str() {
+            "--control" if value == "stdio" => controlled = true,
+            "--control" => return Err(format!("Unknown control mode {value}")),
+            _ => {
+                if !window.set(arg, value)? {
+                    return Err(format!("Unknown option {arg}"));
+                }
+            }
+        }
+    }
+    Ok(ViewerArgs {
+        dir: dir.ok_or("Expected the hints directory")?,
+        controlled,
+        window,
+    })
+}
+
+/// The arguments of the `render` mode, which renders a hint to an image without a window.
+struct RenderArgs {
+    dir: PathBuf,
+    page: usize,
+    size: [u32; 2],
+    output: PathBuf,
+}
+
+fn parse_render_args(args: &[String]) -> Result<RenderArgs, String> {
+    let mut dir = None;
+    let mut page = 1;
+    let mut size = DEFAULT_RENDER_SIZE;
+    let mut output = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("Expected a value after {arg}"))
+        };
+        match arg.as_str() {
+            "--page" => {
+                page = value()?
+                    .parse()
+                    .map_err(|_| format!("{arg} must be a page number from 1"))?;
+            }
+            "--size" => size = parse_size(value()?)?,
+            "-o" | "--output" => output = Some(PathBuf::from(value()?)),
+            _ if arg.starts_with('-') => return Err(format!("Unknown option {arg}")),
+            _ if dir.is_none() => dir = Some(PathBuf::from(arg)),
+            _ => return Err(format!("Unexpected argument {arg}")),
+        }
+    }
+    Ok(RenderArgs {
+        dir: dir.ok_or("Expected the hints directory")?,
+        page,
+        size,
+        output: output.ok_or("Expected an output file with -o")?,
+    })
+}
+
+/// Parses a size such as `800x600`.
+fn parse_size(size: &str) -> Result<[u32; 2], String> {
+    let invalid = || format!("{size:?} is not a size such as 800x600");
+    let (width, height) = size.split_once('x').ok_or_else(invalid)?;
+    let width: u32 = width.parse().map_err(|_| invalid())?;
+    let height: u32 = height.parse().map_err(|_| invalid())?;
+    if width == 0 || height == 0 {
+        return Err(invalid());
+    }
+    Ok([width, height])
+}
+
+/// Renders a hint to an image file, for documentation or Stream Deck keys, without opening a
+/// window.
+fn render(args: &[String]) -> ExitCode {
+    let args = match parse_render_args(args) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{e}\n{RENDER_USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    match Hints::new(args.dir)
+        .and_then(|hints| hints.save_snapshot(args.page, args.size, &args.output))
+    {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error {}: {e}", e.code());
+            ExitCode::FAILURE
+        }
+    }
 }
@@ -0,0 +1,251 @@
+/*
+ * Copyright (c) 2023 Flight Level Change Ltd.
+ *
+ * All rights reserved.
+ */
+
+use std::ffi::c_int;
+
+use glfw::{ffi, Glfw, SwapInterval, WindowHint, WindowMode};
+use tracing::{debug, info, warn};
+
+/// The OpenGL versions tried in turn when no others are given. 2.1 is what the renderer is
+/// written for, and the later versions are for drivers that don't offer it.
+const DEFAULT_GL_VERSIONS: [(u32, u32); 3] = [(2, 1), (3, 0), (3, 3)];
+/// The window class on X11, which desktops use to match the window with its launcher and icon.
+const X11_CLASS_NAME: &str = "flc-hints";
+const X11_INSTANCE_NAME: &str = "hints";
+/// The sizes of the window icon, from which the desktop picks the closest to the size it shows.
+const ICON_SIZES: [u32; 3] = [16, 32, 64];
+const ICON_BACKGROUND: [u8; 4] = [24, 64, 128, 255];
+const ICON_PAGE: [u8; 4] = [240, 240, 240, 255];
+const ICON_LINE: [u8; 4] = [120, 120, 120, 255];
+
+/// How the viewer's window and OpenGL context are created, set with `--gl`, `--msaa` and
+/// `--vsync`.
+#[derive(Debug)]
+pub struct WindowOptions {
+    /// The OpenGL versions to request, in order of preference.
+    gl_versions: Vec<(u32, u32)>,
+    /// The number of samples for multisample anti-aliasing, if it is on.
+    samples: Option<u32>,
+    /// The swap interval, if it is not to be left to the driver.
+    vsync: Option<SwapInterval>,
+    /// Whether to use X11 rather than Wayland, with `--x11`.
+    x11: bool,
+}
+
+impl Default for WindowOptions {
+    fn default() -> Self {
+        WindowOptions {
+            gl_versions: DEFAULT_GL_VERSIONS.to_vec(),
+            samples: None,
+            vsync: None,
+            x11: false,
+        }
+    }
+}
+
+impl WindowOptions {
+    /// Sets the option `arg` to `value`, returning `false` if `arg` is not a window option.
+    pub fn set(&mut self, arg: &str, value: &str) -> Result<bool, String> {
+        match arg {
+            "--gl" => self.gl_versions = parse_gl_versions(value)?,
+            "--msaa" => {
+                let samples: u32 = value.parse().map_err(|_| {
+                    String::from("--msaa must be a number of samples, or 0 for off")
+                })?;
+                self.samples = Some(samples).filter(|samples| *samples > 0);
+            }
+            "--vsync" => self.vsync = Some(parse_vsync(value)?),
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// Sets the option `arg`, which takes no value, returning `false` if it is not a window flag.
+    pub fn set_flag(&mut self, arg: &str) -> bool {
+        match arg {
+            "--x11" => self.x11 = true,
+            _ => return false,
+        }
+        true
+    }
+
+    /// Makes GLFW use X11 (or XWayland) rather than Wayland, if `--x11` was given, for desktops
+    /// where the Wayland window is drawn wrongly. GLFW uses Wayland when it finds a Wayland
+    /// display, so the display is hidden from it.
+    ///
+    /// This changes the environment, so must be called before any threads are started.
+    pub fn select_platform(&self) {
+        if self.x11 {
+            std::env::remove_var("WAYLAND_DISPLAY");
+            std::env::set_var("XDG_SESSION_TYPE", "x11");
+        }
+    }
+
+    /// Sets the window hints for the first of the OpenGL versions that a context can be created
+    /// for, by creating a hidden window with each in turn. If none can be created with
+    /// anti-aliasing, they are tried again without it.
+    pub fn apply_hints(&self, glfw: &mut Glfw) -> Result<(), String> {
+        let mut samples = self.samples;
+        let version = loop {
+            if let Some(version) = self.find_gl_version(glfw, samples) {
+                break version;
+            }
+            if samples.take().is_some() {
+                warn!("Unable to create an OpenGL context with MSAA, trying without it");
+            } else {
+                return Err(format!(
+                    "Unable to create an OpenGL context with any of versions {}",
+                    format_gl_versions(&self.gl_versions)
+                ));
+            }
+        };
+        info!(
+            version = %format_gl_versions(&[version]),
+            ?samples,
+            "Creating OpenGL context"
+        );
+        glfw.window_hint(WindowHint::Visible(true));
+        glfw.window_hint(WindowHint::ContextVersion(version.0, version.1));
+        glfw.window_hint(WindowHint::Samples(samples));
+        glfw.window_hint(WindowHint::X11ClassName(Some(X11_CLASS_NAME.to_string())));
+        glfw.window_hint(WindowHint::X11InstanceName(Some(
+            X11_INSTANCE_NAME.to_string(),
+        )));
+        // sizes the window for the monitor's scale, so that it isn't tiny on high DPI monitors
+        glfw.window_hint(WindowHint::ScaleToMonitor(true));
+        Ok(())
+    }
+
+    fn find_gl_version(&self, glfw: &mut Glfw, samples: Option<u32>) -> Option<(u32, u32)> {
+        glfw.window_hint(WindowHint::Visible(false));
+        glfw.window_hint(WindowHint::Samples(samples));
+        self.gl_versions.iter().copied().find(|&(major, minor)| {
+            glfw.window_hint(WindowHint::ContextVersion(major, minor));
+            // the window is closed as soon as it is dropped
+            let created = glfw.create_window(1, 1, "", WindowMode::Windowed).is_some();
+            if !created {
+                warn!("Unable to create an OpenGL {major}.{minor} context");
+            }
+            created
+        })
+    }
+
+    /// Sets the swap interval of the current context, if one was given.
+    pub fn apply_vsync(&self, glfw: &mut Glfw) {
+        if let Some(vsync) = self.vsync {
+            info!(?vsync, "Setting swap interval");
+            glfw.set_swap_interval(vsync);
+        }
+    }
+}
+
+/// Sets the icon of the window whose context is current, and logs its content scale.
+pub fn set_window_icon() {
+    let icons: Vec<(u32, Vec<u8>)> = ICON_SIZES
+        .iter()
+        .map(|&size| (size, draw_icon(size)))
+        .collect();
+    let mut images = vec![];
+    for (size, pixels) in &icons {
+        match c_int::try_from(*size) {
+            Ok(size) => images.push(ffi::GLFWimage {
+                width: size,
+                height: size,
+                pixels: pixels.as_ptr(),
+            }),
+            Err(e) => warn!("Unable to use icon of size {size}: {e}"),
+        }
+    }
+    // GLFW copies the images, so they only need to live until it returns
+    unsafe {
+        let window = ffi::glfwGetCurrentContext();
+        if window.is_null() {
+            warn!("No window to set the icon of");
+            return;
+        }
+        if let Ok(count) = c_int::try_from(images.len()) {
+            ffi::glfwSetWindowIcon(window, count, images.as_ptr());
+        }
+        let (mut x_scale, mut y_scale) = (1.0_f32, 1.0_f32);
+        ffi::glfwGetWindowContentScale(window, &mut x_scale, &mut y_scale);
+        debug!(x_scale, y_scale, "Window content scale");
+    }
+}
+
+/// Asks the main loop to close the window whose context is current, once the frame being drawn
+/// is finished, so that the viewer exits as it does when the window is closed.
+pub fn close_window() {
+    unsafe {
+        let window = ffi::glfwGetCurrentContext();
+        if window.is_null() {
+            warn!("No window to close");
+            return;
+        }
+        ffi::glfwSetWindowShouldClose(window, ffi::TRUE);
+    }
+}
+
+/// Draws the icon, a page of text on a blue background, as RGBA pixels of `size` square.
+fn draw_icon(size: u32) -> Vec<u8> {
+    let margin = size / 8;
+    let line_height = (size / 8).max(2);
+    let mut pixels = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            let on_page = (margin * 2..size - margin * 2).contains(&x)
+                && (margin..size - margin).contains(&y);
+            let on_line = on_page
+                && x >= margin * 3
+                && x < size - margin * 3
+                && y >= margin * 2
+                && y < size - margin * 2
+                && (y - margin * 2) % line_height == 0;
+            let colour = if on_line {
+                ICON_LINE
+            } else if on_page {
+                ICON_PAGE
+            } else {
+                ICON_BACKGROUND
+            };
+            pixels.extend_from_slice(&colour);
+        }
+    }
+    pixels
+}
+
+/// Parses a list of OpenGL versions such as `3.3,2.1`.
+fn parse_gl_versions(versions: &str) -> Result<Vec<(u32, u32)>, String> {
+    versions
+        .split(',')
+        .map(|version| {
+            let invalid = || format!("{version:?} is not an OpenGL version such as 3.3");
+            let (major, minor) = version.trim().split_once('.').ok_or_else(invalid)?;
+            Ok((
+                major.parse().map_err(|_| invalid())?,
+                minor.parse().map_err(|_| invalid())?,
+            ))
+        })
+        .collect()
+}
+
+fn parse_vsync(vsync: &str) -> Result<SwapInterval, String> {
+    match vsync {
+        "on" => Ok(SwapInterval::Sync(1)),
+        "off" => Ok(SwapInterval::None),
+        "adaptive" => Ok(SwapInterval::Adaptive),
+        _ => Err(format!(
+            "{vsync:?} is not a vsync setting: use on, off or adaptive"
+        )),
+    }
+}
+
+fn format_gl_versions(versions: &[(u32, u32)]) -> String {
+    versions
+        .iter()
+        .map(|(major, minor)| format!("{major}.{minor}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}